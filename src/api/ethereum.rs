@@ -6,13 +6,25 @@ use crate::api::ethereum::responses::{
     EthereumAccountResponse, EthereumAccountsResponse, EthereumSignTransactionResponse,
 };
 
+use crate::audit::{AuditOperation, AuditScope, AuditSink};
+use crate::policy::PolicyEngine;
+use crate::retry::RetryPolicy;
+use futures::stream::{self, Stream, StreamExt};
 use vaultrs::client::Client;
 use vaultrs::error::ClientError;
 use web3::types::{Address, TransactionRequest};
 
+use self::filler::{FillProvider, TransactionFiller};
+use self::nonce::{NonceManager, NonceSource};
+use self::replacement::{
+    cancellation_transaction, decode_signed_transaction, replacement_transaction,
+};
 use self::requests::EthereumSignRequest;
 use self::responses::EthereumSignResponse;
 
+pub mod filler;
+pub mod nonce;
+pub mod replacement;
 pub mod requests;
 pub mod responses;
 
@@ -32,40 +44,82 @@ pub async fn create_account(
         .map_err(Into::into)
 }
 
-/// List Ethereum accounts.
+/// List Ethereum accounts, retrying transient failures with the default [RetryPolicy].
 ///
 /// See [ListEthereumAccountsRequest]
 pub async fn list_accounts(
     client: &impl Client,
     mount: &str,
 ) -> Result<EthereumAccountsResponse, ClientError> {
-    let request = ListEthereumAccountsRequest::builder()
-        .mount(mount)
-        .build()
-        .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    list_accounts_with_retry(client, mount, &RetryPolicy::default()).await
+}
+
+/// Same as [list_accounts], retrying with `policy`.
+pub async fn list_accounts_with_retry(
+    client: &impl Client,
+    mount: &str,
+    policy: &RetryPolicy,
+) -> Result<EthereumAccountsResponse, ClientError> {
+    policy
+        .run_vault(|| {
+            let request = ListEthereumAccountsRequest::builder()
+                .mount(mount)
+                .build()
+                .unwrap();
+            vaultrs::api::exec_with_result(client, request)
+        })
         .await
-        .map_err(Into::into)
 }
 
-/// Read an Ethereum account.
+/// Stream the details of every Ethereum account, reading at most `concurrency` accounts at
+/// a time. A failed read is yielded as an error without ending the stream.
+/// See [ListEthereumAccountsRequest] and [ReadEthereumAccountRequest]
+pub fn stream_accounts<'a>(
+    client: &'a impl Client,
+    mount: &'a str,
+    concurrency: usize,
+) -> impl Stream<Item = Result<EthereumAccountResponse, ClientError>> + 'a {
+    stream::once(list_accounts(client, mount))
+        .map(move |list| match list {
+            Ok(list) => stream::iter(list.keys)
+                .map(move |address| read_account(client, mount, address))
+                .buffered(concurrency.max(1))
+                .left_stream(),
+            Err(e) => stream::once(async { Err(e) }).right_stream(),
+        })
+        .flatten()
+}
+
+/// Read an Ethereum account, retrying transient failures with the default [RetryPolicy].
 ///
 /// See [ReadEthereumAccountRequest]
 pub async fn read_account(
     client: &impl Client,
     mount: &str,
     address: Address,
+) -> Result<EthereumAccountResponse, ClientError> {
+    read_account_with_retry(client, mount, address, &RetryPolicy::default()).await
+}
+
+/// Same as [read_account], retrying with `policy`.
+pub async fn read_account_with_retry(
+    client: &impl Client,
+    mount: &str,
+    address: Address,
+    policy: &RetryPolicy,
 ) -> Result<EthereumAccountResponse, ClientError> {
     let address = format!("{address:?}");
     let checksummed = eth_checksum::checksum(&address);
-    let request = ReadEthereumAccountRequest::builder()
-        .mount(mount)
-        .address(checksummed)
-        .build()
-        .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    policy
+        .run_vault(|| {
+            let request = ReadEthereumAccountRequest::builder()
+                .mount(mount)
+                .address(checksummed.clone())
+                .build()
+                .unwrap();
+            vaultrs::api::exec_with_result(client, request)
+        })
         .await
-        .map_err(Into::into)
 }
 
 /// Sign an Ethereum transaction.
@@ -76,6 +130,17 @@ pub async fn sign_transaction(
     mount: &str,
     chain_id: u64,
     transaction: TransactionRequest,
+) -> Result<EthereumSignTransactionResponse, ClientError> {
+    sign_transaction_audited(client, mount, chain_id, transaction, None).await
+}
+
+/// Same as [sign_transaction], reporting the signature to `audit`.
+pub async fn sign_transaction_audited(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: TransactionRequest,
+    audit: Option<&dyn AuditSink>,
 ) -> Result<EthereumSignTransactionResponse, ClientError> {
     let address = format!("{:?}", transaction.from);
     let checksummed = eth_checksum::checksum(&address);
@@ -91,11 +156,256 @@ pub async fn sign_transaction(
         .data(transaction.data.unwrap_or_default())
         .build()
         .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    let scope = AuditScope::start(
+        audit,
+        AuditOperation::EthereumSignTransaction,
+        mount,
+        &request.address,
+        &request.data.0,
+    )
+    .map(|scope| scope.with_transaction(&request));
+    let result = vaultrs::api::exec_with_result(client, request).await;
+    if let Some(scope) = scope {
+        scope.finish(result.as_ref().map(|r| r.signature.as_str()));
+    }
+    result
+}
+
+/// Sign an Ethereum transaction after checking it against the [PolicyEngine].
+///
+/// See [SignEthereumTransactionRequest]
+pub async fn sign_transaction_with_policy(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: TransactionRequest,
+    policy: &PolicyEngine,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    sign_transaction_with_policy_audited(client, mount, chain_id, transaction, policy, None).await
+}
+
+/// Same as [sign_transaction_with_policy], reporting the signature to `audit`.
+pub async fn sign_transaction_with_policy_audited(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: TransactionRequest,
+    policy: &PolicyEngine,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    let reservation = policy.check_transaction(chain_id, &transaction)?;
+    let result = sign_transaction_audited(client, mount, chain_id, transaction, audit).await;
+    if result.is_err() {
+        policy.release(reservation);
+    }
+    result.map_err(Into::into)
+}
+
+/// Sign an Ethereum transaction, reserving its nonce from the [NonceManager] when the
+/// transaction has none set.
+///
+/// The reserved nonce is released when signing fails. If the signed transaction is never
+/// broadcast, call [NonceManager::resync] for the sender so the nonce is not skipped.
+///
+/// See [SignEthereumTransactionRequest]
+pub async fn sign_transaction_with_nonce_manager<S: NonceSource>(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: TransactionRequest,
+    nonce_manager: &NonceManager<S>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    sign_transaction_with_nonce_manager_audited(
+        client,
+        mount,
+        chain_id,
+        transaction,
+        nonce_manager,
+        None,
+    )
+    .await
+}
+
+/// Same as [sign_transaction_with_nonce_manager], reporting the signature to `audit`.
+pub async fn sign_transaction_with_nonce_manager_audited<S: NonceSource>(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    mut transaction: TransactionRequest,
+    nonce_manager: &NonceManager<S>,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    let from = transaction.from;
+    let reserved = match transaction.nonce {
+        Some(_) => None,
+        None => Some(nonce_manager.next(chain_id, from).await?),
+    };
+    transaction.nonce = transaction.nonce.or(reserved);
+    let result = sign_transaction_audited(client, mount, chain_id, transaction, audit).await;
+    if let (Err(_), Some(nonce)) = (&result, reserved) {
+        nonce_manager.release(chain_id, from, nonce).await;
+    }
+    result.map_err(Into::into)
+}
+
+/// Sign an Ethereum transaction, populating its missing fields with the [TransactionFiller]
+/// first. The nonce reserved while filling is released when signing fails.
+///
+/// See [SignEthereumTransactionRequest]
+pub async fn sign_transaction_with_filler<P: FillProvider>(
+    client: &impl Client,
+    mount: &str,
+    transaction: TransactionRequest,
+    filler: &TransactionFiller<P>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    sign_transaction_with_filler_audited(client, mount, transaction, filler, None).await
+}
+
+/// Same as [sign_transaction_with_filler], reporting the signature to `audit`.
+pub async fn sign_transaction_with_filler_audited<P: FillProvider>(
+    client: &impl Client,
+    mount: &str,
+    transaction: TransactionRequest,
+    filler: &TransactionFiller<P>,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    let filled = filler.fill(transaction).await?;
+    let from = filled.transaction.from;
+    let result =
+        sign_transaction_audited(client, mount, filled.chain_id, filled.transaction, audit).await;
+    if let (Err(_), Some(nonce)) = (&result, filled.reserved_nonce) {
+        filler
+            .nonce_manager()
+            .release(filled.chain_id, from, nonce)
+            .await;
+    }
+    result.map_err(Into::into)
+}
+
+/// Sign a transaction replacing `transaction`, with the same nonce and the gas price
+/// bumped by `bump_percent`.
+///
+/// See [replacement_transaction]
+pub async fn speed_up_transaction(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: &TransactionRequest,
+    bump_percent: u64,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    speed_up_transaction_audited(client, mount, chain_id, transaction, bump_percent, None).await
+}
+
+/// Same as [speed_up_transaction], reporting the signature to `audit`.
+pub async fn speed_up_transaction_audited(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: &TransactionRequest,
+    bump_percent: u64,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    let replacement = replacement_transaction(transaction, bump_percent)?;
+    sign_transaction_audited(client, mount, chain_id, replacement, audit)
+        .await
+        .map_err(Into::into)
+}
+
+/// Sign a zero-value self-transfer cancelling `transaction`, with the same nonce and the
+/// gas price bumped by `bump_percent`.
+///
+/// See [cancellation_transaction]
+pub async fn cancel_transaction(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: &TransactionRequest,
+    bump_percent: u64,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    cancel_transaction_audited(client, mount, chain_id, transaction, bump_percent, None).await
+}
+
+/// Same as [cancel_transaction], reporting the signature to `audit`.
+pub async fn cancel_transaction_audited(
+    client: &impl Client,
+    mount: &str,
+    chain_id: u64,
+    transaction: &TransactionRequest,
+    bump_percent: u64,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    let cancellation = cancellation_transaction(transaction, bump_percent)?;
+    sign_transaction_audited(client, mount, chain_id, cancellation, audit)
         .await
         .map_err(Into::into)
 }
 
+/// Sign a transaction replacing the raw signed legacy transaction, with the same nonce
+/// and the gas price bumped by `bump_percent`.
+///
+/// See [decode_signed_transaction]
+pub async fn speed_up_signed_transaction(
+    client: &impl Client,
+    mount: &str,
+    raw_transaction: &[u8],
+    bump_percent: u64,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    speed_up_signed_transaction_audited(client, mount, raw_transaction, bump_percent, None).await
+}
+
+/// Same as [speed_up_signed_transaction], reporting the signature to `audit`.
+pub async fn speed_up_signed_transaction_audited(
+    client: &impl Client,
+    mount: &str,
+    raw_transaction: &[u8],
+    bump_percent: u64,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    let signed = decode_signed_transaction(raw_transaction)?;
+    speed_up_transaction_audited(
+        client,
+        mount,
+        signed.chain_id,
+        &signed.transaction,
+        bump_percent,
+        audit,
+    )
+    .await
+}
+
+/// Sign a zero-value self-transfer cancelling the raw signed legacy transaction, with
+/// the same nonce and the gas price bumped by `bump_percent`.
+///
+/// See [decode_signed_transaction]
+pub async fn cancel_signed_transaction(
+    client: &impl Client,
+    mount: &str,
+    raw_transaction: &[u8],
+    bump_percent: u64,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    cancel_signed_transaction_audited(client, mount, raw_transaction, bump_percent, None).await
+}
+
+/// Same as [cancel_signed_transaction], reporting the signature to `audit`.
+pub async fn cancel_signed_transaction_audited(
+    client: &impl Client,
+    mount: &str,
+    raw_transaction: &[u8],
+    bump_percent: u64,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignTransactionResponse, crate::error::ClientError> {
+    let signed = decode_signed_transaction(raw_transaction)?;
+    cancel_transaction_audited(
+        client,
+        mount,
+        signed.chain_id,
+        &signed.transaction,
+        bump_percent,
+        audit,
+    )
+    .await
+}
+
 /// Import a Private Key
 /// See [ImportPrivateKeyRequest]
 pub async fn import_private_key(
@@ -113,6 +423,45 @@ pub async fn import_private_key(
         .map_err(Into::into)
 }
 
+/// Import a hex encoded private key unless the account `address` exists, in which case
+/// the existing account is returned. The address of the key is derived before importing
+/// it, the key of another account is refused without being imported.
+/// See [ReadEthereumAccountRequest] and [ImportPrivateKeyRequest]
+#[cfg(feature = "in-memory")]
+pub async fn ensure_account(
+    client: &impl Client,
+    mount: &str,
+    address: Address,
+    private_key: &str,
+) -> Result<EthereumAccountResponse, crate::error::ClientError> {
+    let derived = crate::signer::memory::private_key_address(private_key)?;
+    if derived != address {
+        return Err(crate::error::ClientError::Conflict(format!(
+            "the private key is the one of {derived:?}, not {address:?}"
+        )));
+    }
+    match read_account(client, mount, address)
+        .await
+        .map_err(crate::error::ClientError::from)
+    {
+        Ok(account) => return Ok(account),
+        Err(e) if e.status() == Some(404) => {}
+        Err(e) => return Err(e),
+    }
+    let account = match import_private_key(client, mount, private_key).await {
+        Ok(account) => account,
+        // imported concurrently
+        Err(e) => read_account(client, mount, address).await.map_err(|_| e)?,
+    };
+    if account.address != address {
+        return Err(crate::error::ClientError::Conflict(format!(
+            "private key of {:?} imported instead of {address:?}",
+            account.address
+        )));
+    }
+    Ok(account)
+}
+
 /// Sign a message with an Ethereum account.
 /// See [SignEthereumRequest]
 pub async fn sign(
@@ -120,6 +469,17 @@ pub async fn sign(
     mount: &str,
     address: Address,
     data: &[u8],
+) -> Result<EthereumSignResponse, ClientError> {
+    sign_audited(client, mount, address, data, None).await
+}
+
+/// Same as [sign], reporting the signature to `audit`.
+pub async fn sign_audited(
+    client: &impl Client,
+    mount: &str,
+    address: Address,
+    data: &[u8],
+    audit: Option<&dyn AuditSink>,
 ) -> Result<EthereumSignResponse, ClientError> {
     let address = format!("{address:?}");
     let checksummed = eth_checksum::checksum(&address);
@@ -129,7 +489,73 @@ pub async fn sign(
         .data(format!("0x{}", hex::encode(data)))
         .build()
         .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    let scope = AuditScope::start(
+        audit,
+        AuditOperation::EthereumSign,
+        mount,
+        &request.address,
+        data,
+    );
+    let result = vaultrs::api::exec_with_result(client, request).await;
+    if let Some(scope) = scope {
+        scope.finish(result.as_ref().map(|r| r.signature.as_str()));
+    }
+    result
+}
+
+/// Sign many messages with an Ethereum account, running at most `concurrency` requests at
+/// a time. Results are in the order of `messages`, a failed signature does not stop the
+/// others.
+/// See [SignEthereumRequest]
+pub async fn sign_batch<D: AsRef<[u8]>>(
+    client: &impl Client,
+    mount: &str,
+    address: Address,
+    messages: impl IntoIterator<Item = D>,
+    concurrency: usize,
+) -> Vec<Result<EthereumSignResponse, ClientError>> {
+    sign_batch_audited(client, mount, address, messages, concurrency, None).await
+}
+
+/// Same as [sign_batch], reporting the signatures to `audit`.
+pub async fn sign_batch_audited<D: AsRef<[u8]>>(
+    client: &impl Client,
+    mount: &str,
+    address: Address,
+    messages: impl IntoIterator<Item = D>,
+    concurrency: usize,
+    audit: Option<&dyn AuditSink>,
+) -> Vec<Result<EthereumSignResponse, ClientError>> {
+    stream::iter(messages)
+        .map(|data| async move { sign_audited(client, mount, address, data.as_ref(), audit).await })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await
+}
+
+/// Sign a message with an Ethereum account after checking it against the [PolicyEngine].
+/// See [SignEthereumRequest]
+pub async fn sign_with_policy(
+    client: &impl Client,
+    mount: &str,
+    address: Address,
+    data: &[u8],
+    policy: &PolicyEngine,
+) -> Result<EthereumSignResponse, crate::error::ClientError> {
+    sign_with_policy_audited(client, mount, address, data, policy, None).await
+}
+
+/// Same as [sign_with_policy], reporting the signature to `audit`.
+pub async fn sign_with_policy_audited(
+    client: &impl Client,
+    mount: &str,
+    address: Address,
+    data: &[u8],
+    policy: &PolicyEngine,
+    audit: Option<&dyn AuditSink>,
+) -> Result<EthereumSignResponse, crate::error::ClientError> {
+    policy.check_message(address, data)?;
+    sign_audited(client, mount, address, data, audit)
         .await
         .map_err(Into::into)
 }
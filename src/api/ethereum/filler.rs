@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use tokio::sync::OnceCell;
+use web3::types::{CallRequest, TransactionRequest, U256};
+use web3::{Transport, Web3};
+
+use crate::api::ethereum::nonce::{NonceManager, NonceSource};
+use crate::error::ClientError;
+
+/// Chain data needed to populate a transaction before it is signed.
+#[async_trait]
+pub trait FillProvider: NonceSource {
+    /// Returns the id of the chain the transaction is sent to.
+    async fn chain_id(&self) -> Result<u64, ClientError>;
+
+    /// Returns the amount of gas the transaction is expected to use.
+    async fn estimate_gas(&self, transaction: &TransactionRequest) -> Result<U256, ClientError>;
+
+    /// Returns the suggested gas price.
+    async fn gas_price(&self) -> Result<U256, ClientError>;
+}
+
+#[async_trait]
+impl<T> FillProvider for Web3<T>
+where
+    T: Transport + Send + Sync,
+    T::Out: Send,
+{
+    async fn chain_id(&self) -> Result<u64, ClientError> {
+        let chain_id = self.eth().chain_id().await?;
+        u64::try_from(chain_id).map_err(|_| {
+            ClientError::InvalidResponse(format!("chain id {chain_id} does not fit in 64 bits"))
+        })
+    }
+
+    async fn estimate_gas(&self, transaction: &TransactionRequest) -> Result<U256, ClientError> {
+        let request = CallRequest {
+            from: Some(transaction.from),
+            to: transaction.to,
+            gas: None,
+            gas_price: transaction.gas_price,
+            value: transaction.value,
+            data: transaction.data.clone(),
+            transaction_type: transaction.transaction_type,
+            access_list: transaction.access_list.clone(),
+            max_fee_per_gas: transaction.max_fee_per_gas,
+            max_priority_fee_per_gas: transaction.max_priority_fee_per_gas,
+        };
+        self.eth()
+            .estimate_gas(request, None)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn gas_price(&self) -> Result<U256, ClientError> {
+        self.eth().gas_price().await.map_err(Into::into)
+    }
+}
+
+/// Transaction with every field required for signing populated.
+#[derive(Clone, Debug)]
+pub struct FilledTransaction {
+    pub chain_id: u64,
+    pub transaction: TransactionRequest,
+    /// Nonce reserved from the [NonceManager] while filling, to give back with
+    /// [NonceManager::release] if the transaction is not signed
+    pub reserved_nonce: Option<U256>,
+}
+
+/// Populates the missing fields of a [TransactionRequest].
+///
+/// Fields are filled in order: chain id, gas limit, gas price and nonce, which is
+/// reserved last so a failed request to the provider does not leave it unused.
+/// Fields already set on the transaction are kept as is. Nonces are handed out by
+/// a [NonceManager] backed by the provider, the chain id is requested once.
+pub struct TransactionFiller<P> {
+    nonce_manager: NonceManager<P>,
+    chain_id: OnceCell<u64>,
+}
+
+impl<P: FillProvider> TransactionFiller<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            nonce_manager: NonceManager::new(provider),
+            chain_id: OnceCell::new(),
+        }
+    }
+
+    /// Creates a filler that signs for the given chain without asking the provider.
+    pub fn with_chain_id(provider: P, chain_id: u64) -> Self {
+        Self {
+            nonce_manager: NonceManager::new(provider),
+            chain_id: OnceCell::new_with(Some(chain_id)),
+        }
+    }
+
+    /// Returns the underlying provider
+    pub fn provider(&self) -> &P {
+        self.nonce_manager.source()
+    }
+
+    /// Returns the nonce manager used to populate nonces
+    pub fn nonce_manager(&self) -> &NonceManager<P> {
+        &self.nonce_manager
+    }
+
+    /// Populates the missing fields of the transaction.
+    pub async fn fill(
+        &self,
+        mut transaction: TransactionRequest,
+    ) -> Result<FilledTransaction, ClientError> {
+        let chain_id = *self
+            .chain_id
+            .get_or_try_init(|| self.provider().chain_id())
+            .await?;
+        if transaction.gas.is_none() {
+            let gas = self.provider().estimate_gas(&transaction).await?;
+            transaction.gas = Some(gas);
+        }
+        if transaction.gas_price.is_none() {
+            let gas_price = match transaction.max_fee_per_gas {
+                Some(max_fee_per_gas) => max_fee_per_gas,
+                None => self.provider().gas_price().await?,
+            };
+            transaction.gas_price = Some(gas_price);
+        }
+        let reserved_nonce = match transaction.nonce {
+            Some(_) => None,
+            None => Some(self.nonce_manager.next(chain_id, transaction.from).await?),
+        };
+        transaction.nonce = transaction.nonce.or(reserved_nonce);
+        Ok(FilledTransaction {
+            chain_id,
+            transaction,
+            reserved_nonce,
+        })
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use web3::types::{Address, BlockNumber, U256};
+use web3::{Transport, Web3};
+
+use crate::error::ClientError;
+
+/// Source of the next nonce for an account, usually an Ethereum node.
+#[async_trait]
+pub trait NonceSource: Send + Sync {
+    /// Returns the nonce the next transaction of `address` on `chain_id` must use.
+    async fn next_nonce(&self, chain_id: u64, address: Address) -> Result<U256, ClientError>;
+}
+
+/// Reads the pending transaction count from the node the [Web3] instance is connected to.
+///
+/// The node is expected to serve the chain the nonces are requested for.
+#[async_trait]
+impl<T> NonceSource for Web3<T>
+where
+    T: Transport + Send + Sync,
+    T::Out: Send,
+{
+    async fn next_nonce(&self, _chain_id: u64, address: Address) -> Result<U256, ClientError> {
+        self.eth()
+            .transaction_count(address, Some(BlockNumber::Pending))
+            .await
+            .map_err(Into::into)
+    }
+}
+
+type NonceSlot = Arc<tokio::sync::Mutex<Option<U256>>>;
+
+/// Hands out sequential nonces per `(chain_id, address)`.
+///
+/// The first nonce of an account is read from the [NonceSource], every following one
+/// is incremented locally, so concurrent callers never receive the same nonce.
+/// Call [NonceManager::release] when a handed out nonce will not be used, or
+/// [NonceManager::resync] when a transaction using it was not broadcast, so no nonce
+/// is skipped.
+pub struct NonceManager<S> {
+    source: S,
+    slots: Mutex<HashMap<(u64, Address), NonceSlot>>,
+}
+
+impl<S: NonceSource> NonceManager<S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the underlying nonce source
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Reserves the next nonce of `address` on `chain_id`.
+    pub async fn next(&self, chain_id: u64, address: Address) -> Result<U256, ClientError> {
+        let slot = self.slot(chain_id, address);
+        let mut current = slot.lock().await;
+        let nonce = match *current {
+            Some(nonce) => nonce,
+            None => self.source.next_nonce(chain_id, address).await?,
+        };
+        *current = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Returns the nonce the next call to [NonceManager::next] will reserve, if it is
+    /// already known.
+    pub async fn peek(&self, chain_id: u64, address: Address) -> Option<U256> {
+        *self.slot(chain_id, address).lock().await
+    }
+
+    /// Discards the locally tracked nonce and reads it from the source again.
+    pub async fn resync(&self, chain_id: u64, address: Address) -> Result<U256, ClientError> {
+        let slot = self.slot(chain_id, address);
+        let mut current = slot.lock().await;
+        *current = None;
+        let nonce = self.source.next_nonce(chain_id, address).await?;
+        *current = Some(nonce);
+        Ok(nonce)
+    }
+
+    /// Gives back a reserved `nonce` that will not be used, e.g. because signing failed.
+    /// It is handed out again when no later nonce was reserved meanwhile, otherwise the
+    /// tracked nonce is forgotten and the next one is read from the source.
+    pub async fn release(&self, chain_id: u64, address: Address, nonce: U256) {
+        let slot = self.slot(chain_id, address);
+        let mut current = slot.lock().await;
+        *current = match *current {
+            Some(next) if next == nonce + 1 => Some(nonce),
+            _ => None,
+        };
+    }
+
+    /// Forgets the locally tracked nonce, the next call to [NonceManager::next] reads
+    /// it from the source.
+    pub async fn reset(&self, chain_id: u64, address: Address) {
+        *self.slot(chain_id, address).lock().await = None;
+    }
+
+    fn slot(&self, chain_id: u64, address: Address) -> NonceSlot {
+        self.slots
+            .lock()
+            .unwrap()
+            .entry((chain_id, address))
+            .or_default()
+            .clone()
+    }
+}
@@ -0,0 +1,188 @@
+use rlp::{Rlp, RlpStream};
+use web3::signing::{keccak256, recover};
+use web3::types::{Address, Bytes, TransactionRequest, U256};
+
+use crate::api::ethereum::filler::FilledTransaction;
+use crate::error::ClientError;
+
+/// Minimum gas price increase most nodes require to accept a replacement transaction.
+pub const DEFAULT_BUMP_PERCENT: u64 = 10;
+
+/// Gas limit of a plain transfer, used by cancellation transactions.
+const TRANSFER_GAS: u64 = 21000;
+
+/// Increases the gas price by `percent`, rounding up so the result is always strictly
+/// greater than the original price. Percents below [DEFAULT_BUMP_PERCENT] are raised to
+/// it, as nodes reject smaller replacements, and the result saturates at `U256::MAX`.
+pub fn bump_gas_price(gas_price: U256, percent: u64) -> U256 {
+    let percent = U256::from(percent.max(DEFAULT_BUMP_PERCENT));
+    let hundred = U256::from(100);
+    let increase = (gas_price / hundred)
+        .saturating_mul(percent)
+        .saturating_add((gas_price % hundred * percent + 99) / hundred);
+    gas_price.saturating_add(increase.max(U256::one()))
+}
+
+/// Builds a transaction replacing `transaction`: same nonce and payload, gas price
+/// bumped by `percent`.
+///
+/// The original transaction must have its nonce, gas price and recipient set, contract
+/// deployments cannot be replaced as Vault signs them as transfers to the zero address.
+pub fn replacement_transaction(
+    transaction: &TransactionRequest,
+    percent: u64,
+) -> Result<TransactionRequest, ClientError> {
+    if transaction.to.is_none() {
+        return Err(ClientError::InvalidTransaction(
+            "contract deployments cannot be replaced".to_string(),
+        ));
+    }
+    let (nonce, gas_price) = replaced_fields(transaction)?;
+    let mut replacement = transaction.clone();
+    replacement.nonce = Some(nonce);
+    replacement.gas_price = Some(bump_gas_price(gas_price, percent));
+    replacement.max_fee_per_gas = None;
+    replacement.max_priority_fee_per_gas = None;
+    Ok(replacement)
+}
+
+/// Builds a zero-value transfer to the sender itself that cancels `transaction`: same
+/// nonce, gas price bumped by `percent`.
+///
+/// The original transaction must have its nonce and gas price set.
+pub fn cancellation_transaction(
+    transaction: &TransactionRequest,
+    percent: u64,
+) -> Result<TransactionRequest, ClientError> {
+    let (nonce, gas_price) = replaced_fields(transaction)?;
+    let mut cancellation = TransactionRequest::builder()
+        .from(transaction.from)
+        .to(transaction.from)
+        .value(U256::zero())
+        .gas(U256::from(TRANSFER_GAS))
+        .nonce(nonce)
+        .build();
+    cancellation.gas_price = Some(bump_gas_price(gas_price, percent));
+    Ok(cancellation)
+}
+
+/// Decodes a signed legacy (EIP-155) transaction, recovering its sender from the
+/// signature.
+pub fn decode_signed_transaction(raw: &[u8]) -> Result<FilledTransaction, ClientError> {
+    decode_legacy(&Rlp::new(raw)).map_err(|e| ClientError::InvalidTransaction(e.to_string()))
+}
+
+/// Encodes a transaction signed by Vault with the `r || s || v` signature returned by
+/// [sign_transaction][crate::api::ethereum::sign_transaction], ready for
+/// `eth_sendRawTransaction`.
+///
+/// Unset fields take the defaults used when signing. Contract deployments, transactions
+/// without `to`, are refused as Vault signs them as transfers to the zero address.
+pub fn encode_signed_transaction(
+    filled: &FilledTransaction,
+    signature: &str,
+) -> Result<Bytes, ClientError> {
+    let to = filled.transaction.to.ok_or_else(|| {
+        ClientError::InvalidTransaction("contract deployments are not supported".to_string())
+    })?;
+    let signature = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|e| ClientError::InvalidResponse(e.to_string()))?;
+    if signature.len() != 65 {
+        return Err(ClientError::InvalidResponse(format!(
+            "expected a 65 byte signature, got {} bytes",
+            signature.len()
+        )));
+    }
+    let recovery_id = match signature[64] {
+        v @ (0 | 1) => v as u64,
+        v @ (27 | 28) => v as u64 - 27,
+        v => return Err(ClientError::InvalidResponse(format!("invalid v {v}"))),
+    };
+    let transaction = &filled.transaction;
+    let mut stream = RlpStream::new_list(9);
+    stream.append(&transaction.nonce.unwrap_or_default());
+    stream.append(&transaction.gas_price.unwrap_or_default());
+    stream.append(&transaction.gas.unwrap_or_else(|| U256::from(TRANSFER_GAS)));
+    stream.append(&to);
+    stream.append(&transaction.value.unwrap_or_default());
+    stream.append(&transaction.data.clone().unwrap_or_default().0);
+    stream.append(&(filled.chain_id * 2 + 35 + recovery_id));
+    stream.append(&U256::from_big_endian(&signature[..32]));
+    stream.append(&U256::from_big_endian(&signature[32..64]));
+    Ok(Bytes(stream.out().to_vec()))
+}
+
+fn replaced_fields(transaction: &TransactionRequest) -> Result<(U256, U256), ClientError> {
+    let nonce = transaction.nonce.ok_or_else(|| {
+        ClientError::InvalidTransaction("transaction to replace has no nonce".to_string())
+    })?;
+    let gas_price = transaction
+        .gas_price
+        .or(transaction.max_fee_per_gas)
+        .ok_or_else(|| {
+            ClientError::InvalidTransaction("transaction to replace has no gas price".to_string())
+        })?;
+    Ok((nonce, gas_price))
+}
+
+fn decode_legacy(rlp: &Rlp) -> Result<FilledTransaction, String> {
+    if rlp.item_count().map_err(|e| e.to_string())? != 9 {
+        return Err("not a signed legacy transaction".to_string());
+    }
+    let field = |index: usize| rlp.at(index).map_err(|e| e.to_string());
+    let nonce: U256 = field(0)?.as_val().map_err(|e| e.to_string())?;
+    let gas_price: U256 = field(1)?.as_val().map_err(|e| e.to_string())?;
+    let gas: U256 = field(2)?.as_val().map_err(|e| e.to_string())?;
+    let to = field(3)?;
+    let to: Option<Address> = if to.is_empty() {
+        None
+    } else {
+        Some(to.as_val().map_err(|e| e.to_string())?)
+    };
+    let value: U256 = field(4)?.as_val().map_err(|e| e.to_string())?;
+    let data: Vec<u8> = field(5)?.data().map_err(|e| e.to_string())?.to_vec();
+    let v: u64 = field(6)?.as_val().map_err(|e| e.to_string())?;
+    let r: U256 = field(7)?.as_val().map_err(|e| e.to_string())?;
+    let s: U256 = field(8)?.as_val().map_err(|e| e.to_string())?;
+
+    if v < 35 {
+        return Err("transaction is not replay protected (EIP-155)".to_string());
+    }
+    let chain_id = (v - 35) / 2;
+    let recovery_id = ((v - 35) % 2) as i32;
+
+    let mut stream = RlpStream::new_list(9);
+    stream.append(&nonce);
+    stream.append(&gas_price);
+    stream.append(&gas);
+    match to {
+        Some(to) => stream.append(&to),
+        None => stream.append(&""),
+    };
+    stream.append(&value);
+    stream.append(&data);
+    stream.append(&chain_id);
+    stream.append(&0u8);
+    stream.append(&0u8);
+    let hash = keccak256(&stream.out());
+
+    let mut signature = [0u8; 64];
+    r.to_big_endian(&mut signature[..32]);
+    s.to_big_endian(&mut signature[32..]);
+    let from = recover(&hash, &signature, recovery_id).map_err(|e| e.to_string())?;
+
+    let mut transaction = TransactionRequest::builder()
+        .from(from)
+        .gas(gas)
+        .value(value)
+        .data(Bytes(data))
+        .nonce(nonce)
+        .build();
+    transaction.to = to;
+    transaction.gas_price = Some(gas_price);
+    Ok(FilledTransaction {
+        chain_id,
+        transaction,
+        reserved_nonce: None,
+    })
+}
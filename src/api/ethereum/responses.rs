@@ -3,7 +3,7 @@ use web3::types::Address;
 
 /// Response from executing
 /// [ReadEthereumAccountRequest][crate::api::ethereum::requests::ReadEthereumAccountRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct EthereumAccountResponse {
     pub address: Address,
     pub compressed_public_key: String,
@@ -13,21 +13,21 @@ pub struct EthereumAccountResponse {
 
 /// Response from executing
 /// [SignEthereumTransactionRequest][crate::api::ethereum::requests::SignEthereumTransactionRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct EthereumSignTransactionResponse {
     pub signature: String,
 }
 
 /// Response from executing
 /// [ListEthereumAccountsRequest][crate::api::ethereum::requests::ListEthereumAccountsRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct EthereumAccountsResponse {
     pub keys: Vec<Address>,
 }
 
 /// Response from executing
 /// [EthereumSignRequest][crate::api::ethereum::requests::EthereumSignRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct EthereumSignResponse {
     pub signature: String,
 }
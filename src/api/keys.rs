@@ -1,19 +1,29 @@
-use std::collections::HashMap;
-
 use base64::Engine;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use vaultrs::client::Client;
 use vaultrs::error::ClientError;
 
 use crate::api::keys::requests::{
-    CreateKeyRequest, DestroyKeyRequest, ImportKeyRequest, ListKeysRequest, ReadKeyRequest,
-    SignRequest, UpdateKeyTagsRequest,
+    CreateKeyRequest, DeleteKeyRequest, DestroyKeyRequest, ImportKeyRequest, ListKeysRequest,
+    ReadKeyRequest, RestoreKeyRequest, SignRequest, UpdateKeyTagsRequest,
 };
 use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+use crate::audit::{AuditOperation, AuditScope, AuditSink};
+use crate::retry::RetryPolicy;
+
+use self::patch::TagPatch;
+use self::query::{KeyIndex, TagQuery};
 
+pub mod patch;
+pub mod query;
 pub mod requests;
 pub mod responses;
 
 /// Key crypto algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum KeyCryptoAlgorithm {
     Secp256k1,
     Babyjubjub,
@@ -59,30 +69,126 @@ pub async fn create_key(
         .map_err(Into::into)
 }
 
-/// Read a Key
+/// Create a key unless one with the same id exists, in which case the existing key is
+/// returned after checking its curve and signing algorithm match `algorithm`. With
+/// `reconcile_tags`, the tags of an existing key are replaced by `tags` when they differ.
+/// See [CreateKeyRequest] and [ReadKeyRequest]
+pub async fn ensure_key(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    algorithm: KeyCryptoAlgorithm,
+    tags: HashMap<String, String>,
+    reconcile_tags: bool,
+) -> Result<KeyResponse, crate::error::ClientError> {
+    let key = match read_key(client, mount, id)
+        .await
+        .map_err(crate::error::ClientError::from)
+    {
+        Ok(key) => key,
+        Err(e) if e.status() == Some(404) => {
+            match create_key(client, mount, id, algorithm, tags.clone()).await {
+                Ok(key) => return Ok(key),
+                // created concurrently
+                Err(e) => read_key(client, mount, id).await.map_err(|_| e)?,
+            }
+        }
+        Err(e) => return Err(e),
+    };
+    if key.curve != algorithm.curve() || key.signing_algorithm != algorithm.signing_algorithm() {
+        return Err(crate::error::ClientError::Conflict(format!(
+            "key {id} exists with curve {} and signing algorithm {}",
+            key.curve, key.signing_algorithm
+        )));
+    }
+    if reconcile_tags && key.tags != tags {
+        return Ok(update_key_tags(client, mount, id, tags).await?);
+    }
+    Ok(key)
+}
+
+/// Read a Key, retrying transient failures with the default [RetryPolicy]
 /// See [ReadKeyRequest]
 pub async fn read_key(
     client: &impl Client,
     mount: &str,
     id: &str,
 ) -> Result<KeyResponse, ClientError> {
-    let request = ReadKeyRequest::builder()
-        .mount(mount)
-        .id(id)
-        .build()
-        .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    read_key_with_retry(client, mount, id, &RetryPolicy::default()).await
+}
+
+/// Same as [read_key], retrying with `policy`.
+pub async fn read_key_with_retry(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    policy: &RetryPolicy,
+) -> Result<KeyResponse, ClientError> {
+    policy
+        .run_vault(|| {
+            let request = ReadKeyRequest::builder()
+                .mount(mount)
+                .id(id)
+                .build()
+                .unwrap();
+            vaultrs::api::exec_with_result(client, request)
+        })
         .await
-        .map_err(Into::into)
 }
 
-/// List Keys
+/// List Keys, retrying transient failures with the default [RetryPolicy]
 /// See [ListKeysRequest]
 pub async fn list_keys(client: &impl Client, mount: &str) -> Result<KeysResponse, ClientError> {
-    let request = ListKeysRequest::builder().mount(mount).build().unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    list_keys_with_retry(client, mount, &RetryPolicy::default()).await
+}
+
+/// Same as [list_keys], retrying with `policy`.
+pub async fn list_keys_with_retry(
+    client: &impl Client,
+    mount: &str,
+    policy: &RetryPolicy,
+) -> Result<KeysResponse, ClientError> {
+    policy
+        .run_vault(|| {
+            let request = ListKeysRequest::builder().mount(mount).build().unwrap();
+            vaultrs::api::exec_with_result(client, request)
+        })
         .await
-        .map_err(Into::into)
+}
+
+/// Stream the details of every key, reading at most `concurrency` keys at a time.
+/// A failed read is yielded as an error without ending the stream.
+/// See [ListKeysRequest] and [ReadKeyRequest]
+pub fn stream_keys<'a>(
+    client: &'a impl Client,
+    mount: &'a str,
+    concurrency: usize,
+) -> impl Stream<Item = Result<KeyResponse, ClientError>> + 'a {
+    stream::once(list_keys(client, mount))
+        .map(move |list| match list {
+            Ok(list) => stream::iter(list.keys)
+                .map(move |id| async move { read_key(client, mount, &id).await })
+                .buffered(concurrency.max(1))
+                .left_stream(),
+            Err(e) => stream::once(async { Err(e) }).right_stream(),
+        })
+        .flatten()
+}
+
+/// Find the keys whose tags match `query`, reading at most `concurrency` keys at a time.
+/// With an `index`, the keys are read from Vault only when the index is empty or expired.
+/// See [ListKeysRequest] and [ReadKeyRequest]
+pub async fn find_keys(
+    client: &impl Client,
+    mount: &str,
+    query: &TagQuery,
+    concurrency: usize,
+    index: Option<&KeyIndex>,
+) -> Result<Vec<KeyResponse>, crate::error::ClientError> {
+    match index {
+        Some(index) => index.find(client, mount, query, concurrency).await,
+        None => query::read_keys(client, mount, query, concurrency).await,
+    }
 }
 
 /// Update a Key tags
@@ -104,6 +210,76 @@ pub async fn update_key_tags(
         .map_err(Into::into)
 }
 
+/// Apply a patch to the tags of a key.
+///
+/// The patch is applied to the tags of a snapshot of the key, which are then written. When
+/// the version returned by the write shows that another write landed since the snapshot,
+/// the key is read again and the patch applied to it, up to `max_attempts` times before
+/// failing with [Conflict][crate::error::ClientError::Conflict]. The plugin has no
+/// conditional writes, so the tags set by that other write may have been overwritten.
+/// See [UpdateKeyTagsRequest]
+pub async fn patch_key_tags(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    patch: &TagPatch,
+    max_attempts: u32,
+) -> Result<KeyResponse, crate::error::ClientError> {
+    let max_attempts = max_attempts.max(1);
+    let mut key = read_key(client, mount, id).await?;
+    for attempt in 1..=max_attempts {
+        match try_patch_key_tags(client, mount, &key, patch).await? {
+            Ok(patched) => return Ok(patched),
+            Err(_) if attempt < max_attempts => key = read_key(client, mount, id).await?,
+            Err(_) => break,
+        }
+    }
+    Err(crate::error::ClientError::Conflict(format!(
+        "key {id} modified concurrently {max_attempts} times"
+    )))
+}
+
+/// Apply a patch to the tags of `key`, failing with
+/// [Conflict][crate::error::ClientError::Conflict] if the version returned by the write
+/// shows that the key was modified since it was read.
+/// See [UpdateKeyTagsRequest]
+pub async fn patch_key_tags_from(
+    client: &impl Client,
+    mount: &str,
+    key: &KeyResponse,
+    patch: &TagPatch,
+) -> Result<KeyResponse, crate::error::ClientError> {
+    try_patch_key_tags(client, mount, key, patch)
+        .await?
+        .map_err(|written| {
+            crate::error::ClientError::Conflict(format!(
+                "key {} modified concurrently, written as version {} over version {}",
+                key.id, written.version, key.version
+            ))
+        })
+}
+
+/// Writes the tags of the `key` snapshot with the patch applied. When the version of the
+/// written key is not the next one of the snapshot, another write landed in between and
+/// the written key is returned as the error.
+async fn try_patch_key_tags(
+    client: &impl Client,
+    mount: &str,
+    key: &KeyResponse,
+    patch: &TagPatch,
+) -> Result<Result<KeyResponse, KeyResponse>, ClientError> {
+    let tags = patch.apply(&key.tags);
+    if tags == key.tags {
+        return Ok(Ok(key.clone()));
+    }
+    let written = update_key_tags(client, mount, &key.id, tags).await?;
+    if written.version == key.version + 1 {
+        Ok(Ok(written))
+    } else {
+        Ok(Err(written))
+    }
+}
+
 /// Destroy a Key
 /// See [DestroyKeyRequest]
 pub async fn destroy_key(client: &impl Client, mount: &str, id: &str) -> Result<(), ClientError> {
@@ -117,6 +293,32 @@ pub async fn destroy_key(client: &impl Client, mount: &str, id: &str) -> Result<
         .map_err(Into::into)
 }
 
+/// Soft-delete a Key, it is no longer listed or usable until restored
+/// See [DeleteKeyRequest]
+pub async fn delete_key(client: &impl Client, mount: &str, id: &str) -> Result<(), ClientError> {
+    let request = DeleteKeyRequest::builder()
+        .mount(mount)
+        .id(id)
+        .build()
+        .unwrap();
+    vaultrs::api::exec_with_empty_result(client, request)
+        .await
+        .map_err(Into::into)
+}
+
+/// Restore a soft-deleted Key
+/// See [RestoreKeyRequest]
+pub async fn restore_key(client: &impl Client, mount: &str, id: &str) -> Result<(), ClientError> {
+    let request = RestoreKeyRequest::builder()
+        .mount(mount)
+        .id(id)
+        .build()
+        .unwrap();
+    vaultrs::api::exec_with_empty_result(client, request)
+        .await
+        .map_err(Into::into)
+}
+
 /// Import a Key
 /// See [ImportKeyRequest]
 pub async fn import_key(
@@ -148,6 +350,17 @@ pub async fn sign(
     mount: &str,
     id: &str,
     data: &[u8],
+) -> Result<SignResponse, ClientError> {
+    sign_audited(client, mount, id, data, None).await
+}
+
+/// Same as [sign], reporting the signature to `audit`.
+pub async fn sign_audited(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    data: &[u8],
+    audit: Option<&dyn AuditSink>,
 ) -> Result<SignResponse, ClientError> {
     let hash = web3::signing::keccak256(data);
     let encoded = base64::prelude::BASE64_URL_SAFE.encode(hash);
@@ -157,9 +370,12 @@ pub async fn sign(
         .data(encoded)
         .build()
         .unwrap();
-    vaultrs::api::exec_with_result(client, request)
-        .await
-        .map_err(Into::into)
+    let scope = AuditScope::start(audit, AuditOperation::KeysSign, mount, id, data);
+    let result = vaultrs::api::exec_with_result(client, request).await;
+    if let Some(scope) = scope {
+        scope.finish(result.as_ref().map(|r| r.signature.as_str()));
+    }
+    result
 }
 
 /// Sign a message
@@ -170,6 +386,17 @@ pub async fn sign_hash(
     mount: &str,
     id: &str,
     data: [u8; 32],
+) -> Result<SignResponse, ClientError> {
+    sign_hash_audited(client, mount, id, data, None).await
+}
+
+/// Same as [sign_hash], reporting the signature to `audit`.
+pub async fn sign_hash_audited(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    data: [u8; 32],
+    audit: Option<&dyn AuditSink>,
 ) -> Result<SignResponse, ClientError> {
     let encoded = base64::prelude::BASE64_URL_SAFE.encode(data);
     let request = SignRequest::builder()
@@ -178,7 +405,39 @@ pub async fn sign_hash(
         .data(encoded)
         .build()
         .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    let scope = AuditScope::start(audit, AuditOperation::KeysSign, mount, id, &data);
+    let result = vaultrs::api::exec_with_result(client, request).await;
+    if let Some(scope) = scope {
+        scope.finish(result.as_ref().map(|r| r.signature.as_str()));
+    }
+    result
+}
+
+/// Sign many 32 byte hashes with a key, running at most `concurrency` requests at a time.
+/// Results are in the order of `hashes`, a failed signature does not stop the others.
+/// See [SignRequest]
+pub async fn sign_hash_batch(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    hashes: impl IntoIterator<Item = [u8; 32]>,
+    concurrency: usize,
+) -> Vec<Result<SignResponse, ClientError>> {
+    sign_hash_batch_audited(client, mount, id, hashes, concurrency, None).await
+}
+
+/// Same as [sign_hash_batch], reporting the signatures to `audit`.
+pub async fn sign_hash_batch_audited(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    hashes: impl IntoIterator<Item = [u8; 32]>,
+    concurrency: usize,
+    audit: Option<&dyn AuditSink>,
+) -> Vec<Result<SignResponse, ClientError>> {
+    stream::iter(hashes)
+        .map(|hash| sign_hash_audited(client, mount, id, hash, audit))
+        .buffered(concurrency.max(1))
+        .collect()
         .await
-        .map_err(Into::into)
 }
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+/// Changes to the tags of a key, see [patch_key_tags][crate::api::keys::patch_key_tags].
+///
+/// ```
+/// use quorum_vault_client::api::keys::patch::TagPatch;
+///
+/// let patch = TagPatch::new().set("env", "prod").remove("deprecated");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TagPatch {
+    /// Tags added or overwritten
+    pub set: HashMap<String, String>,
+    /// Tags removed, after the ones set
+    pub remove: Vec<String>,
+}
+
+impl TagPatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or overwrites a tag.
+    pub fn set(mut self, tag: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set.insert(tag.into(), value.into());
+        self
+    }
+
+    /// Removes a tag.
+    pub fn remove(mut self, tag: impl Into<String>) -> Self {
+        self.remove.push(tag.into());
+        self
+    }
+
+    /// Returns `tags` with the patch applied.
+    pub fn apply(&self, tags: &HashMap<String, String>) -> HashMap<String, String> {
+        let mut tags = tags.clone();
+        tags.extend(self.set.clone());
+        for tag in &self.remove {
+            tags.remove(tag);
+        }
+        tags
+    }
+}
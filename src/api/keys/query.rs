@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap};
+use std::pin::pin;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use tokio::sync::Mutex;
+use vaultrs::client::Client;
+
+use crate::api::keys::responses::KeyResponse;
+use crate::api::keys::stream_keys;
+use crate::error::ClientError;
+
+/// Condition on the tags of a key, see [find_keys][crate::api::keys::find_keys].
+///
+/// ```
+/// use quorum_vault_client::api::keys::query::TagQuery;
+///
+/// // env=prod and (team=payments or an owner tag set)
+/// let query = TagQuery::equals("env", "prod")
+///     .and(TagQuery::equals("team", "payments").or(TagQuery::exists("owner")));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TagQuery {
+    /// The tag is set to the value
+    Equals(String, String),
+    /// The tag is set, to any value
+    Exists(String),
+    /// The value of the tag starts with the prefix
+    Prefix(String, String),
+    /// Every query matches, true when empty
+    And(Vec<TagQuery>),
+    /// Any query matches, false when empty
+    Or(Vec<TagQuery>),
+}
+
+impl TagQuery {
+    pub fn equals(tag: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Equals(tag.into(), value.into())
+    }
+
+    pub fn exists(tag: impl Into<String>) -> Self {
+        Self::Exists(tag.into())
+    }
+
+    pub fn prefix(tag: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self::Prefix(tag.into(), prefix.into())
+    }
+
+    /// Query matching when both this query and `other` match.
+    pub fn and(self, other: TagQuery) -> Self {
+        match self {
+            Self::And(mut queries) => {
+                queries.push(other);
+                Self::And(queries)
+            }
+            query => Self::And(vec![query, other]),
+        }
+    }
+
+    /// Query matching when this query or `other` matches.
+    pub fn or(self, other: TagQuery) -> Self {
+        match self {
+            Self::Or(mut queries) => {
+                queries.push(other);
+                Self::Or(queries)
+            }
+            query => Self::Or(vec![query, other]),
+        }
+    }
+
+    /// Returns whether `tags` satisfy the query.
+    pub fn matches(&self, tags: &HashMap<String, String>) -> bool {
+        match self {
+            Self::Equals(tag, value) => tags.get(tag) == Some(value),
+            Self::Exists(tag) => tags.contains_key(tag),
+            Self::Prefix(tag, prefix) => tags.get(tag).is_some_and(|v| v.starts_with(prefix)),
+            Self::And(queries) => queries.iter().all(|query| query.matches(tags)),
+            Self::Or(queries) => queries.iter().any(|query| query.matches(tags)),
+        }
+    }
+}
+
+/// Keys of a mount loaded at an instant.
+type MountKeys = (Instant, BTreeMap<String, KeyResponse>);
+
+/// Keys kept in memory per mount so searches do not read every key from Vault.
+///
+/// The keys of a mount are loaded by its first search and loaded again once older than
+/// the TTL. Keys created, updated or destroyed by the application can be reflected right
+/// away with [KeyIndex::insert] and [KeyIndex::remove].
+pub struct KeyIndex {
+    ttl: Duration,
+    mounts: Mutex<HashMap<String, MountKeys>>,
+}
+
+impl KeyIndex {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            mounts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Drops the indexed keys of every mount, the next searches load them again.
+    pub async fn invalidate(&self) {
+        self.mounts.lock().await.clear();
+    }
+
+    /// Adds or replaces a key of `mount`, e.g. after creating it or updating its tags.
+    pub async fn insert(&self, mount: &str, key: KeyResponse) {
+        if let Some((_, keys)) = self.mounts.lock().await.get_mut(mount) {
+            keys.insert(key.id.clone(), key);
+        }
+    }
+
+    /// Removes a destroyed key of `mount`.
+    pub async fn remove(&self, mount: &str, id: &str) {
+        if let Some((_, keys)) = self.mounts.lock().await.get_mut(mount) {
+            keys.remove(id);
+        }
+    }
+
+    /// Returns the indexed keys matching `query`, loading them from `mount` first when
+    /// missing or expired.
+    pub(crate) async fn find(
+        &self,
+        client: &impl Client,
+        mount: &str,
+        query: &TagQuery,
+        concurrency: usize,
+    ) -> Result<Vec<KeyResponse>, ClientError> {
+        let mut mounts = self.mounts.lock().await;
+        let expired = mounts
+            .get(mount)
+            .is_none_or(|(loaded, _)| loaded.elapsed() >= self.ttl);
+        if expired {
+            let loaded = read_keys(client, mount, &TagQuery::And(vec![]), concurrency).await?;
+            let loaded = loaded.into_iter().map(|key| (key.id.clone(), key));
+            mounts.insert(mount.to_string(), (Instant::now(), loaded.collect()));
+        }
+        let (_, keys) = &mounts[mount];
+        Ok(keys
+            .values()
+            .filter(|key| query.matches(&key.tags))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Reads every key of `mount` and keeps those matching `query`. Keys destroyed while
+/// reading are skipped.
+pub(crate) async fn read_keys(
+    client: &impl Client,
+    mount: &str,
+    query: &TagQuery,
+    concurrency: usize,
+) -> Result<Vec<KeyResponse>, ClientError> {
+    let mut keys = pin!(stream_keys(client, mount, concurrency));
+    let mut matching = Vec::new();
+    while let Some(key) = keys.next().await {
+        match key.map_err(ClientError::from) {
+            Ok(key) if query.matches(&key.tags) => matching.push(key),
+            Ok(_) => {}
+            Err(e) if e.status() == Some(404) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(matching)
+}
@@ -84,6 +84,44 @@ pub struct DestroyKeyRequest {
     pub id: String,
 }
 
+/// ## Delete Key
+/// This endpoint soft-deletes a key by ID, it can be restored until destroyed.
+///
+/// * Path: {self.mount}/keys/{self.id}
+/// * Method: DELETE
+#[derive(Builder, Debug, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/keys/{self.id}",
+    method = "DELETE",
+    builder = "true"
+)]
+#[builder(setter(into))]
+pub struct DeleteKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub id: String,
+}
+
+/// ## Restore Key
+/// This endpoint restores a soft-deleted key by ID.
+///
+/// * Path: {self.mount}/keys/{self.id}/restore
+/// * Method: POST
+#[derive(Builder, Debug, Endpoint)]
+#[endpoint(
+    path = "{self.mount}/keys/{self.id}/restore",
+    method = "POST",
+    builder = "true"
+)]
+#[builder(setter(into))]
+pub struct RestoreKeyRequest {
+    #[endpoint(skip)]
+    pub mount: String,
+    #[endpoint(skip)]
+    pub id: String,
+}
+
 /// ## Import Key
 /// This endpoint imports a key with given crypto algorithm and curve.
 ///
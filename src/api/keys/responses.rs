@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Response from executing [CreateKeyRequest][crate::api::keys::requests::CreateKeyRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct KeyResponse {
     pub created_at: String,
     pub curve: String,
@@ -16,13 +16,13 @@ pub struct KeyResponse {
 }
 
 /// Response from executing [ListKeysRequest][crate::api::keys::requests::ListKeysRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct KeysResponse {
     pub keys: Vec<String>,
 }
 
 /// Response from executing [SignRequest][crate::api::keys::requests::SignRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct SignResponse {
     pub signature: String,
 }
@@ -1,3 +1,4 @@
+use crate::api::keys::KeyCryptoAlgorithm;
 use crate::api::zksnarks::requests::{
     CreateZkSnarksAccountRequest, ListZkSnarksAccountsRequest, ReadZkSnarksAccountRequest,
     ZkSnarksSignRequest,
@@ -5,8 +6,11 @@ use crate::api::zksnarks::requests::{
 use crate::api::zksnarks::responses::{
     ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
 };
+use crate::audit::{AuditOperation, AuditScope, AuditSink};
 use crate::error::ClientError;
+use crate::retry::RetryPolicy;
 use crate::H256;
+use futures::stream::{self, Stream, StreamExt};
 use vaultrs::client::Client;
 
 pub mod requests;
@@ -27,36 +31,101 @@ pub async fn create_zksnarks_account(
         .map_err(Into::into)
 }
 
-/// Read a zk-SNARKs account
+/// Return the zk-SNARKs account `id` after checking it is a babyjubjub/eddsa account.
+/// The plugin assigns account ids, so accounts cannot be created idempotently: a
+/// provisioning script creates an account once with [create_zksnarks_account] and
+/// records its id.
+/// See [ReadZkSnarksAccountRequest]
+pub async fn ensure_zksnarks_account(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+) -> Result<ZkSnarksAccountResponse, ClientError> {
+    let account = read_zksnarks_account(client, mount, id).await?;
+    let algorithm = KeyCryptoAlgorithm::Babyjubjub;
+    if account.curve != algorithm.curve()
+        || account.signing_algorithm != algorithm.signing_algorithm()
+    {
+        return Err(ClientError::Conflict(format!(
+            "account {id} exists with curve {} and signing algorithm {}",
+            account.curve, account.signing_algorithm
+        )));
+    }
+    Ok(account)
+}
+
+/// Read a zk-SNARKs account, retrying transient failures with the default [RetryPolicy]
 /// See [ReadZkSnarksAccountRequest]
 pub async fn read_zksnarks_account(
     client: &impl Client,
     mount: &str,
     id: &str,
 ) -> Result<ZkSnarksAccountResponse, ClientError> {
-    let request = ReadZkSnarksAccountRequest::builder()
-        .mount(mount)
-        .id(id)
-        .build()
-        .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    read_zksnarks_account_with_retry(client, mount, id, &RetryPolicy::default()).await
+}
+
+/// Same as [read_zksnarks_account], retrying with `policy`.
+pub async fn read_zksnarks_account_with_retry(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    policy: &RetryPolicy,
+) -> Result<ZkSnarksAccountResponse, ClientError> {
+    policy
+        .run(|| {
+            let request = ReadZkSnarksAccountRequest::builder()
+                .mount(mount)
+                .id(id)
+                .build()
+                .unwrap();
+            vaultrs::api::exec_with_result(client, request)
+        })
         .await
-        .map_err(Into::into)
 }
 
-/// List zk-SNARKs accounts
+/// List zk-SNARKs accounts, retrying transient failures with the default [RetryPolicy]
 /// See [ListZkSnarksAccountsRequest]
 pub async fn list_zksnarks_accounts(
     client: &impl Client,
     mount: &str,
 ) -> Result<ZkSnarksAccountsResponse, ClientError> {
-    let request = ListZkSnarksAccountsRequest::builder()
-        .mount(mount)
-        .build()
-        .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    list_zksnarks_accounts_with_retry(client, mount, &RetryPolicy::default()).await
+}
+
+/// Same as [list_zksnarks_accounts], retrying with `policy`.
+pub async fn list_zksnarks_accounts_with_retry(
+    client: &impl Client,
+    mount: &str,
+    policy: &RetryPolicy,
+) -> Result<ZkSnarksAccountsResponse, ClientError> {
+    policy
+        .run(|| {
+            let request = ListZkSnarksAccountsRequest::builder()
+                .mount(mount)
+                .build()
+                .unwrap();
+            vaultrs::api::exec_with_result(client, request)
+        })
         .await
-        .map_err(Into::into)
+}
+
+/// Stream the details of every zk-SNARKs account, reading at most `concurrency` accounts
+/// at a time. A failed read is yielded as an error without ending the stream.
+/// See [ListZkSnarksAccountsRequest] and [ReadZkSnarksAccountRequest]
+pub fn stream_zksnarks_accounts<'a>(
+    client: &'a impl Client,
+    mount: &'a str,
+    concurrency: usize,
+) -> impl Stream<Item = Result<ZkSnarksAccountResponse, ClientError>> + 'a {
+    stream::once(list_zksnarks_accounts(client, mount))
+        .map(move |list| match list {
+            Ok(list) => stream::iter(list.keys)
+                .map(move |id| async move { read_zksnarks_account(client, mount, &id).await })
+                .buffered(concurrency.max(1))
+                .left_stream(),
+            Err(e) => stream::once(async { Err(e) }).right_stream(),
+        })
+        .flatten()
 }
 
 /// Sign a message with a zk-SNARKs account (eddsa)
@@ -66,6 +135,17 @@ pub async fn zksnarks_sign(
     mount: &str,
     id: &str,
     data: &[u8],
+) -> Result<ZkSnarksSignResponse, ClientError> {
+    zksnarks_sign_audited(client, mount, id, data, None).await
+}
+
+/// Same as [zksnarks_sign], reporting the signature to `audit`.
+pub async fn zksnarks_sign_audited(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    data: &[u8],
+    audit: Option<&dyn AuditSink>,
 ) -> Result<ZkSnarksSignResponse, ClientError> {
     let hash = web3::signing::keccak256(data);
     let hex = H256::from(hash);
@@ -76,9 +156,12 @@ pub async fn zksnarks_sign(
         .data(encoded)
         .build()
         .unwrap();
-    vaultrs::api::exec_with_result(client, request)
-        .await
-        .map_err(Into::into)
+    let scope = AuditScope::start(audit, AuditOperation::ZkSnarksSign, mount, id, data);
+    let result = vaultrs::api::exec_with_result(client, request).await;
+    if let Some(scope) = scope {
+        scope.finish(result.as_ref().map(|r| r.signature.as_str()));
+    }
+    result.map_err(Into::into)
 }
 
 /// Sign a message with a zk-SNARKs account (eddsa)
@@ -89,6 +172,17 @@ pub async fn zksnarks_sign_hash(
     mount: &str,
     id: &str,
     data: [u8; 32],
+) -> Result<ZkSnarksSignResponse, ClientError> {
+    zksnarks_sign_hash_audited(client, mount, id, data, None).await
+}
+
+/// Same as [zksnarks_sign_hash], reporting the signature to `audit`.
+pub async fn zksnarks_sign_hash_audited(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    data: [u8; 32],
+    audit: Option<&dyn AuditSink>,
 ) -> Result<ZkSnarksSignResponse, ClientError> {
     let hex = H256::from(data);
     let encoded = format!("{:?}", hex);
@@ -98,7 +192,40 @@ pub async fn zksnarks_sign_hash(
         .data(encoded)
         .build()
         .unwrap();
-    vaultrs::api::exec_with_result(client, request)
+    let scope = AuditScope::start(audit, AuditOperation::ZkSnarksSign, mount, id, &data);
+    let result = vaultrs::api::exec_with_result(client, request).await;
+    if let Some(scope) = scope {
+        scope.finish(result.as_ref().map(|r| r.signature.as_str()));
+    }
+    result.map_err(Into::into)
+}
+
+/// Sign many 32 byte hashes with a zk-SNARKs account, running at most `concurrency`
+/// requests at a time. Results are in the order of `hashes`, a failed signature does not
+/// stop the others.
+/// See [ZkSnarksSignResponse]
+pub async fn zksnarks_sign_hash_batch(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    hashes: impl IntoIterator<Item = [u8; 32]>,
+    concurrency: usize,
+) -> Vec<Result<ZkSnarksSignResponse, ClientError>> {
+    zksnarks_sign_hash_batch_audited(client, mount, id, hashes, concurrency, None).await
+}
+
+/// Same as [zksnarks_sign_hash_batch], reporting the signatures to `audit`.
+pub async fn zksnarks_sign_hash_batch_audited(
+    client: &impl Client,
+    mount: &str,
+    id: &str,
+    hashes: impl IntoIterator<Item = [u8; 32]>,
+    concurrency: usize,
+    audit: Option<&dyn AuditSink>,
+) -> Vec<Result<ZkSnarksSignResponse, ClientError>> {
+    stream::iter(hashes)
+        .map(|hash| zksnarks_sign_hash_audited(client, mount, id, hash, audit))
+        .buffered(concurrency.max(1))
+        .collect()
         .await
-        .map_err(Into::into)
 }
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Response from executing [CreateZkSnarksAccountRequest][crate::api::zksnarks::requests::CreateZkSnarksAccountRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct ZkSnarksAccountResponse {
     pub curve: String,
     pub namespace: String,
@@ -10,13 +10,13 @@ pub struct ZkSnarksAccountResponse {
 }
 
 /// Response from executing [ListZkSnarksAccountsRequest][crate::api::zksnarks::requests::ListZkSnarksAccountsRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct ZkSnarksAccountsResponse {
     pub keys: Vec<String>,
 }
 
 /// Response from executing [ZkSnarksSignRequest][crate::api::zksnarks::requests::ZkSnarksSignRequest]
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct ZkSnarksSignResponse {
     pub signature: String,
 }
@@ -0,0 +1,198 @@
+//! Audit trail of signing operations.
+//!
+//! Every signing call of [api::ethereum][crate::api::ethereum],
+//! [api::keys][crate::api::keys] and [api::zksnarks][crate::api::zksnarks], batches,
+//! policy checks, nonce management and replacements included, has an `_audited` variant
+//! taking an [AuditSink], such as
+//! [sign_transaction_audited][crate::api::ethereum::sign_transaction_audited]. It reports
+//! an [AuditRecord] to the sink once each signature completes. A signer created with
+//! [VaultSigner::with_audit_sink][crate::signer::VaultSigner::with_audit_sink] audits all
+//! of its signing calls.
+//!
+//! Payloads are only recorded as keccak256 hashes unless the sink opts in with
+//! [AuditSink::include_payload].
+
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::ethereum::requests::SignEthereumTransactionRequest;
+
+/// Signing operation an [AuditRecord] was produced for.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    EthereumSignTransaction,
+    EthereumSign,
+    KeysSign,
+    ZkSnarksSign,
+}
+
+/// Fields of a signed Ethereum transaction as sent to Vault, the data is part of the
+/// payload.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuditTransaction {
+    pub chain_id: String,
+    pub to: String,
+    pub amount: String,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub gas_price: String,
+}
+
+/// Record of a single signing operation.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch at which the operation started
+    pub timestamp: u64,
+    pub operation: AuditOperation,
+    pub mount: String,
+    /// Key id, zk-SNARKs account id or checksummed Ethereum address
+    pub key: String,
+    /// Hex encoded keccak256 hash of the signed payload
+    pub payload_hash: String,
+    /// Hex encoded payload, only present when the sink asks for it
+    pub payload: Option<String>,
+    pub transaction: Option<AuditTransaction>,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Destination of [AuditRecord]s.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord);
+
+    /// Whether records carry the signed payload in addition to its hash.
+    fn include_payload(&self) -> bool {
+        false
+    }
+}
+
+/// Writes records to a file as JSON lines.
+pub struct JsonLinesAuditSink {
+    file: Mutex<File>,
+    include_payload: bool,
+}
+
+impl JsonLinesAuditSink {
+    /// Opens the file for appending, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+            include_payload: false,
+        })
+    }
+
+    /// Stores the signed payload in the records.
+    pub fn with_payload(mut self) -> Self {
+        self.include_payload = true;
+        self
+    }
+}
+
+impl AuditSink for JsonLinesAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let mut line = match serde_json::to_vec(record) {
+            Ok(line) => line,
+            Err(e) => {
+                log::error!("Failed to serialize audit record: {e}");
+                return;
+            }
+        };
+        line.push(b'\n');
+        if let Err(e) = self.file.lock().unwrap().write_all(&line) {
+            log::error!("Failed to write audit record: {e}");
+        }
+    }
+
+    fn include_payload(&self) -> bool {
+        self.include_payload
+    }
+}
+
+/// Emits records through the [log] crate with the `quorum_vault_client::audit` target.
+#[derive(Default)]
+pub struct LogAuditSink;
+
+impl AuditSink for LogAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let json = serde_json::to_string(record).unwrap_or_default();
+        match record.error {
+            Some(_) => log::warn!(target: "quorum_vault_client::audit", "{json}"),
+            None => log::info!(target: "quorum_vault_client::audit", "{json}"),
+        }
+    }
+}
+
+/// Measures a signing operation and reports it to a sink.
+pub(crate) struct AuditScope<'a> {
+    sink: &'a dyn AuditSink,
+    started: Instant,
+    record: AuditRecord,
+}
+
+impl<'a> AuditScope<'a> {
+    /// Starts auditing an operation, returns `None` without a sink.
+    pub(crate) fn start(
+        sink: Option<&'a dyn AuditSink>,
+        operation: AuditOperation,
+        mount: &str,
+        key: &str,
+        payload: &[u8],
+    ) -> Option<Self> {
+        let sink = sink?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        let payload_hash = hex::encode(web3::signing::keccak256(payload));
+        let payload = sink
+            .include_payload()
+            .then(|| format!("0x{}", hex::encode(payload)));
+        Some(Self {
+            sink,
+            started: Instant::now(),
+            record: AuditRecord {
+                timestamp,
+                operation,
+                mount: mount.to_string(),
+                key: key.to_string(),
+                payload_hash: format!("0x{payload_hash}"),
+                payload,
+                transaction: None,
+                signature: None,
+                error: None,
+                duration_ms: 0,
+            },
+        })
+    }
+
+    pub(crate) fn with_transaction(mut self, request: &SignEthereumTransactionRequest) -> Self {
+        self.record.transaction = Some(AuditTransaction {
+            chain_id: request.chain_id.clone(),
+            to: request.to.clone(),
+            amount: request.amount.clone(),
+            nonce: request.nonce,
+            gas_limit: request.gas_limit,
+            gas_price: request.gas_price.clone(),
+        });
+        self
+    }
+
+    /// Reports the outcome of the operation.
+    pub(crate) fn finish<E: Display>(mut self, result: Result<&str, &E>) {
+        match result {
+            Ok(signature) => self.record.signature = Some(signature.to_string()),
+            Err(e) => self.record.error = Some(e.to_string()),
+        }
+        self.record.duration_ms = self.started.elapsed().as_millis() as u64;
+        self.sink.record(&self.record);
+    }
+}
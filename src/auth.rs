@@ -0,0 +1,486 @@
+//! Managed authentication with the AppRole, Kubernetes and JWT auth methods, or with the
+//! token file written by a Vault Agent.
+//!
+//! An [AuthenticatedClient] logs in with an [AuthMethod] and keeps its token alive in the
+//! background: the token is renewed once two thirds of its lease have elapsed, and a new
+//! login is made when it can no longer be renewed. Requests are made with
+//! [AuthenticatedClient::call], which logs in again and retries once when Vault answers
+//! `403`, e.g. after the token was revoked:
+//!
+//! ```no_run
+//! use quorum_vault_client::auth::{AuthMethod, AuthenticatedClient};
+//! use quorum_vault_client::config::Config;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config = Config::from_env();
+//!     let client = AuthenticatedClient::login(config.client().unwrap(), AuthMethod::kubernetes("signer"))
+//!         .await
+//!         .unwrap();
+//!     let accounts = client
+//!         .call(async |client| {
+//!             quorum_vault_client::api::ethereum::list_accounts(client, config.mount()).await
+//!         })
+//!         .await
+//!         .unwrap();
+//!     println!("accounts: {:?}", accounts.keys);
+//! }
+//! ```
+//!
+//! Neither client implements [Client]: the trait of vaultrs 0.6 hands out plain
+//! references to the token ([Client::middle] and [Client::settings]) and has no hook
+//! around requests, so a client replacing its token would have to keep every token it
+//! ever used alive, and could not log in again on `403` anyway. The `api::*` functions
+//! are called inside [AuthenticatedClient::call] instead.
+//!
+//! [AuthenticatedClient::session] returns a [VaultClient] with the token in use, for a
+//! single request that is not retried. It keeps its token when a new one is obtained, so
+//! it must not be held on to.
+//!
+//! A [TokenFileClient] instead reads its token from the sink of a Vault Agent sidecar,
+//! picking up the tokens it rotates. It makes requests the same way.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+
+use rustify::clients::reqwest::Client as HTTPClient;
+use tokio::task::AbortHandle;
+use vaultrs::api::{AuthInfo, EndpointMiddleware};
+use vaultrs::client::{Client, VaultClient, VaultClientSettings};
+
+use crate::error::ClientError;
+
+/// Service account token mounted in Kubernetes pods.
+pub const SERVICE_ACCOUNT_TOKEN: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+
+/// Delay before retrying a failed renewal or login.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Interval at which a [TokenFileClient] checks its token file.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// JSON Web Token presented to the Kubernetes or JWT auth methods.
+#[derive(Clone)]
+pub enum Jwt {
+    Token(String),
+    /// File read at every login, so rotated tokens are picked up
+    File(PathBuf),
+}
+
+impl Jwt {
+    fn read(&self) -> Result<String, ClientError> {
+        match self {
+            Jwt::Token(token) => Ok(token.clone()),
+            Jwt::File(path) => std::fs::read_to_string(path)
+                .map(|token| token.trim().to_string())
+                .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display()))),
+        }
+    }
+}
+
+/// Auth method used to obtain a token, with the path it is mounted at.
+#[derive(Clone)]
+pub enum AuthMethod {
+    AppRole {
+        mount: String,
+        role_id: String,
+        secret_id: String,
+    },
+    Kubernetes {
+        mount: String,
+        role: String,
+        jwt: Jwt,
+    },
+    Jwt {
+        mount: String,
+        role: Option<String>,
+        jwt: Jwt,
+    },
+}
+
+impl AuthMethod {
+    /// AppRole auth method mounted at `approle`.
+    pub fn approle(role_id: impl Into<String>, secret_id: impl Into<String>) -> Self {
+        AuthMethod::AppRole {
+            mount: "approle".to_string(),
+            role_id: role_id.into(),
+            secret_id: secret_id.into(),
+        }
+    }
+
+    /// Kubernetes auth method mounted at `kubernetes`, presenting the service account
+    /// token of the pod.
+    pub fn kubernetes(role: impl Into<String>) -> Self {
+        AuthMethod::Kubernetes {
+            mount: "kubernetes".to_string(),
+            role: role.into(),
+            jwt: Jwt::File(PathBuf::from(SERVICE_ACCOUNT_TOKEN)),
+        }
+    }
+
+    /// JWT auth method mounted at `jwt`, the default role of the method is used when
+    /// `role` is `None`.
+    pub fn jwt(role: Option<&str>, jwt: Jwt) -> Self {
+        AuthMethod::Jwt {
+            mount: "jwt".to_string(),
+            role: role.map(str::to_string),
+            jwt,
+        }
+    }
+
+    /// Returns the auth method mounted at another path.
+    pub fn with_mount(mut self, path: impl Into<String>) -> Self {
+        match &mut self {
+            AuthMethod::AppRole { mount, .. }
+            | AuthMethod::Kubernetes { mount, .. }
+            | AuthMethod::Jwt { mount, .. } => *mount = path.into(),
+        }
+        self
+    }
+
+    async fn login(&self, client: &impl Client) -> Result<AuthInfo, ClientError> {
+        let auth = match self {
+            AuthMethod::AppRole {
+                mount,
+                role_id,
+                secret_id,
+            } => vaultrs::auth::approle::login(client, mount, role_id, secret_id).await?,
+            AuthMethod::Kubernetes { mount, role, jwt } => {
+                vaultrs::auth::kubernetes::login(client, mount, role, &jwt.read()?).await?
+            }
+            AuthMethod::Jwt { mount, role, jwt } => {
+                vaultrs::auth::oidc::login(client, mount, &jwt.read()?, role.clone()).await?
+            }
+        };
+        Ok(auth)
+    }
+}
+
+impl fmt::Debug for AuthMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthMethod::AppRole { mount, role_id, .. } => f
+                .debug_struct("AppRole")
+                .field("mount", mount)
+                .field("role_id", role_id)
+                .finish_non_exhaustive(),
+            AuthMethod::Kubernetes { mount, role, .. } => f
+                .debug_struct("Kubernetes")
+                .field("mount", mount)
+                .field("role", role)
+                .finish_non_exhaustive(),
+            AuthMethod::Jwt { mount, role, .. } => f
+                .debug_struct("Jwt")
+                .field("mount", mount)
+                .field("role", role)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Client logged in with an [AuthMethod], renewing its token in the background.
+///
+/// Clones share the token and the renewal, which stops when the last clone is dropped.
+#[derive(Clone)]
+pub struct AuthenticatedClient {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    /// Client without token, used to log in
+    client: VaultClient,
+    method: AuthMethod,
+    session: RwLock<Arc<VaultClient>>,
+    lease: Mutex<Lease>,
+    login: tokio::sync::Mutex<()>,
+    renewal: Mutex<Option<AbortHandle>>,
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        if let Some(renewal) = self.renewal.get_mut().unwrap().take() {
+            renewal.abort();
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Lease {
+    duration: Duration,
+    renewable: bool,
+}
+
+impl From<&AuthInfo> for Lease {
+    fn from(auth: &AuthInfo) -> Self {
+        Self {
+            duration: Duration::from_secs(auth.lease_duration),
+            renewable: auth.renewable,
+        }
+    }
+}
+
+impl AuthenticatedClient {
+    /// Logs in to the Vault of `client` and starts renewing the token, the token of
+    /// `client` is not used.
+    pub async fn login(mut client: VaultClient, method: AuthMethod) -> Result<Self, ClientError> {
+        client.set_token("");
+        let auth = method.login(&client).await?;
+        let session = session(&client, &auth.client_token);
+        let authenticated = Self {
+            shared: Arc::new(Shared {
+                client,
+                method,
+                session: RwLock::new(session),
+                lease: Mutex::new(Lease::from(&auth)),
+                login: tokio::sync::Mutex::new(()),
+                renewal: Mutex::new(None),
+            }),
+        };
+        let renewal = tokio::spawn(renew(Arc::downgrade(&authenticated.shared)));
+        *authenticated.shared.renewal.lock().unwrap() = Some(renewal.abort_handle());
+        Ok(authenticated)
+    }
+
+    /// Returns the auth method
+    pub fn method(&self) -> &AuthMethod {
+        &self.shared.method
+    }
+
+    /// Returns the token in use
+    pub fn token(&self) -> String {
+        self.session().settings.token.clone()
+    }
+
+    /// Returns a client using the token in use, which is not replaced by later logins:
+    /// use it for a single request and prefer [AuthenticatedClient::call].
+    pub fn session(&self) -> Arc<VaultClient> {
+        self.shared.session.read().unwrap().clone()
+    }
+
+    /// Logs in again, replacing the token in use.
+    pub async fn reauthenticate(&self) -> Result<(), ClientError> {
+        let token = self.token();
+        let _login = self.shared.login.lock().await;
+        // a concurrent call already replaced the rejected token
+        if self.token() != token {
+            return Ok(());
+        }
+        let auth = self.shared.method.login(&self.shared.client).await?;
+        *self.shared.session.write().unwrap() = session(&self.shared.client, &auth.client_token);
+        *self.shared.lease.lock().unwrap() = Lease::from(&auth);
+        log::info!("logged in to Vault with {:?}", self.shared.method);
+        Ok(())
+    }
+
+    /// Renews the token in use, logging in again when it cannot be renewed.
+    pub async fn renew(&self) -> Result<(), ClientError> {
+        let lease = *self.shared.lease.lock().unwrap();
+        if lease.renewable {
+            match vaultrs::token::renew_self(&*self.session(), None).await {
+                // a lease shorter than half the previous one hit the max TTL of the token
+                Ok(auth) if auth.renewable && Lease::from(&auth).duration * 2 >= lease.duration => {
+                    *self.shared.lease.lock().unwrap() = Lease::from(&auth);
+                    return Ok(());
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("failed to renew the Vault token: {e}"),
+            }
+        }
+        self.reauthenticate().await
+    }
+
+    /// Runs `call` with the token in use, logging in again and running it once more if
+    /// Vault denies the token.
+    pub async fn call<F, T, E>(&self, call: F) -> Result<T, ClientError>
+    where
+        F: AsyncFn(&VaultClient) -> Result<T, E>,
+        E: Into<ClientError>,
+    {
+        match call(&self.session()).await.map_err(Into::into) {
+            Err(e) if is_forbidden(&e) => {
+                log::info!("Vault denied the token, logging in again");
+                self.reauthenticate().await?;
+                call(&self.session()).await.map_err(Into::into)
+            }
+            result => result,
+        }
+    }
+}
+
+/// Returns a client sending requests with `token` through the transport of `client`.
+fn session(client: &VaultClient, token: &str) -> Arc<VaultClient> {
+    Arc::new(VaultClient {
+        http: HTTPClient {
+            http: client.http.http.clone(),
+            base: client.http.base.clone(),
+        },
+        middle: EndpointMiddleware {
+            token: token.to_string(),
+            ..client.middle.clone()
+        },
+        settings: VaultClientSettings {
+            token: token.to_string(),
+            ..client.settings.clone()
+        },
+    })
+}
+
+/// Client reading its token from a file, such as the sink of a Vault Agent.
+///
+/// The file is checked every poll interval and when Vault answers `403` to a call
+/// made with [TokenFileClient::call], so rotated tokens are used without restarting.
+/// Clones share the token and the watch, which stops when the last clone is dropped.
+#[derive(Clone)]
+pub struct TokenFileClient {
+    shared: Arc<FileShared>,
+}
+
+struct FileShared {
+    client: VaultClient,
+    path: PathBuf,
+    session: RwLock<Arc<VaultClient>>,
+    reload: Mutex<()>,
+    watch: Mutex<Option<AbortHandle>>,
+}
+
+impl Drop for FileShared {
+    fn drop(&mut self) {
+        if let Some(watch) = self.watch.get_mut().unwrap().take() {
+            watch.abort();
+        }
+    }
+}
+
+impl TokenFileClient {
+    /// Reads the token from `path` and watches it, must be called from a Tokio runtime.
+    pub fn new(client: VaultClient, path: impl Into<PathBuf>) -> Result<Self, ClientError> {
+        Self::with_poll_interval(client, path, DEFAULT_POLL_INTERVAL)
+    }
+
+    /// Same as [TokenFileClient::new], checking the file every `interval`.
+    pub fn with_poll_interval(
+        client: VaultClient,
+        path: impl Into<PathBuf>,
+        interval: Duration,
+    ) -> Result<Self, ClientError> {
+        let path = path.into();
+        let token = read_token(&path)?;
+        let session = session(&client, &token);
+        let watched = Self {
+            shared: Arc::new(FileShared {
+                client,
+                path,
+                session: RwLock::new(session),
+                reload: Mutex::new(()),
+                watch: Mutex::new(None),
+            }),
+        };
+        let watch = tokio::spawn(watch(Arc::downgrade(&watched.shared), interval));
+        *watched.shared.watch.lock().unwrap() = Some(watch.abort_handle());
+        Ok(watched)
+    }
+
+    /// Returns the path of the token file
+    pub fn path(&self) -> &Path {
+        &self.shared.path
+    }
+
+    /// Returns the token in use
+    pub fn token(&self) -> String {
+        self.session().settings.token.clone()
+    }
+
+    /// Returns a client using the token in use, which is not replaced by later reloads:
+    /// use it for a single request and prefer [TokenFileClient::call].
+    pub fn session(&self) -> Arc<VaultClient> {
+        self.shared.session.read().unwrap().clone()
+    }
+
+    /// Reads the token file again, returns whether the token changed.
+    pub fn reload(&self) -> Result<bool, ClientError> {
+        let token = read_token(&self.shared.path)?;
+        let _reload = self.shared.reload.lock().unwrap();
+        if token == self.token() {
+            return Ok(false);
+        }
+        *self.shared.session.write().unwrap() = session(&self.shared.client, &token);
+        log::info!(
+            "reloaded the Vault token from {}",
+            self.shared.path.display()
+        );
+        Ok(true)
+    }
+
+    /// Runs `call` with the token in use, running it once more if Vault denies the token
+    /// and the token file holds another token.
+    pub async fn call<F, T, E>(&self, call: F) -> Result<T, ClientError>
+    where
+        F: AsyncFn(&VaultClient) -> Result<T, E>,
+        E: Into<ClientError>,
+    {
+        match call(&self.session()).await.map_err(Into::into) {
+            Err(e) if is_forbidden(&e) => match self.reload() {
+                Ok(true) => call(&self.session()).await.map_err(Into::into),
+                _ => Err(e),
+            },
+            result => result,
+        }
+    }
+}
+
+/// Reloads the token file while the client is in use.
+async fn watch(shared: Weak<FileShared>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        // the file is briefly missing while a Vault Agent rewrites it
+        if let Err(e) = (TokenFileClient { shared }).reload() {
+            log::debug!("failed to reload the Vault token: {e}");
+        }
+    }
+}
+
+fn read_token(path: &Path) -> Result<String, ClientError> {
+    let token = std::fs::read_to_string(path)
+        .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err(ClientError::ConfigError(format!(
+            "{}: empty token",
+            path.display()
+        )));
+    }
+    Ok(token.to_string())
+}
+
+/// Keeps the token alive while the client is in use.
+async fn renew(shared: Weak<Shared>) {
+    let mut delay = None;
+    loop {
+        let lease = match shared.upgrade() {
+            Some(shared) => *shared.lease.lock().unwrap(),
+            None => return,
+        };
+        // tokens without lease do not expire
+        if lease.duration.is_zero() {
+            return;
+        }
+        tokio::time::sleep(delay.take().unwrap_or(lease.duration * 2 / 3)).await;
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        let client = AuthenticatedClient { shared };
+        if let Err(e) = client.renew().await {
+            log::error!("failed to log in to Vault: {e}");
+            delay = Some(RETRY_DELAY.min(lease.duration / 3));
+        }
+    }
+}
+
+fn is_forbidden(error: &ClientError) -> bool {
+    error.status() == Some(403)
+}
@@ -0,0 +1,87 @@
+//! JSON-RPC signing proxy in front of an Ethereum node, signing with accounts held in Vault.
+//!
+//! ```text
+//! export VAULT_ADDR=http://127.0.0.1:8200 VAULT_TOKEN=root
+//! eth-signer-proxy --mount quorum --node-url http://127.0.0.1:8546 --listen 127.0.0.1:8545
+//! ```
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::Parser;
+use quorum_vault_client::api::ethereum::filler::TransactionFiller;
+use quorum_vault_client::config::Config;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::policy::{PolicyEngine, PolicySigner, SigningPolicy};
+use quorum_vault_client::proxy::{router, SigningProxy};
+use web3::transports::Http;
+use web3::Web3;
+
+#[derive(Parser)]
+#[command(
+    name = "eth-signer-proxy",
+    version,
+    about = "JSON-RPC proxy signing transactions with accounts held in Vault"
+)]
+struct Args {
+    /// Configuration file (TOML, YAML or JSON), overridden by the environment
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Address of Vault [default: VAULT_ADDR or http://127.0.0.1:8200]
+    #[arg(long)]
+    address: Option<String>,
+    /// Vault token [default: VAULT_TOKEN]
+    #[arg(long)]
+    token: Option<String>,
+    /// Mount of the plugin [default: QUORUM_VAULT_MOUNT or quorum]
+    #[arg(long)]
+    mount: Option<String>,
+    /// Signing policy file (TOML or JSON) enforced on every signature
+    #[arg(long)]
+    policy: Option<PathBuf>,
+    /// JSON-RPC endpoint of the node
+    #[arg(long, default_value = "http://127.0.0.1:8546")]
+    node_url: String,
+    /// Chain id to sign for, requested from the node when omitted
+    #[arg(long)]
+    chain_id: Option<u64>,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8545")]
+    listen: SocketAddr,
+}
+
+async fn run(args: Args) -> Result<(), ClientError> {
+    let arguments = Config {
+        address: args.address,
+        token: args.token,
+        mount: args.mount,
+        ..Config::default()
+    };
+    let signer = Config::load(args.config)?.merge(arguments).signer()?;
+    let policy = match args.policy {
+        Some(path) => SigningPolicy::from_file(path)?,
+        None => SigningPolicy::default(),
+    };
+    let signer = PolicySigner::new(signer, PolicyEngine::new(policy));
+    let provider = Web3::new(Http::new(&args.node_url)?);
+    let filler = match args.chain_id {
+        Some(chain_id) => TransactionFiller::with_chain_id(provider, chain_id),
+        None => TransactionFiller::new(provider),
+    };
+    let proxy = SigningProxy::with_filler(signer, filler, &args.node_url);
+    let app = router(Arc::new(proxy));
+    axum::Server::try_bind(&args.listen)
+        .map_err(|e| ClientError::ConfigError(e.to_string()))?
+        .serve(app.into_make_service())
+        .await
+        .map_err(|e| ClientError::ConfigError(e.to_string()))
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Args::parse()).await {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,72 @@
+//! Web3Signer compatible eth1 REST API in front of Vault.
+//!
+//! ```text
+//! export VAULT_ADDR=http://127.0.0.1:8200 VAULT_TOKEN=root
+//! quorum-vault-web3signer --mount quorum --listen 127.0.0.1:9000
+//! ```
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::Parser;
+use quorum_vault_client::config::Config;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::policy::{PolicyEngine, PolicySigner, SigningPolicy};
+use quorum_vault_client::web3signer::{router, Web3Signer};
+
+#[derive(Parser)]
+#[command(
+    name = "quorum-vault-web3signer",
+    version,
+    about = "Web3Signer compatible signing service backed by Vault"
+)]
+struct Args {
+    /// Configuration file (TOML, YAML or JSON), overridden by the environment
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Address of Vault [default: VAULT_ADDR or http://127.0.0.1:8200]
+    #[arg(long)]
+    address: Option<String>,
+    /// Vault token [default: VAULT_TOKEN]
+    #[arg(long)]
+    token: Option<String>,
+    /// Mount of the plugin [default: QUORUM_VAULT_MOUNT or quorum]
+    #[arg(long)]
+    mount: Option<String>,
+    /// Signing policy file (TOML or JSON) enforced on every signature
+    #[arg(long)]
+    policy: Option<PathBuf>,
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:9000")]
+    listen: SocketAddr,
+}
+
+async fn run(args: Args) -> Result<(), ClientError> {
+    let arguments = Config {
+        address: args.address,
+        token: args.token,
+        mount: args.mount,
+        ..Config::default()
+    };
+    let signer = Config::load(args.config)?.merge(arguments).signer()?;
+    let policy = match args.policy {
+        Some(path) => SigningPolicy::from_file(path)?,
+        None => SigningPolicy::default(),
+    };
+    let signer = PolicySigner::new(signer, PolicyEngine::new(policy));
+    let app = router(Arc::new(Web3Signer::new(signer)));
+    axum::Server::try_bind(&args.listen)
+        .map_err(|e| ClientError::ConfigError(e.to_string()))?
+        .serve(app.into_make_service())
+        .await
+        .map_err(|e| ClientError::ConfigError(e.to_string()))
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Args::parse()).await {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
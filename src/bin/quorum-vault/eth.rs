@@ -0,0 +1,104 @@
+use clap::Subcommand;
+use quorum_vault_client::api::ethereum;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::{Address, TransactionRequest, VaultClient, U256};
+
+use crate::{parse_data, Data, Output, PrivateKey};
+
+#[derive(Subcommand)]
+pub enum EthCommand {
+    /// Create an account
+    Create,
+    /// List accounts
+    List,
+    /// Read an account
+    Read { address: Address },
+    /// Import a hex encoded private key
+    Import {
+        #[command(flatten)]
+        private_key: PrivateKey,
+    },
+    /// Sign the keccak256 hash of data
+    Sign {
+        address: Address,
+        /// `0x` prefixed hex, or UTF-8 text
+        #[arg(value_parser = parse_data)]
+        data: Data,
+    },
+    /// Sign a legacy transaction
+    SignTx {
+        /// Sender account
+        from: Address,
+        #[arg(long)]
+        chain_id: u64,
+        #[arg(long)]
+        to: Address,
+        /// Value in wei
+        #[arg(long, value_parser = parse_u256, default_value = "0")]
+        value: U256,
+        #[arg(long, default_value_t = 21000)]
+        gas: u64,
+        /// Gas price in wei
+        #[arg(long, value_parser = parse_u256)]
+        gas_price: U256,
+        #[arg(long)]
+        nonce: u64,
+        /// `0x` prefixed hex call data
+        #[arg(long, value_parser = parse_data)]
+        data: Option<Data>,
+    },
+}
+
+/// Decimal or `0x` prefixed hex amount.
+fn parse_u256(value: &str) -> Result<U256, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| e.to_string()),
+        None => U256::from_dec_str(value).map_err(|e| e.to_string()),
+    }
+}
+
+pub async fn run(
+    command: EthCommand,
+    client: &VaultClient,
+    mount: &str,
+    output: &Output,
+) -> Result<(), ClientError> {
+    match command {
+        EthCommand::Create => output.print(&ethereum::create_account(client, mount).await?),
+        EthCommand::List => output.print(&ethereum::list_accounts(client, mount).await?),
+        EthCommand::Read { address } => {
+            output.print(&ethereum::read_account(client, mount, address).await?)
+        }
+        EthCommand::Import { private_key } => {
+            let private_key = private_key.read()?;
+            output.print(
+                &ethereum::import_private_key(client, mount, private_key.trim_start_matches("0x"))
+                    .await?,
+            )
+        }
+        EthCommand::Sign { address, data } => {
+            output.print(&ethereum::sign(client, mount, address, &data.0).await?)
+        }
+        EthCommand::SignTx {
+            from,
+            chain_id,
+            to,
+            value,
+            gas,
+            gas_price,
+            nonce,
+            data,
+        } => {
+            let mut transaction = TransactionRequest::builder()
+                .from(from)
+                .to(to)
+                .value(value)
+                .gas(U256::from(gas))
+                .nonce(U256::from(nonce))
+                .data(data.map(|data| data.0).unwrap_or_default().into())
+                .build();
+            transaction.gas_price = Some(gas_price);
+            output.print(&ethereum::sign_transaction(client, mount, chain_id, transaction).await?)
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::inventory::{self, Inventory};
+use quorum_vault_client::VaultClient;
+
+use crate::Output;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    #[arg(long, value_enum, default_value = "json")]
+    format: Format,
+    /// File written instead of stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+    /// Accounts and keys read at a time
+    #[arg(long, default_value = "16")]
+    concurrency: usize,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Previous export, read as CSV when its extension is `.csv` and as JSON otherwise
+    old: PathBuf,
+    /// Newer export
+    new: PathBuf,
+}
+
+pub async fn export(
+    args: ExportArgs,
+    client: &VaultClient,
+    mount: &str,
+) -> Result<(), ClientError> {
+    let inventory = inventory::export(client, mount, args.concurrency).await?;
+    let content = match args.format {
+        Format::Json => inventory.to_json() + "\n",
+        Format::Csv => inventory.to_csv(),
+    };
+    match args.output {
+        Some(path) => std::fs::write(&path, content)
+            .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display()))),
+        None => {
+            print!("{content}");
+            Ok(())
+        }
+    }
+}
+
+pub fn diff(args: DiffArgs, output: &Output) -> Result<(), ClientError> {
+    let old = Inventory::from_file(&args.old)?;
+    let new = Inventory::from_file(&args.new)?;
+    let diff = inventory::diff(&old, &new);
+    if output.json {
+        output.print(&diff)?;
+    } else {
+        print!("{diff}");
+    }
+    if diff.is_empty() {
+        Ok(())
+    } else {
+        Err(ClientError::Conflict("the exports differ".to_string()))
+    }
+}
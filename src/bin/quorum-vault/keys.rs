@@ -0,0 +1,126 @@
+use clap::{Subcommand, ValueEnum};
+use quorum_vault_client::api::keys::{self, KeyCryptoAlgorithm};
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::VaultClient;
+
+use crate::{parse_data, parse_tag, tags, Data, Output, PrivateKey};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Algorithm {
+    /// ecdsa over secp256k1
+    Secp256k1,
+    /// eddsa over babyjubjub
+    Babyjubjub,
+}
+
+impl From<Algorithm> for KeyCryptoAlgorithm {
+    fn from(algorithm: Algorithm) -> Self {
+        match algorithm {
+            Algorithm::Secp256k1 => KeyCryptoAlgorithm::Secp256k1,
+            Algorithm::Babyjubjub => KeyCryptoAlgorithm::Babyjubjub,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum KeysCommand {
+    /// Create a key
+    Create {
+        id: String,
+        #[arg(long, value_enum, default_value = "secp256k1")]
+        algorithm: Algorithm,
+        /// Tag in the key=value form, can be repeated
+        #[arg(long = "tag", value_parser = parse_tag)]
+        tags: Vec<(String, String)>,
+    },
+    /// List keys
+    List,
+    /// Read a key
+    Read { id: String },
+    /// Replace the tags of a key
+    Tag {
+        id: String,
+        /// Tag in the key=value form, can be repeated, none clears the tags
+        #[arg(long = "tag", value_parser = parse_tag)]
+        tags: Vec<(String, String)>,
+    },
+    /// Destroy a key
+    Destroy { id: String },
+    /// Import a base64url encoded private key
+    Import {
+        id: String,
+        #[command(flatten)]
+        private_key: PrivateKey,
+        #[arg(long, value_enum, default_value = "secp256k1")]
+        algorithm: Algorithm,
+        /// Tag in the key=value form, can be repeated
+        #[arg(long = "tag", value_parser = parse_tag)]
+        tags: Vec<(String, String)>,
+    },
+    /// Sign the keccak256 hash of data
+    Sign {
+        id: String,
+        /// `0x` prefixed hex, or UTF-8 text
+        #[arg(value_parser = parse_data)]
+        data: Data,
+        /// Sign the data as is, it must be a 32 byte hash
+        #[arg(long)]
+        prehashed: bool,
+    },
+}
+
+pub async fn run(
+    command: KeysCommand,
+    client: &VaultClient,
+    mount: &str,
+    output: &Output,
+) -> Result<(), ClientError> {
+    match command {
+        KeysCommand::Create {
+            id,
+            algorithm,
+            tags: tag_list,
+        } => output
+            .print(&keys::create_key(client, mount, &id, algorithm.into(), tags(tag_list)).await?),
+        KeysCommand::List => output.print(&keys::list_keys(client, mount).await?),
+        KeysCommand::Read { id } => output.print(&keys::read_key(client, mount, &id).await?),
+        KeysCommand::Tag { id, tags: tag_list } => {
+            output.print(&keys::update_key_tags(client, mount, &id, tags(tag_list)).await?)
+        }
+        KeysCommand::Destroy { id } => {
+            keys::destroy_key(client, mount, &id).await?;
+            output.print(&())
+        }
+        KeysCommand::Import {
+            id,
+            private_key,
+            algorithm,
+            tags: tag_list,
+        } => output.print(
+            &keys::import_key(
+                client,
+                mount,
+                &id,
+                algorithm.into(),
+                tags(tag_list),
+                &private_key.read()?,
+            )
+            .await?,
+        ),
+        KeysCommand::Sign {
+            id,
+            data,
+            prehashed,
+        } => {
+            let response = if prehashed {
+                let hash: [u8; 32] = data.0.try_into().map_err(|_| {
+                    ClientError::ConfigError("prehashed data must be 32 bytes".to_string())
+                })?;
+                keys::sign_hash(client, mount, &id, hash).await?
+            } else {
+                keys::sign(client, mount, &id, &data.0).await?
+            };
+            output.print(&response)
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Command-line tool for the Quorum Vault plugin.
+//!
+//! ```text
+//! export VAULT_ADDR=http://127.0.0.1:8200 VAULT_TOKEN=root
+//! quorum-vault eth create
+//! quorum-vault keys create my-key --algorithm secp256k1 --tag env=dev
+//! quorum-vault --json zk list
+//! quorum-vault export --format csv --output inventory.csv
+//! ```
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use quorum_vault_client::config::Config;
+use quorum_vault_client::error::ClientError;
+use serde::Serialize;
+use serde_json::Value;
+
+mod eth;
+mod inventory;
+mod keys;
+mod zk;
+
+#[derive(Parser)]
+#[command(
+    name = "quorum-vault",
+    version,
+    about = "Manage Quorum Vault plugin accounts and keys"
+)]
+struct Cli {
+    /// Configuration file (TOML, YAML or JSON), overridden by the environment
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Address of Vault [default: VAULT_ADDR or http://127.0.0.1:8200]
+    #[arg(long, global = true)]
+    address: Option<String>,
+    /// Vault token [default: VAULT_TOKEN]
+    #[arg(long, global = true)]
+    token: Option<String>,
+    /// Mount of the plugin [default: QUORUM_VAULT_MOUNT or quorum]
+    #[arg(long, global = true)]
+    mount: Option<String>,
+    /// Print responses as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Ethereum accounts
+    #[command(subcommand)]
+    Eth(eth::EthCommand),
+    /// Keys
+    #[command(subcommand)]
+    Keys(keys::KeysCommand),
+    /// zk-SNARKs accounts
+    #[command(subcommand)]
+    Zk(zk::ZkCommand),
+    /// Export every account and key of the mount
+    Export(inventory::ExportArgs),
+    /// Compare two exports, failing when they differ
+    Diff(inventory::DiffArgs),
+}
+
+/// Prints responses either as JSON or as `field: value` lines.
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub fn print<T: Serialize>(&self, response: &T) -> Result<(), ClientError> {
+        let value =
+            serde_json::to_value(response).map_err(|e| ClientError::ConfigError(e.to_string()))?;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&value).unwrap());
+            return Ok(());
+        }
+        match value {
+            Value::Object(fields) => {
+                // list responses only carry `keys`, printed one per line
+                if let (1, Some(Value::Array(items))) = (fields.len(), fields.get("keys")) {
+                    items.iter().for_each(|item| println!("{}", text(item)));
+                    return Ok(());
+                }
+                for (name, value) in fields {
+                    match value {
+                        Value::Object(entries) => {
+                            println!("{name}:");
+                            let mut entries: Vec<_> = entries.into_iter().collect();
+                            entries.sort_by(|a, b| a.0.cmp(&b.0));
+                            for (key, value) in entries {
+                                println!("  {key}: {}", text(&value));
+                            }
+                        }
+                        value => println!("{name}: {}", text(&value)),
+                    }
+                }
+            }
+            Value::Null => {}
+            value => println!("{}", text(&value)),
+        }
+        Ok(())
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Data argument: `0x` prefixed hex, or UTF-8 text otherwise.
+#[derive(Clone)]
+pub struct Data(pub Vec<u8>);
+
+pub fn parse_data(value: &str) -> Result<Data, String> {
+    match value.strip_prefix("0x") {
+        Some(encoded) => hex::decode(encoded).map(Data).map_err(|e| e.to_string()),
+        None => Ok(Data(value.as_bytes().to_vec())),
+    }
+}
+
+/// Source of a private key, kept out of the arguments so it does not show in the process
+/// list or the shell history. Read from stdin when neither option is given.
+#[derive(Args)]
+pub struct PrivateKey {
+    /// File holding the private key
+    #[arg(long, conflicts_with = "private_key_env")]
+    private_key_file: Option<PathBuf>,
+    /// Environment variable holding the private key
+    #[arg(long)]
+    private_key_env: Option<String>,
+}
+
+impl PrivateKey {
+    pub fn read(&self) -> Result<String, ClientError> {
+        let key = match (&self.private_key_file, &self.private_key_env) {
+            (Some(path), _) => std::fs::read_to_string(path)
+                .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?,
+            (None, Some(name)) => std::env::var(name).map_err(|_| {
+                ClientError::ConfigError(format!("environment variable {name} is not set"))
+            })?,
+            (None, None) => {
+                let mut key = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut key)
+                    .map_err(|e| ClientError::ConfigError(format!("stdin: {e}")))?;
+                key
+            }
+        };
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(ClientError::ConfigError("empty private key".to_string()));
+        }
+        Ok(key.to_string())
+    }
+}
+
+/// Tag argument in the `key=value` form.
+pub fn parse_tag(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("invalid tag `{value}`, expected key=value"))
+}
+
+pub fn tags(tags: Vec<(String, String)>) -> HashMap<String, String> {
+    tags.into_iter().collect()
+}
+
+fn config(cli: &Cli) -> Result<Config, ClientError> {
+    let arguments = Config {
+        address: cli.address.clone(),
+        token: cli.token.clone(),
+        mount: cli.mount.clone(),
+        ..Config::default()
+    };
+    Ok(Config::load(cli.config.as_ref())?.merge(arguments))
+}
+
+async fn run(cli: Cli) -> Result<(), ClientError> {
+    let config = config(&cli)?;
+    let client = config.client()?;
+    let mount = config.mount();
+    let output = Output { json: cli.json };
+    match cli.command {
+        Command::Eth(command) => eth::run(command, &client, mount, &output).await,
+        Command::Keys(command) => keys::run(command, &client, mount, &output).await,
+        Command::Zk(command) => zk::run(command, &client, mount, &output).await,
+        Command::Export(args) => inventory::export(args, &client, mount).await,
+        Command::Diff(args) => inventory::diff(args, &output),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run(Cli::parse()).await {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
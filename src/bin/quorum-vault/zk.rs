@@ -0,0 +1,41 @@
+use clap::Subcommand;
+use quorum_vault_client::api::zksnarks;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::VaultClient;
+
+use crate::{parse_data, Data, Output};
+
+#[derive(Subcommand)]
+pub enum ZkCommand {
+    /// Create an account
+    Create,
+    /// List accounts
+    List,
+    /// Read an account
+    Read { id: String },
+    /// Sign the keccak256 hash of data
+    Sign {
+        id: String,
+        /// `0x` prefixed hex, or UTF-8 text
+        #[arg(value_parser = parse_data)]
+        data: Data,
+    },
+}
+
+pub async fn run(
+    command: ZkCommand,
+    client: &VaultClient,
+    mount: &str,
+    output: &Output,
+) -> Result<(), ClientError> {
+    match command {
+        ZkCommand::Create => output.print(&zksnarks::create_zksnarks_account(client, mount).await?),
+        ZkCommand::List => output.print(&zksnarks::list_zksnarks_accounts(client, mount).await?),
+        ZkCommand::Read { id } => {
+            output.print(&zksnarks::read_zksnarks_account(client, mount, &id).await?)
+        }
+        ZkCommand::Sign { id, data } => {
+            output.print(&zksnarks::zksnarks_sign(client, mount, &id, &data.0).await?)
+        }
+    }
+}
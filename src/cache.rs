@@ -0,0 +1,467 @@
+//! Caching of account and key lookups.
+//!
+//! Public keys and addresses almost never change, yet services often read them on every
+//! request. A [CachingSigner] keeps the results of reads and lists of another signer for
+//! [CacheConfig::ttl], holding at most [CacheConfig::max_entries] accounts or keys per
+//! backend. Creating, importing, updating or destroying through the same signer updates
+//! or drops the affected entries, changes made by other clients show up once the entries
+//! expire:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use quorum_vault_client::cache::{CacheConfig, CachingSigner};
+//! use quorum_vault_client::config::Config;
+//! use quorum_vault_client::signer::KeySigner;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config = CacheConfig {
+//!         ttl: Duration::from_secs(300),
+//!         ..CacheConfig::default()
+//!     };
+//!     let signer = CachingSigner::new(Config::from_env().signer().unwrap(), config);
+//!     // the second read is answered from the cache
+//!     let key = signer.read_key("my-key").await.unwrap();
+//!     let key = signer.read_key("my-key").await.unwrap();
+//!     println!("public key: {}", key.public_key);
+//! }
+//! ```
+//!
+//! Signing is never cached.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use web3::types::{Address, TransactionRequest};
+
+use crate::api::ethereum::responses::{
+    EthereumAccountResponse, EthereumAccountsResponse, EthereumSignResponse,
+    EthereumSignTransactionResponse,
+};
+use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::zksnarks::responses::{
+    ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
+};
+use crate::error::ClientError;
+use crate::signer::{EthereumSigner, KeySigner, ZkSigner};
+
+/// How long and how many lookups a [CachingSigner] keeps.
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    /// Time after which an entry is read from Vault again
+    pub ttl: Duration,
+    /// Accounts or keys kept per backend, the oldest entries are dropped first
+    pub max_entries: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            max_entries: 1024,
+        }
+    }
+}
+
+/// Entries expiring after a TTL, bounded in number.
+struct TtlCache<K, V> {
+    entries: HashMap<K, (Instant, V)>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &K, ttl: Duration) -> Option<V> {
+        match self.entries.get(key) {
+            Some((inserted, value)) if inserted.elapsed() < ttl => Some(value.clone()),
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V, config: &CacheConfig) {
+        if config.max_entries == 0 {
+            return;
+        }
+        if !self.entries.contains_key(&key) && self.entries.len() >= config.max_entries {
+            self.entries
+                .retain(|_, (inserted, _)| inserted.elapsed() < config.ttl);
+            while self.entries.len() >= config.max_entries {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, (inserted, _))| *inserted)
+                    .map(|(key, _)| key.clone());
+                match oldest {
+                    Some(oldest) => self.entries.remove(&oldest),
+                    None => break,
+                };
+            }
+        }
+        self.entries.insert(key, (Instant::now(), value));
+    }
+
+    fn remove(&mut self, key: &K) {
+        self.entries.remove(key);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Cached entries of one backend, the list being kept apart from the entries.
+///
+/// The generation is bumped by every change, a value read from the signer is only cached
+/// when no change happened while it was being read, so it cannot bring back stale data.
+struct Backend<K, V, L> {
+    entries: TtlCache<K, V>,
+    list: Option<(Instant, L)>,
+    generation: u64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone, L: Clone> Backend<K, V, L> {
+    fn new() -> Self {
+        Self {
+            entries: TtlCache::new(),
+            list: None,
+            generation: 0,
+        }
+    }
+
+    /// Returns the cached entry `key`, or the generation to cache it with once read.
+    fn get(&mut self, key: &K, ttl: Duration) -> Result<V, u64> {
+        self.entries.get(key, ttl).ok_or(self.generation)
+    }
+
+    /// Returns the cached list, or the generation to cache it with once read.
+    fn list(&self, ttl: Duration) -> Result<L, u64> {
+        self.list
+            .as_ref()
+            .filter(|(inserted, _)| inserted.elapsed() < ttl)
+            .map(|(_, list)| list.clone())
+            .ok_or(self.generation)
+    }
+
+    /// Caches an entry read at `generation` unless it changed since.
+    fn insert_read(&mut self, generation: u64, key: K, value: V, config: &CacheConfig) {
+        if generation == self.generation {
+            self.entries.insert(key, value, config);
+        }
+    }
+
+    /// Caches a list read at `generation` unless it changed since.
+    fn insert_list(&mut self, generation: u64, list: L) {
+        if generation == self.generation {
+            self.list = Some((Instant::now(), list));
+        }
+    }
+
+    /// Records a change of `key`, caching its new value when known, and drops the list.
+    fn changed(&mut self, key: Option<&K>, value: Option<V>, config: &CacheConfig) {
+        self.generation += 1;
+        self.list = None;
+        match (key, value) {
+            (Some(key), Some(value)) => self.entries.insert(key.clone(), value, config),
+            (Some(key), None) => self.entries.remove(key),
+            (None, _) => {}
+        }
+    }
+
+    fn clear(&mut self) {
+        self.generation += 1;
+        self.entries.clear();
+        self.list = None;
+    }
+}
+
+/// Signer caching the reads and lists of another signer.
+pub struct CachingSigner<S> {
+    signer: S,
+    config: CacheConfig,
+    accounts: Mutex<Backend<Address, EthereumAccountResponse, EthereumAccountsResponse>>,
+    keys: Mutex<Backend<String, KeyResponse, KeysResponse>>,
+    zksnarks_accounts: Mutex<Backend<String, ZkSnarksAccountResponse, ZkSnarksAccountsResponse>>,
+}
+
+impl<S> CachingSigner<S> {
+    pub fn new(signer: S, config: CacheConfig) -> Self {
+        Self {
+            signer,
+            config,
+            accounts: Mutex::new(Backend::new()),
+            keys: Mutex::new(Backend::new()),
+            zksnarks_accounts: Mutex::new(Backend::new()),
+        }
+    }
+
+    /// Returns the wrapped signer
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Returns the cache configuration
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Drops every cached entry, e.g. after changes made by another client.
+    pub fn invalidate(&self) {
+        self.accounts.lock().unwrap().clear();
+        self.keys.lock().unwrap().clear();
+        self.zksnarks_accounts.lock().unwrap().clear();
+    }
+
+    /// Drops the cached key `id` and the cached key list.
+    pub fn invalidate_key(&self, id: &str) {
+        self.keys
+            .lock()
+            .unwrap()
+            .changed(Some(&id.to_string()), None, &self.config);
+    }
+
+    /// Caches an account created or imported, the account list is dropped even when the
+    /// call failed as it may have been applied anyway.
+    fn store_account(&self, result: &Result<EthereumAccountResponse, ClientError>) {
+        let account = result.as_ref().ok();
+        self.accounts.lock().unwrap().changed(
+            account.map(|account| &account.address),
+            account.cloned(),
+            &self.config,
+        );
+    }
+
+    /// Caches the key returned by a change of `id`, or drops it when the change failed
+    /// as it may have been applied anyway.
+    fn store_key(&self, id: &str, result: &Result<KeyResponse, ClientError>) {
+        let key = result.as_ref().ok().cloned();
+        self.keys
+            .lock()
+            .unwrap()
+            .changed(Some(&id.to_string()), key, &self.config);
+    }
+}
+
+#[async_trait]
+impl<S: EthereumSigner> EthereumSigner for CachingSigner<S> {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError> {
+        let result = self.signer.create_account().await;
+        self.store_account(&result);
+        result
+    }
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError> {
+        let generation = match self.accounts.lock().unwrap().list(self.config.ttl) {
+            Ok(list) => return Ok(list),
+            Err(generation) => generation,
+        };
+        let list = self.signer.list_accounts().await?;
+        self.accounts
+            .lock()
+            .unwrap()
+            .insert_list(generation, list.clone());
+        Ok(list)
+    }
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError> {
+        let generation = match self.accounts.lock().unwrap().get(&address, self.config.ttl) {
+            Ok(account) => return Ok(account),
+            Err(generation) => generation,
+        };
+        let account = self.signer.read_account(address).await?;
+        self.accounts.lock().unwrap().insert_read(
+            generation,
+            address,
+            account.clone(),
+            &self.config,
+        );
+        Ok(account)
+    }
+
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        let result = self.signer.import_private_key(private_key).await;
+        self.store_account(&result);
+        result
+    }
+
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError> {
+        self.signer.sign_transaction(chain_id, transaction).await
+    }
+
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError> {
+        self.signer.sign(address, data).await
+    }
+}
+
+#[async_trait]
+impl<S: KeySigner> KeySigner for CachingSigner<S> {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        let result = self.signer.create_key(id, algorithm, tags).await;
+        self.store_key(id, &result);
+        result
+    }
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        let generation = match self
+            .keys
+            .lock()
+            .unwrap()
+            .get(&id.to_string(), self.config.ttl)
+        {
+            Ok(key) => return Ok(key),
+            Err(generation) => generation,
+        };
+        let key = self.signer.read_key(id).await?;
+        self.keys.lock().unwrap().insert_read(
+            generation,
+            id.to_string(),
+            key.clone(),
+            &self.config,
+        );
+        Ok(key)
+    }
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError> {
+        let generation = match self.keys.lock().unwrap().list(self.config.ttl) {
+            Ok(list) => return Ok(list),
+            Err(generation) => generation,
+        };
+        let list = self.signer.list_keys().await?;
+        self.keys
+            .lock()
+            .unwrap()
+            .insert_list(generation, list.clone());
+        Ok(list)
+    }
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        let result = self.signer.update_key_tags(id, tags).await;
+        self.store_key(id, &result);
+        result
+    }
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError> {
+        let result = self.signer.destroy_key(id).await;
+        self.invalidate_key(id);
+        result
+    }
+
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError> {
+        let result = self
+            .signer
+            .import_key(id, algorithm, tags, private_key)
+            .await;
+        self.store_key(id, &result);
+        result
+    }
+
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError> {
+        self.signer.sign_data(id, data).await
+    }
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError> {
+        self.signer.sign_hash(id, hash).await
+    }
+}
+
+#[async_trait]
+impl<S: ZkSigner> ZkSigner for CachingSigner<S> {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError> {
+        let result = self.signer.create_zksnarks_account().await;
+        self.zksnarks_accounts
+            .lock()
+            .unwrap()
+            .changed(None, None, &self.config);
+        result
+    }
+
+    async fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        let generation = match self
+            .zksnarks_accounts
+            .lock()
+            .unwrap()
+            .get(&id.to_string(), self.config.ttl)
+        {
+            Ok(account) => return Ok(account),
+            Err(generation) => generation,
+        };
+        let account = self.signer.read_zksnarks_account(id).await?;
+        self.zksnarks_accounts.lock().unwrap().insert_read(
+            generation,
+            id.to_string(),
+            account.clone(),
+            &self.config,
+        );
+        Ok(account)
+    }
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError> {
+        let generation = match self.zksnarks_accounts.lock().unwrap().list(self.config.ttl) {
+            Ok(list) => return Ok(list),
+            Err(generation) => generation,
+        };
+        let list = self.signer.list_zksnarks_accounts().await?;
+        self.zksnarks_accounts
+            .lock()
+            .unwrap()
+            .insert_list(generation, list.clone());
+        Ok(list)
+    }
+
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.signer.zksnarks_sign(id, data).await
+    }
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.signer.zksnarks_sign_hash(id, hash).await
+    }
+}
@@ -0,0 +1,249 @@
+//! Client configuration shared by services and tools.
+//!
+//! A [Config] is read from a TOML, YAML or JSON file and from the environment, the
+//! environment taking precedence over the file:
+//!
+//! | Field       | Environment variable   | Default                 |
+//! |-------------|------------------------|-------------------------|
+//! | `address`   | `VAULT_ADDR`           | `http://127.0.0.1:8200` |
+//! | `token`     | `VAULT_TOKEN`          |                         |
+//! | `namespace` | `VAULT_NAMESPACE`      |                         |
+//! | `ca_cert`   | `VAULT_CACERT`         |                         |
+//! | `mount`     | `QUORUM_VAULT_MOUNT`   | `quorum`                |
+//! | `backend`   | `QUORUM_VAULT_BACKEND` | `vault`                 |
+//!
+//! The `backend` selects the signer returned by [Config::signer]: `vault` signs with the
+//! plugin, `in-memory` with keys kept in process (`in-memory` feature) for local
+//! development and tests.
+//!
+//! ```no_run
+//! use quorum_vault_client::config::Config;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config = Config::load(Some("vault.toml")).unwrap();
+//!     let client = config.client().unwrap();
+//!     let accounts = quorum_vault_client::api::ethereum::list_accounts(&client, config.mount())
+//!         .await
+//!         .unwrap();
+//!     println!("accounts: {:?}", accounts.keys);
+//! }
+//! ```
+//!
+//! with `vault.toml`:
+//!
+//! ```toml
+//! address = "https://vault.example.com:8200"
+//! namespace = "team"
+//! ca_cert = "/etc/vault/ca.pem"
+//! mount = "quorum"
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use reqwest::header::{HeaderMap, HeaderValue};
+use rustify::clients::reqwest::Client as HTTPClient;
+use serde::{Deserialize, Serialize};
+use vaultrs::client::{VaultClient, VaultClientSettings, VaultClientSettingsBuilder};
+
+use crate::error::ClientError;
+use crate::signer::{BackendSigner, VaultSigner};
+
+/// Address of Vault when none is configured.
+pub const DEFAULT_ADDRESS: &str = "http://127.0.0.1:8200";
+/// Mount of the plugin when none is configured.
+pub const DEFAULT_MOUNT: &str = "quorum";
+/// Backend signing with the plugin in Vault, the default.
+pub const VAULT_BACKEND: &str = "vault";
+/// Backend signing with keys kept in process.
+pub const IN_MEMORY_BACKEND: &str = "in-memory";
+
+const NAMESPACE_HEADER: &str = "X-Vault-Namespace";
+
+/// Connection settings of Vault and mount of the plugin. Unset fields take their default.
+///
+/// The token is redacted from the [Debug] output.
+#[derive(Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub address: Option<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Path of a PEM encoded CA certificate to trust
+    #[serde(default)]
+    pub ca_cert: Option<String>,
+    #[serde(default)]
+    pub mount: Option<String>,
+    /// Signer backend, `vault` or `in-memory`
+    #[serde(default)]
+    pub backend: Option<String>,
+}
+
+impl Config {
+    /// Parses a configuration from JSON
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        serde_json::from_str(json).map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Parses a configuration from TOML
+    pub fn from_toml(toml: &str) -> Result<Self, ClientError> {
+        toml::from_str(toml).map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Parses a configuration from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self, ClientError> {
+        serde_yaml::from_str(yaml).map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Reads a configuration from a file, parsed as TOML when the extension is `.toml`,
+    /// as YAML when it is `.yaml` or `.yml` and as JSON otherwise.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("yaml" | "yml") => Self::from_yaml(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+
+    /// Reads a configuration from the environment variables of the process.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Reads a configuration from environment variables resolved by `lookup`, empty
+    /// values are treated as unset.
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| lookup(name).filter(|value| !value.is_empty());
+        Self {
+            address: var("VAULT_ADDR"),
+            token: var("VAULT_TOKEN"),
+            namespace: var("VAULT_NAMESPACE"),
+            ca_cert: var("VAULT_CACERT"),
+            mount: var("QUORUM_VAULT_MOUNT"),
+            backend: var("QUORUM_VAULT_BACKEND"),
+        }
+    }
+
+    /// Reads the file at `path` if any, then overrides it with the environment.
+    pub fn load(path: Option<impl AsRef<Path>>) -> Result<Self, ClientError> {
+        let file = match path {
+            Some(path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+        Ok(file.merge(Self::from_env()))
+    }
+
+    /// Returns this configuration with the fields set in `other` overridden.
+    pub fn merge(self, other: Config) -> Self {
+        Self {
+            address: other.address.or(self.address),
+            token: other.token.or(self.token),
+            namespace: other.namespace.or(self.namespace),
+            ca_cert: other.ca_cert.or(self.ca_cert),
+            mount: other.mount.or(self.mount),
+            backend: other.backend.or(self.backend),
+        }
+    }
+
+    /// Returns the address of Vault
+    pub fn address(&self) -> &str {
+        self.address.as_deref().unwrap_or(DEFAULT_ADDRESS)
+    }
+
+    /// Returns the mount of the plugin
+    pub fn mount(&self) -> &str {
+        self.mount.as_deref().unwrap_or(DEFAULT_MOUNT)
+    }
+
+    /// Returns the signer backend
+    pub fn backend(&self) -> &str {
+        self.backend.as_deref().unwrap_or(VAULT_BACKEND)
+    }
+
+    /// Creates a client for the configured Vault, sending the namespace with every
+    /// request when one is set.
+    pub fn client(&self) -> Result<VaultClient, ClientError> {
+        let address = self.address();
+        reqwest::Url::parse(address)
+            .map_err(|e| ClientError::ConfigError(format!("invalid address {address}: {e}")))?;
+        let settings = VaultClientSettingsBuilder::default()
+            .address(address)
+            .token(self.token.clone().unwrap_or_default())
+            .ca_certs(self.ca_cert.iter().cloned().collect())
+            .build()
+            .map_err(|e| ClientError::ConfigError(e.to_string()))?;
+        let mut client = VaultClient::new(settings)?;
+        if let Some(namespace) = &self.namespace {
+            client.http = HTTPClient::new(address, http_client(&client.settings, namespace)?);
+        }
+        Ok(client)
+    }
+
+    /// Creates a signer for the configured backend, signing with the configured mount
+    /// for the `vault` backend.
+    pub fn signer(&self) -> Result<BackendSigner, ClientError> {
+        match self.backend() {
+            VAULT_BACKEND => Ok(BackendSigner::Vault(VaultSigner::new(
+                self.client()?,
+                self.mount(),
+            ))),
+            #[cfg(feature = "in-memory")]
+            IN_MEMORY_BACKEND => Ok(BackendSigner::InMemory(crate::signer::InMemorySigner::new())),
+            #[cfg(not(feature = "in-memory"))]
+            IN_MEMORY_BACKEND => Err(ClientError::ConfigError(format!(
+                "backend {IN_MEMORY_BACKEND} requires the in-memory feature"
+            ))),
+            backend => Err(ClientError::ConfigError(format!(
+                "unknown backend {backend}, expected {VAULT_BACKEND} or {IN_MEMORY_BACKEND}"
+            ))),
+        }
+    }
+}
+
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("address", &self.address)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .field("namespace", &self.namespace)
+            .field("ca_cert", &self.ca_cert)
+            .field("mount", &self.mount)
+            .field("backend", &self.backend)
+            .finish()
+    }
+}
+
+/// HTTP client configured like the one [VaultClient::new] builds from `settings`
+/// (timeout, TLS verification and CA certificates), with the namespace header.
+fn http_client(
+    settings: &VaultClientSettings,
+    namespace: &str,
+) -> Result<reqwest::Client, ClientError> {
+    let mut headers = HeaderMap::new();
+    let namespace = HeaderValue::from_str(namespace)
+        .map_err(|e| ClientError::ConfigError(format!("invalid namespace: {e}")))?;
+    headers.insert(NAMESPACE_HEADER, namespace);
+    let mut builder = reqwest::Client::builder()
+        .default_headers(headers)
+        .danger_accept_invalid_certs(!settings.verify);
+    if let Some(timeout) = settings.timeout {
+        builder = builder.timeout(timeout);
+    }
+    for path in &settings.ca_certs {
+        let content =
+            std::fs::read(path).map_err(|e| ClientError::ConfigError(format!("{path}: {e}")))?;
+        let certificate = reqwest::Certificate::from_pem(&content)
+            .map_err(|e| ClientError::ConfigError(format!("{path}: {e}")))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+    builder
+        .build()
+        .map_err(|e| ClientError::ConfigError(e.to_string()))
+}
@@ -1,7 +1,69 @@
 use thiserror::Error;
 
+use crate::policy::PolicyViolation;
+
 #[derive(Error, Debug)]
 pub enum ClientError {
     #[error("Error: {0}")]
     VaultClientError(#[from] vaultrs::error::ClientError),
+    #[error("Web3 error: {0}")]
+    Web3Error(#[from] web3::Error),
+    #[error("Invalid transaction: {0}")]
+    InvalidTransaction(String),
+    #[error("Policy violation: {0}")]
+    PolicyViolation(#[from] PolicyViolation),
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("Unavailable: {0}")]
+    Unavailable(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+}
+
+impl ClientError {
+    /// Returns the HTTP status Vault answered with, if the request reached it.
+    pub fn status(&self) -> Option<u16> {
+        use rustify::errors::ClientError as RestClientError;
+        use vaultrs::error::ClientError as VaultError;
+
+        match self {
+            ClientError::VaultClientError(VaultError::APIError { code, .. })
+            | ClientError::VaultClientError(VaultError::RestClientError {
+                source: RestClientError::ServerResponseError { code, .. },
+            }) => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the request failed before a response was received from Vault.
+    pub fn is_connection_error(&self) -> bool {
+        use rustify::errors::ClientError as RestClientError;
+        use vaultrs::error::ClientError as VaultError;
+
+        matches!(
+            self,
+            ClientError::VaultClientError(VaultError::RestClientError {
+                source: RestClientError::RequestError { .. }
+                    | RestClientError::ResponseError { .. },
+            })
+        )
+    }
+
+    /// Returns whether no connection to Vault could be established, so the request was
+    /// never sent.
+    pub fn is_connect_error(&self) -> bool {
+        use rustify::errors::ClientError as RestClientError;
+        use vaultrs::error::ClientError as VaultError;
+
+        match self {
+            ClientError::VaultClientError(VaultError::RestClientError {
+                source: RestClientError::RequestError { source, .. },
+            }) => source
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(reqwest::Error::is_connect),
+            _ => false,
+        }
+    }
 }
@@ -0,0 +1,552 @@
+//! Failover across the nodes of a Vault HA cluster.
+//!
+//! A [FailoverClient] holds a client per node. [FailoverClient::discover] asks every node
+//! for its role, after which [FailoverClient::read] runs read-only calls on performance
+//! standbys first and [FailoverClient::write] runs other calls on the active node first.
+//! When a node cannot be reached or answers with a `5xx` status, a read moves on to the
+//! next node. A write only moves on when no connection to the node could be established:
+//! a node failing after receiving a write may have applied it, so the error is returned
+//! rather than creating, importing or signing twice. Reads are best made with a single
+//! attempt per node, through the `_with_retry` forms of the `api` functions given
+//! [RetryPolicy::never]. A [CircuitBreaker] stops sending calls to a node after repeated
+//! failures and lets a single trial call through once its open period has elapsed.
+//!
+//! ```no_run
+//! use quorum_vault_client::api;
+//! use quorum_vault_client::config::Config;
+//! use quorum_vault_client::failover::{FailoverClient, FailoverSigner};
+//! use quorum_vault_client::retry::RetryPolicy;
+//! use quorum_vault_client::signer::KeySigner;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let nodes = ["https://vault-0:8200", "https://vault-1:8200", "https://vault-2:8200"];
+//!     let client = FailoverClient::from_config(&Config::from_env(), nodes).unwrap();
+//!     client.discover().await;
+//!     let policy = RetryPolicy::never();
+//!     let keys = client
+//!         .read(|node| api::keys::list_keys_with_retry(node, "quorum", &policy))
+//!         .await
+//!         .unwrap();
+//!     println!("keys: {:?}", keys.keys);
+//!
+//!     let signer = FailoverSigner::new(client, "quorum");
+//!     let key = signer.read_key("my-key").await.unwrap();
+//!     println!("public key: {}", key.public_key);
+//! }
+//! ```
+//!
+//! [FailoverClient] does not implement [Client]: the trait of vaultrs has no hook around
+//! requests, so a request sent through it could not move on to another node. Calls go
+//! through [FailoverClient::read] and [FailoverClient::write] instead.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use vaultrs::client::{Client, VaultClient};
+use vaultrs::sys::ServerStatus;
+use web3::types::{Address, TransactionRequest};
+
+use crate::api;
+use crate::api::ethereum::responses::{
+    EthereumAccountResponse, EthereumAccountsResponse, EthereumSignResponse,
+    EthereumSignTransactionResponse,
+};
+use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::zksnarks::responses::{
+    ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
+};
+use crate::config::Config;
+use crate::error::ClientError;
+use crate::retry::RetryPolicy;
+use crate::signer::{EthereumSigner, KeySigner, ZkSigner};
+
+/// Roles tried in order by writes.
+const WRITE_ROLES: [Role; 4] = [
+    Role::Active,
+    Role::Unknown,
+    Role::PerformanceStandby,
+    Role::Standby,
+];
+
+/// Role of a node in the cluster.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Active,
+    /// Serves reads locally and forwards writes to the active node
+    PerformanceStandby,
+    /// Forwards every request to the active node
+    Standby,
+    /// Sealed, uninitialized or in recovery mode
+    Unavailable,
+    /// Not discovered yet or unreachable during the discovery
+    Unknown,
+}
+
+/// Failures after which calls stop being sent to a node, and for how long.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    /// Consecutive failures opening the circuit
+    pub failure_threshold: u32,
+    /// Time calls are not sent to the node once the circuit is open
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a node as seen by a [FailoverClient].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeStatus {
+    pub address: String,
+    pub role: Role,
+    /// Consecutive failures
+    pub failures: u32,
+    /// Whether the circuit of the node is open
+    pub open: bool,
+}
+
+struct Node {
+    client: VaultClient,
+    health: Mutex<Health>,
+}
+
+#[derive(Clone, Copy)]
+struct Health {
+    role: Role,
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Client failing over between the nodes of a cluster.
+pub struct FailoverClient {
+    nodes: Vec<Node>,
+    breaker: CircuitBreaker,
+}
+
+impl FailoverClient {
+    /// Creates a client over the given nodes, in order of preference.
+    pub fn new(clients: Vec<VaultClient>) -> Result<Self, ClientError> {
+        if clients.is_empty() {
+            return Err(ClientError::ConfigError(
+                "at least one Vault node is required".to_string(),
+            ));
+        }
+        let nodes = clients
+            .into_iter()
+            .map(|client| Node {
+                client,
+                health: Mutex::new(Health {
+                    role: Role::Unknown,
+                    failures: 0,
+                    open_until: None,
+                }),
+            })
+            .collect();
+        Ok(Self {
+            nodes,
+            breaker: CircuitBreaker::default(),
+        })
+    }
+
+    /// Creates a client over the nodes at `addresses`, configured like `config` otherwise.
+    pub fn from_config(
+        config: &Config,
+        addresses: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Result<Self, ClientError> {
+        let clients = addresses
+            .into_iter()
+            .map(|address| {
+                Config {
+                    address: Some(address.into()),
+                    ..config.clone()
+                }
+                .client()
+            })
+            .collect::<Result<_, _>>()?;
+        Self::new(clients)
+    }
+
+    /// Returns the client using another circuit breaker.
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// Returns the state of every node
+    pub fn nodes(&self) -> Vec<NodeStatus> {
+        let now = Instant::now();
+        self.nodes
+            .iter()
+            .map(|node| {
+                let health = *node.health.lock().unwrap();
+                NodeStatus {
+                    address: node.client.settings.address.to_string(),
+                    role: health.role,
+                    failures: health.failures,
+                    open: health.open_until.is_some_and(|until| until > now),
+                }
+            })
+            .collect()
+    }
+
+    /// Asks every node for its role.
+    pub async fn discover(&self) {
+        for node in &self.nodes {
+            let role = match vaultrs::sys::status(&node.client).await {
+                Ok(ServerStatus::OK) => Role::Active,
+                Ok(ServerStatus::PERFSTANDBY) => Role::PerformanceStandby,
+                Ok(ServerStatus::STANDBY) => Role::Standby,
+                Ok(ServerStatus::UNKNOWN) | Err(_) => Role::Unknown,
+                Ok(_) => Role::Unavailable,
+            };
+            node.health.lock().unwrap().role = role;
+        }
+    }
+
+    /// Runs a read-only call, on performance standbys first.
+    pub async fn read<'a, F, Fut, T, E>(&'a self, call: F) -> Result<T, ClientError>
+    where
+        F: Fn(&'a VaultClient) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Into<ClientError>,
+    {
+        self.run(
+            &[
+                Role::PerformanceStandby,
+                Role::Active,
+                Role::Unknown,
+                Role::Standby,
+            ],
+            true,
+            call,
+        )
+        .await
+    }
+
+    /// Runs a call changing data or signing, on the active node first. It only moves on
+    /// to the next node when the request was never sent.
+    pub async fn write<'a, F, Fut, T, E>(&'a self, call: F) -> Result<T, ClientError>
+    where
+        F: Fn(&'a VaultClient) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Into<ClientError>,
+    {
+        self.run(&WRITE_ROLES, false, call).await
+    }
+
+    async fn run<'a, F, Fut, T, E>(
+        &'a self,
+        roles: &[Role],
+        idempotent: bool,
+        call: F,
+    ) -> Result<T, ClientError>
+    where
+        F: Fn(&'a VaultClient) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Into<ClientError>,
+    {
+        let mut last_error = None;
+        let candidates = self.candidates(roles);
+        let candidates_found = !candidates.is_empty();
+        for node in candidates {
+            if !self.acquire(node) {
+                continue;
+            }
+            match call(&node.client).await.map_err(Into::into) {
+                Err(e) if is_node_failure(&e) => {
+                    self.record_failure(node);
+                    if !idempotent && !e.is_connect_error() {
+                        return Err(e);
+                    }
+                    log::warn!(
+                        "Vault node {} failed, failing over: {e}",
+                        node.client.settings.address
+                    );
+                    last_error = Some(e);
+                }
+                // a performance standby behind the active node, not a failure
+                Err(e) if e.status() == Some(412) => last_error = Some(e),
+                result => {
+                    self.record_success(node);
+                    return result;
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            ClientError::Unavailable(if candidates_found {
+                "every Vault node has an open circuit".to_string()
+            } else {
+                "every Vault node is sealed or otherwise unavailable".to_string()
+            })
+        }))
+    }
+
+    /// Nodes with one of `roles`, by preferred role then configured order.
+    fn candidates(&self, roles: &[Role]) -> Vec<&Node> {
+        let mut candidates: Vec<(usize, &Node)> = self
+            .nodes
+            .iter()
+            .filter_map(|node| {
+                let role = node.health.lock().unwrap().role;
+                let rank = roles.iter().position(|preferred| *preferred == role)?;
+                Some((rank, node))
+            })
+            .collect();
+        candidates.sort_by_key(|(rank, _)| *rank);
+        candidates.into_iter().map(|(_, node)| node).collect()
+    }
+
+    /// Returns whether a call may be sent to `node`. Once the open period has elapsed
+    /// the call is the trial of the half-open circuit, which stays open for other calls.
+    fn acquire(&self, node: &Node) -> bool {
+        let mut health = node.health.lock().unwrap();
+        let now = Instant::now();
+        match health.open_until {
+            Some(until) if until > now => false,
+            Some(_) => {
+                health.open_until = Some(now + self.breaker.open_duration);
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn record_success(&self, node: &Node) {
+        let mut health = node.health.lock().unwrap();
+        health.failures = 0;
+        health.open_until = None;
+    }
+
+    fn record_failure(&self, node: &Node) {
+        let mut health = node.health.lock().unwrap();
+        health.failures += 1;
+        // a failed trial call of a half-open circuit opens it again
+        if health.failures >= self.breaker.failure_threshold || health.open_until.is_some() {
+            health.open_until = Some(Instant::now() + self.breaker.open_duration);
+        }
+    }
+
+    /// Sets the token of every node.
+    pub fn set_token(&mut self, token: &str) {
+        for node in &mut self.nodes {
+            node.client.set_token(token);
+        }
+    }
+}
+
+/// Returns whether `error` comes from the node rather than from the request.
+fn is_node_failure(error: &ClientError) -> bool {
+    error.is_connection_error() || error.status().is_some_and(|status| status >= 500)
+}
+
+/// Signer backed by the plugin mounted at `mount` on the nodes of a [FailoverClient].
+pub struct FailoverSigner {
+    client: FailoverClient,
+    mount: String,
+}
+
+impl FailoverSigner {
+    pub fn new(client: FailoverClient, mount: impl Into<String>) -> Self {
+        Self {
+            client,
+            mount: mount.into(),
+        }
+    }
+
+    /// Returns the failover client
+    pub fn client(&self) -> &FailoverClient {
+        &self.client
+    }
+
+    /// Returns the mount of the plugin
+    pub fn mount(&self) -> &str {
+        &self.mount
+    }
+}
+
+#[async_trait]
+impl EthereumSigner for FailoverSigner {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError> {
+        self.client
+            .write(|node| api::ethereum::create_account(node, &self.mount))
+            .await
+    }
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError> {
+        let policy = RetryPolicy::never();
+        self.client
+            .read(|node| api::ethereum::list_accounts_with_retry(node, &self.mount, &policy))
+            .await
+    }
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError> {
+        let policy = RetryPolicy::never();
+        self.client
+            .read(|node| {
+                api::ethereum::read_account_with_retry(node, &self.mount, address, &policy)
+            })
+            .await
+    }
+
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        self.client
+            .write(|node| api::ethereum::import_private_key(node, &self.mount, private_key))
+            .await
+    }
+
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError> {
+        self.client
+            .write(|node| {
+                api::ethereum::sign_transaction(node, &self.mount, chain_id, transaction.clone())
+            })
+            .await
+    }
+
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError> {
+        self.client
+            .write(|node| api::ethereum::sign(node, &self.mount, address, data))
+            .await
+    }
+}
+
+#[async_trait]
+impl KeySigner for FailoverSigner {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        self.client
+            .write(|node| api::keys::create_key(node, &self.mount, id, algorithm, tags.clone()))
+            .await
+    }
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        let policy = RetryPolicy::never();
+        self.client
+            .read(|node| api::keys::read_key_with_retry(node, &self.mount, id, &policy))
+            .await
+    }
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError> {
+        let policy = RetryPolicy::never();
+        self.client
+            .read(|node| api::keys::list_keys_with_retry(node, &self.mount, &policy))
+            .await
+    }
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        self.client
+            .write(|node| api::keys::update_key_tags(node, &self.mount, id, tags.clone()))
+            .await
+    }
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError> {
+        self.client
+            .write(|node| api::keys::destroy_key(node, &self.mount, id))
+            .await
+    }
+
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError> {
+        self.client
+            .write(|node| {
+                api::keys::import_key(node, &self.mount, id, algorithm, tags.clone(), private_key)
+            })
+            .await
+    }
+
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError> {
+        self.client
+            .write(|node| api::keys::sign(node, &self.mount, id, data))
+            .await
+    }
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError> {
+        self.client
+            .write(|node| api::keys::sign_hash(node, &self.mount, id, hash))
+            .await
+    }
+}
+
+#[async_trait]
+impl ZkSigner for FailoverSigner {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError> {
+        self.client
+            .write(|node| api::zksnarks::create_zksnarks_account(node, &self.mount))
+            .await
+    }
+
+    async fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        let policy = RetryPolicy::never();
+        self.client
+            .read(|node| {
+                api::zksnarks::read_zksnarks_account_with_retry(node, &self.mount, id, &policy)
+            })
+            .await
+    }
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError> {
+        let policy = RetryPolicy::never();
+        self.client
+            .read(|node| {
+                api::zksnarks::list_zksnarks_accounts_with_retry(node, &self.mount, &policy)
+            })
+            .await
+    }
+
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.client
+            .write(|node| api::zksnarks::zksnarks_sign(node, &self.mount, id, data))
+            .await
+    }
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.client
+            .write(|node| api::zksnarks::zksnarks_sign_hash(node, &self.mount, id, hash))
+            .await
+    }
+}
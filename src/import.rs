@@ -0,0 +1,532 @@
+//! Bulk import of keys and Ethereum accounts, e.g. when migrating from an HSM export.
+//!
+//! A [Manifest] lists the keys to import with their id, algorithm and tags, and a
+//! reference to their private key rather than the key itself:
+//!
+//! * `file:<path>` reads the hex encoded private key from a file, relative to the manifest
+//! * `env:<name>` reads it from an environment variable
+//!
+//! [import] imports every row, derives the public key of each `secp256k1` private key
+//! locally and checks it against the one reported by Vault. The `babyjubjub` derivation of
+//! the plugin has no published test vector to check a local one against, so those keys
+//! are reported as unverified. The returned [ImportReport] holds one result per row and
+//! never holds key material:
+//!
+//! ```no_run
+//! use quorum_vault_client::config::Config;
+//! use quorum_vault_client::import::{self, Manifest};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config = Config::from_env();
+//!     let client = config.client().unwrap();
+//!     let manifest = Manifest::from_file("hsm-export/manifest.csv").unwrap();
+//!     let report = import::import(&client, config.mount(), &manifest, 8).await;
+//!     std::fs::write("import-report.csv", report.to_csv()).unwrap();
+//! }
+//! ```
+//!
+//! with `hsm-export/manifest.csv`:
+//!
+//! ```text
+//! kind,id,algorithm,tags,key_ref
+//! key,payments-signer,secp256k1,"{""team"":""payments""}",file:keys/payments-signer.hex
+//! key,rollup-prover,babyjubjub,,env:ROLLUP_PROVER_KEY
+//! ethereum_account,0x8d3113e29CB92F44F1762E52D2a0276509b36b82,,,file:keys/treasury.hex
+//! ```
+//!
+//! Keys whose id exists with the same public key are reported as already imported, so an
+//! interrupted import can be run again. Imported keys whose public key does not match are
+//! reported but left in Vault.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use futures::{stream, StreamExt};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use vaultrs::client::Client;
+use web3::signing::keccak256;
+use web3::types::Address;
+use zeroize::Zeroizing;
+
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::{ethereum, keys};
+use crate::error::ClientError;
+use crate::inventory::{csv_field, csv_records, EntryKind};
+
+/// Keys to import, see [import].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub entries: Vec<ManifestEntry>,
+    /// Directory `file:` references are relative to, the one of the manifest file
+    #[serde(skip)]
+    pub base: Option<PathBuf>,
+}
+
+/// Row of a [Manifest].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestEntry {
+    /// `key` or `ethereum_account`
+    #[serde(default = "default_kind")]
+    pub kind: EntryKind,
+    /// Key id, or the expected address of an Ethereum account, checked when set
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Always `secp256k1` for Ethereum accounts
+    #[serde(default = "default_algorithm")]
+    pub algorithm: KeyCryptoAlgorithm,
+    /// Only supported for keys
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// `file:<path>` or `env:<name>` holding the hex encoded private key
+    pub key_ref: String,
+}
+
+fn default_kind() -> EntryKind {
+    EntryKind::Key
+}
+
+fn default_algorithm() -> KeyCryptoAlgorithm {
+    KeyCryptoAlgorithm::Secp256k1
+}
+
+const CSV_COLUMNS: [&str; 5] = ["kind", "id", "algorithm", "tags", "key_ref"];
+
+impl Manifest {
+    /// Parses a manifest from JSON, an object with the list of `entries`.
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        let manifest: Self =
+            serde_json::from_str(json).map_err(|e| ClientError::ConfigError(e.to_string()))?;
+        manifest.validate()
+    }
+
+    /// Parses a manifest from CSV with a header naming the columns `kind`, `id`,
+    /// `algorithm`, `tags` and `key_ref`, in any order. Only `key_ref` is required and
+    /// tags are written as a JSON object.
+    pub fn from_csv(csv: &str) -> Result<Self, ClientError> {
+        let mut records = csv_records(csv)?.into_iter();
+        let header = records.next().unwrap_or_default();
+        if let Some(column) = header.iter().find(|c| !CSV_COLUMNS.contains(&c.as_str())) {
+            return Err(ClientError::ConfigError(format!(
+                "unknown column `{column}`"
+            )));
+        }
+        if !header.iter().any(|column| column == "key_ref") {
+            return Err(ClientError::ConfigError(
+                "missing column `key_ref`".to_string(),
+            ));
+        }
+
+        let mut entries = Vec::new();
+        for (row, record) in records.enumerate() {
+            let row = row + 2;
+            if record.len() != header.len() {
+                return Err(ClientError::ConfigError(format!(
+                    "row {row}: expected {} fields, found {}",
+                    header.len(),
+                    record.len()
+                )));
+            }
+            let fields: BTreeMap<&str, String> = header
+                .iter()
+                .map(String::as_str)
+                .zip(record)
+                .filter(|(_, field)| !field.is_empty())
+                .collect();
+            let invalid = |column| ClientError::ConfigError(format!("row {row}: invalid {column}"));
+            let algorithm = match fields.get("algorithm") {
+                Some(algorithm) => serde_json::from_value(algorithm.as_str().into())
+                    .map_err(|_| invalid("algorithm"))?,
+                None => default_algorithm(),
+            };
+            let tags = match fields.get("tags") {
+                Some(tags) => serde_json::from_str(tags).map_err(|_| invalid("tags"))?,
+                None => HashMap::new(),
+            };
+            entries.push(ManifestEntry {
+                kind: match fields.get("kind") {
+                    Some(kind) => kind.parse()?,
+                    None => default_kind(),
+                },
+                id: fields.get("id").cloned(),
+                algorithm,
+                tags,
+                key_ref: fields
+                    .get("key_ref")
+                    .cloned()
+                    .ok_or_else(|| invalid("key_ref"))?,
+            });
+        }
+        Self {
+            entries,
+            base: None,
+        }
+        .validate()
+    }
+
+    /// Reads a manifest from a file, parsed as CSV when the extension is `.csv` and as JSON
+    /// otherwise. `file:` references are resolved from the directory of the manifest.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?;
+        let mut manifest = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Self::from_csv(&content)?,
+            _ => Self::from_json(&content)?,
+        };
+        manifest.base = path.parent().map(Path::to_path_buf);
+        Ok(manifest)
+    }
+
+    /// Refuses rows that cannot be imported and keys listed more than once.
+    fn validate(self) -> Result<Self, ClientError> {
+        let mut ids = BTreeSet::new();
+        for (row, entry) in self.entries.iter().enumerate() {
+            let error = |reason: &str| {
+                Err(ClientError::ConfigError(format!(
+                    "entry {}: {reason}",
+                    row + 1
+                )))
+            };
+            match (entry.kind, &entry.id) {
+                (EntryKind::Key, None) => return error("keys need an id"),
+                (EntryKind::Key, Some(id)) if !ids.insert(id.as_str()) => {
+                    return error(&format!("key {id} is listed more than once"))
+                }
+                (EntryKind::Key, _) => {}
+                (EntryKind::EthereumAccount, _)
+                    if entry.algorithm != KeyCryptoAlgorithm::Secp256k1 =>
+                {
+                    return error("Ethereum accounts are secp256k1 keys")
+                }
+                (EntryKind::EthereumAccount, _) if !entry.tags.is_empty() => {
+                    return error("Ethereum accounts have no tags")
+                }
+                (EntryKind::EthereumAccount, _) => {}
+                (EntryKind::ZksnarksAccount, _) => {
+                    return error("zk-SNARKs accounts cannot be imported")
+                }
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Outcome of importing a row.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Imported,
+    /// The key existed with the expected public key
+    AlreadyImported,
+    /// Vault reports another public key than the one derived from the private key
+    Mismatch,
+    /// The key was imported or existed, but its public key cannot be derived to check it
+    Unverified,
+    Failed,
+}
+
+impl fmt::Display for ImportStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ImportStatus::Imported => "imported",
+            ImportStatus::AlreadyImported => "already_imported",
+            ImportStatus::Mismatch => "mismatch",
+            ImportStatus::Unverified => "unverified",
+            ImportStatus::Failed => "failed",
+        })
+    }
+}
+
+/// Result of importing a row of a [Manifest].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ImportResult {
+    /// Index of the entry in the manifest, from 1
+    pub row: usize,
+    pub kind: EntryKind,
+    /// Key id or checksummed Ethereum address, when known
+    pub id: Option<String>,
+    pub status: ImportStatus,
+    /// Public key reported by Vault
+    pub public_key: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Results of [import], in the order of the manifest.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub results: Vec<ImportResult>,
+}
+
+impl ImportReport {
+    /// Returns whether every row was imported, with the expected public key where it
+    /// could be checked
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|result| {
+            matches!(
+                result.status,
+                ImportStatus::Imported | ImportStatus::AlreadyImported | ImportStatus::Unverified
+            )
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Writes the report as CSV, one result per row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = "row,kind,id,status,public_key,error\n".to_string();
+        for result in &self.results {
+            let fields = [
+                result.row.to_string(),
+                result.kind.to_string(),
+                result.id.clone().unwrap_or_default(),
+                result.status.to_string(),
+                result.public_key.clone().unwrap_or_default(),
+                result.error.clone().unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            let id = result.id.as_deref().unwrap_or("-");
+            write!(f, "{} {} {id}: {}", result.row, result.kind, result.status)?;
+            match &result.error {
+                Some(error) => writeln!(f, " ({error})")?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Imports every row of `manifest` into `mount`, at most `concurrency` at a time. A
+/// failed row does not stop the others, its error is reported in its result.
+pub async fn import(
+    client: &impl Client,
+    mount: &str,
+    manifest: &Manifest,
+    concurrency: usize,
+) -> ImportReport {
+    let results = stream::iter(manifest.entries.iter().enumerate())
+        .map(|(row, entry)| import_entry(client, mount, manifest, row + 1, entry))
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+    ImportReport { results }
+}
+
+async fn import_entry(
+    client: &impl Client,
+    mount: &str,
+    manifest: &Manifest,
+    row: usize,
+    entry: &ManifestEntry,
+) -> ImportResult {
+    let mut result = ImportResult {
+        row,
+        kind: entry.kind,
+        id: entry.id.clone(),
+        status: ImportStatus::Failed,
+        public_key: None,
+        error: None,
+    };
+    let imported = match entry.kind {
+        EntryKind::EthereumAccount => import_account(client, mount, manifest, entry).await,
+        _ => import_key(client, mount, manifest, entry).await,
+    };
+    match imported {
+        Ok(imported) => {
+            if entry.kind == EntryKind::EthereumAccount {
+                result.id = Some(imported.id);
+            }
+            result.status = imported.status;
+            result.public_key = Some(imported.public_key);
+            if imported.status == ImportStatus::Mismatch {
+                result.error = Some("public key differs from the derived one".to_string());
+            }
+        }
+        Err(e) => result.error = Some(e.to_string()),
+    }
+    let id = result.id.as_deref().unwrap_or("-");
+    match result.status {
+        ImportStatus::Imported | ImportStatus::AlreadyImported | ImportStatus::Unverified => {
+            log::info!("row {row}: {} {id} {}", result.kind, result.status)
+        }
+        _ => log::warn!(
+            "row {row}: {} {id} {}: {}",
+            result.kind,
+            result.status,
+            result.error.as_deref().unwrap_or_default()
+        ),
+    }
+    result
+}
+
+struct Imported {
+    id: String,
+    status: ImportStatus,
+    public_key: String,
+}
+
+async fn import_key(
+    client: &impl Client,
+    mount: &str,
+    manifest: &Manifest,
+    entry: &ManifestEntry,
+) -> Result<Imported, ClientError> {
+    let id = entry.id.as_deref().unwrap_or_default();
+    let secret = KeyMaterial::load(manifest, &entry.key_ref)?;
+    let expected = secret
+        .public_key(entry.algorithm)?
+        .map(|public_key| base64::prelude::BASE64_URL_SAFE.encode(public_key));
+    let private_key = Zeroizing::new(base64::prelude::BASE64_URL_SAFE.encode(&*secret.0));
+    let imported = keys::import_key(
+        client,
+        mount,
+        id,
+        entry.algorithm,
+        entry.tags.clone(),
+        &private_key,
+    )
+    .await
+    .map_err(ClientError::from);
+    let (status, key) = match imported {
+        Ok(key) => (ImportStatus::Imported, key),
+        Err(e) if e.status() == Some(409) => {
+            let key = keys::read_key(client, mount, id).await.map_err(|_| e)?;
+            (ImportStatus::AlreadyImported, key)
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(Imported {
+        id: key.id,
+        status: match expected {
+            Some(expected) => verified(status, &key.public_key, &expected),
+            None => ImportStatus::Unverified,
+        },
+        public_key: key.public_key,
+    })
+}
+
+async fn import_account(
+    client: &impl Client,
+    mount: &str,
+    manifest: &Manifest,
+    entry: &ManifestEntry,
+) -> Result<Imported, ClientError> {
+    let secret = KeyMaterial::load(manifest, &entry.key_ref)?;
+    let public_key = secret.secp256k1_public_key()?;
+    let address = Address::from_slice(&keccak256(&public_key[1..])[12..]);
+    if let Some(id) = &entry.id {
+        let expected: Address = id
+            .parse()
+            .map_err(|_| ClientError::ConfigError(format!("invalid address {id}")))?;
+        if expected != address {
+            return Err(ClientError::ConfigError(format!(
+                "the private key is the one of {}",
+                checksum(address)
+            )));
+        }
+    }
+    let (status, account) = match ethereum::read_account(client, mount, address)
+        .await
+        .map_err(ClientError::from)
+    {
+        Ok(account) => (ImportStatus::AlreadyImported, account),
+        Err(e) if e.status() == Some(404) => {
+            let private_key = Zeroizing::new(hex::encode(&*secret.0));
+            let account = ethereum::import_private_key(client, mount, &private_key).await?;
+            (ImportStatus::Imported, account)
+        }
+        Err(e) => return Err(e),
+    };
+    let expected = format!("0x{}", hex::encode(public_key));
+    let status = match account.address == address {
+        true => verified(status, &account.public_key, &expected),
+        false => ImportStatus::Mismatch,
+    };
+    Ok(Imported {
+        id: checksum(account.address),
+        status,
+        public_key: account.public_key,
+    })
+}
+
+fn verified(status: ImportStatus, public_key: &str, expected: &str) -> ImportStatus {
+    match public_key.eq_ignore_ascii_case(expected) {
+        true => status,
+        false => ImportStatus::Mismatch,
+    }
+}
+
+fn checksum(address: Address) -> String {
+    eth_checksum::checksum(&format!("{address:?}"))
+}
+
+/// Private key read from a key reference. It has no `Debug` implementation and errors
+/// never include it, so it cannot end up in logs or reports, and it is zeroed on drop.
+struct KeyMaterial(Zeroizing<Vec<u8>>);
+
+impl KeyMaterial {
+    fn load(manifest: &Manifest, key_ref: &str) -> Result<Self, ClientError> {
+        let encoded = Zeroizing::new(match key_ref.split_once(':') {
+            Some(("file", path)) => {
+                let path = match &manifest.base {
+                    Some(base) => base.join(path),
+                    None => PathBuf::from(path),
+                };
+                std::fs::read_to_string(&path)
+                    .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?
+            }
+            Some(("env", name)) => std::env::var(name).map_err(|_| {
+                ClientError::ConfigError(format!("environment variable {name} is not set"))
+            })?,
+            _ => {
+                return Err(ClientError::ConfigError(format!(
+                    "invalid key reference `{key_ref}`, expected file:<path> or env:<name>"
+                )))
+            }
+        });
+        let encoded = encoded.trim();
+        hex::decode(encoded.strip_prefix("0x").unwrap_or(encoded))
+            .map(|secret| Self(Zeroizing::new(secret)))
+            .map_err(|_| {
+                ClientError::ConfigError(format!("{key_ref} is not a hex encoded private key"))
+            })
+    }
+
+    /// Derives the public key as reported by the plugin, for the algorithms whose
+    /// derivation is known to match it. A `babyjubjub` private key is only checked for its
+    /// length.
+    fn public_key(&self, algorithm: KeyCryptoAlgorithm) -> Result<Option<Vec<u8>>, ClientError> {
+        match algorithm {
+            KeyCryptoAlgorithm::Secp256k1 => self.secp256k1_public_key().map(Some),
+            KeyCryptoAlgorithm::Babyjubjub if self.0.len() == 32 => Ok(None),
+            KeyCryptoAlgorithm::Babyjubjub => Err(ClientError::ConfigError(
+                "invalid babyjubjub private key".to_string(),
+            )),
+        }
+    }
+
+    /// Derives the uncompressed `secp256k1` public key.
+    fn secp256k1_public_key(&self) -> Result<Vec<u8>, ClientError> {
+        let secret = SecretKey::from_slice(&self.0)
+            .map_err(|_| ClientError::ConfigError("invalid secp256k1 private key".to_string()))?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret);
+        Ok(public_key.serialize_uncompressed().to_vec())
+    }
+}
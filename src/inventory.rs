@@ -0,0 +1,507 @@
+//! Inventory of the accounts and keys of a mount, e.g. for audits.
+//!
+//! [export] gathers every Ethereum account, key and zk-SNARKs account of a mount with
+//! their public keys, namespaces, tags and timestamps into an [Inventory], written as JSON
+//! or CSV. [diff] compares two inventories read back with [Inventory::from_file]:
+//!
+//! ```no_run
+//! use quorum_vault_client::config::Config;
+//! use quorum_vault_client::inventory::{self, Inventory};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config = Config::from_env();
+//!     let client = config.client().unwrap();
+//!     let inventory = inventory::export(&client, config.mount(), 16).await.unwrap();
+//!     std::fs::write("2024-q2.csv", inventory.to_csv()).unwrap();
+//!
+//!     let previous = Inventory::from_file("2024-q1.csv").unwrap();
+//!     print!("{}", inventory::diff(&previous, &inventory));
+//! }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use std::pin::pin;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use vaultrs::client::Client;
+
+use crate::api::keys::query::{self, TagQuery};
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::{ethereum, zksnarks};
+use crate::error::ClientError;
+
+/// Version of the inventory format, increased on incompatible changes.
+pub const INVENTORY_VERSION: u32 = 1;
+
+const CSV_COLUMNS: [&str; 14] = [
+    "inventory_version",
+    "mount",
+    "exported_at",
+    "kind",
+    "id",
+    "curve",
+    "signing_algorithm",
+    "public_key",
+    "compressed_public_key",
+    "namespace",
+    "tags",
+    "created_at",
+    "updated_at",
+    "key_version",
+];
+
+/// Accounts and keys of a mount, see [export].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    pub version: u32,
+    pub mount: String,
+    /// Milliseconds since the Unix epoch at which the export started
+    pub exported_at: u64,
+    /// Entries sorted by kind and id
+    pub entries: Vec<InventoryEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    EthereumAccount,
+    Key,
+    ZksnarksAccount,
+}
+
+impl EntryKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EntryKind::EthereumAccount => "ethereum_account",
+            EntryKind::Key => "key",
+            EntryKind::ZksnarksAccount => "zksnarks_account",
+        }
+    }
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EntryKind {
+    type Err = ClientError;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "ethereum_account" => Ok(EntryKind::EthereumAccount),
+            "key" => Ok(EntryKind::Key),
+            "zksnarks_account" => Ok(EntryKind::ZksnarksAccount),
+            kind => Err(ClientError::ConfigError(format!("unknown kind `{kind}`"))),
+        }
+    }
+}
+
+/// Ethereum account, key or zk-SNARKs account of an [Inventory].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InventoryEntry {
+    pub kind: EntryKind,
+    /// Key id, zk-SNARKs account id or checksummed Ethereum address
+    pub id: String,
+    pub curve: String,
+    pub signing_algorithm: String,
+    pub public_key: String,
+    /// Only reported for Ethereum accounts
+    #[serde(default)]
+    pub compressed_public_key: Option<String>,
+    pub namespace: String,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    /// Only reported for keys
+    #[serde(default)]
+    pub created_at: Option<String>,
+    /// Only reported for keys
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    /// Only reported for keys
+    #[serde(default)]
+    pub key_version: Option<u64>,
+}
+
+impl Inventory {
+    /// Writes the inventory as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Writes the inventory as CSV, one entry per row. Tags are written as a JSON object
+    /// and the inventory version, mount and export time are repeated on every row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = CSV_COLUMNS.join(",");
+        csv.push('\n');
+        for entry in &self.entries {
+            let tags = serde_json::to_string(&entry.tags).unwrap();
+            let fields = [
+                self.version.to_string(),
+                self.mount.clone(),
+                self.exported_at.to_string(),
+                entry.kind.to_string(),
+                entry.id.clone(),
+                entry.curve.clone(),
+                entry.signing_algorithm.clone(),
+                entry.public_key.clone(),
+                entry.compressed_public_key.clone().unwrap_or_default(),
+                entry.namespace.clone(),
+                tags,
+                entry.created_at.clone().unwrap_or_default(),
+                entry.updated_at.clone().unwrap_or_default(),
+                entry
+                    .key_version
+                    .map(|version| version.to_string())
+                    .unwrap_or_default(),
+            ];
+            let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Parses an inventory written by [Inventory::to_json].
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        let inventory: Self =
+            serde_json::from_str(json).map_err(|e| ClientError::ConfigError(e.to_string()))?;
+        inventory.validate()
+    }
+
+    /// Parses an inventory written by [Inventory::to_csv]. An inventory without entries
+    /// has no row to hold its mount and export time, which are left empty.
+    pub fn from_csv(csv: &str) -> Result<Self, ClientError> {
+        let mut records = csv_records(csv)?.into_iter();
+        if records.next().is_none_or(|header| header != CSV_COLUMNS) {
+            return Err(ClientError::ConfigError(format!(
+                "expected the CSV header {}",
+                CSV_COLUMNS.join(",")
+            )));
+        }
+        let mut inventory = Inventory {
+            version: INVENTORY_VERSION,
+            mount: String::new(),
+            exported_at: 0,
+            entries: Vec::new(),
+        };
+        for (row, record) in records.enumerate() {
+            let row = row + 2;
+            let [version, mount, exported_at, kind, id, curve, signing_algorithm, public_key, compressed_public_key, namespace, tags, created_at, updated_at, key_version]: [String; 14] =
+                record.try_into().map_err(|record: Vec<String>| {
+                    ClientError::ConfigError(format!(
+                        "row {row}: expected {} fields, found {}",
+                        CSV_COLUMNS.len(),
+                        record.len()
+                    ))
+                })?;
+            let invalid =
+                |column: &str| ClientError::ConfigError(format!("row {row}: invalid {column}"));
+            inventory.version = version.parse().map_err(|_| invalid("inventory_version"))?;
+            if inventory.version != INVENTORY_VERSION {
+                break;
+            }
+            inventory.mount = mount;
+            inventory.exported_at = exported_at.parse().map_err(|_| invalid("exported_at"))?;
+            inventory.entries.push(InventoryEntry {
+                kind: kind.parse()?,
+                id,
+                curve,
+                signing_algorithm,
+                public_key,
+                compressed_public_key: non_empty(compressed_public_key),
+                namespace,
+                tags: serde_json::from_str(&tags).map_err(|_| invalid("tags"))?,
+                created_at: non_empty(created_at),
+                updated_at: non_empty(updated_at),
+                key_version: non_empty(key_version)
+                    .map(|version| version.parse())
+                    .transpose()
+                    .map_err(|_| invalid("key_version"))?,
+            });
+        }
+        inventory.validate()
+    }
+
+    /// Reads an inventory from a file, parsed as CSV when the extension is `.csv` and as
+    /// JSON otherwise.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Self::from_csv(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+
+    /// Refuses other versions of the format and entries listed more than once.
+    fn validate(self) -> Result<Self, ClientError> {
+        if self.version != INVENTORY_VERSION {
+            return Err(ClientError::ConfigError(format!(
+                "unsupported inventory version {}, expected {INVENTORY_VERSION}",
+                self.version
+            )));
+        }
+        let mut seen = BTreeSet::new();
+        for entry in &self.entries {
+            if !seen.insert((entry.kind, entry.id.as_str())) {
+                return Err(ClientError::ConfigError(format!(
+                    "{} {} is listed more than once",
+                    entry.kind, entry.id
+                )));
+            }
+        }
+        Ok(self)
+    }
+}
+
+fn non_empty(field: String) -> Option<String> {
+    (!field.is_empty()).then_some(field)
+}
+
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Splits CSV into records of fields, following RFC 4180 quoting.
+pub(crate) fn csv_records(csv: &str) -> Result<Vec<Vec<String>>, ClientError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') if field.is_empty() => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(ClientError::ConfigError(
+            "unterminated quoted CSV field".to_string(),
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Reads every Ethereum account, key and zk-SNARKs account of `mount`, at most
+/// `concurrency` at a time. Accounts and keys destroyed while reading are skipped.
+pub async fn export(
+    client: &impl Client,
+    mount: &str,
+    concurrency: usize,
+) -> Result<Inventory, ClientError> {
+    let exported_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let secp256k1 = KeyCryptoAlgorithm::Secp256k1;
+
+    let mut entries = Vec::new();
+    let accounts = collect(ethereum::stream_accounts(client, mount, concurrency)).await?;
+    entries.extend(accounts.into_iter().map(|account| InventoryEntry {
+        kind: EntryKind::EthereumAccount,
+        id: eth_checksum::checksum(&format!("{:?}", account.address)),
+        curve: secp256k1.curve().to_string(),
+        signing_algorithm: secp256k1.signing_algorithm().to_string(),
+        public_key: account.public_key,
+        compressed_public_key: Some(account.compressed_public_key),
+        namespace: account.namespace,
+        tags: BTreeMap::new(),
+        created_at: None,
+        updated_at: None,
+        key_version: None,
+    }));
+
+    let keys = query::read_keys(client, mount, &TagQuery::And(vec![]), concurrency).await?;
+    entries.extend(keys.into_iter().map(|key| InventoryEntry {
+        kind: EntryKind::Key,
+        id: key.id,
+        curve: key.curve,
+        signing_algorithm: key.signing_algorithm,
+        public_key: key.public_key,
+        compressed_public_key: None,
+        namespace: key.namespace,
+        tags: key.tags.into_iter().collect(),
+        created_at: Some(key.created_at),
+        updated_at: Some(key.updated_at),
+        key_version: Some(key.version),
+    }));
+
+    // the details of a zk-SNARKs account do not hold its id
+    let ids = match zksnarks::list_zksnarks_accounts(client, mount).await {
+        Ok(list) => list.keys,
+        Err(e) if e.status() == Some(404) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    let accounts = stream::iter(ids)
+        .map(|id| async move {
+            let account = zksnarks::read_zksnarks_account(client, mount, &id).await;
+            account.map(|account| (id, account))
+        })
+        .buffered(concurrency.max(1));
+    let accounts = collect(accounts).await?;
+    entries.extend(accounts.into_iter().map(|(id, account)| InventoryEntry {
+        kind: EntryKind::ZksnarksAccount,
+        id,
+        curve: account.curve,
+        signing_algorithm: account.signing_algorithm,
+        public_key: account.public_key,
+        compressed_public_key: None,
+        namespace: account.namespace,
+        tags: BTreeMap::new(),
+        created_at: None,
+        updated_at: None,
+        key_version: None,
+    }));
+
+    entries.sort_by(|a, b| (a.kind, &a.id).cmp(&(b.kind, &b.id)));
+    Ok(Inventory {
+        version: INVENTORY_VERSION,
+        mount: mount.to_string(),
+        exported_at,
+        entries,
+    })
+}
+
+/// Collects a listing, skipping the items missing from Vault.
+async fn collect<T, E: Into<ClientError>>(
+    items: impl Stream<Item = Result<T, E>>,
+) -> Result<Vec<T>, ClientError> {
+    let mut items = pin!(items);
+    let mut collected = Vec::new();
+    while let Some(item) = items.next().await {
+        match item.map_err(Into::into) {
+            Ok(item) => collected.push(item),
+            Err(e) if e.status() == Some(404) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(collected)
+}
+
+/// Entry of both inventories whose fields differ.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct EntryChange {
+    pub from: InventoryEntry,
+    pub to: InventoryEntry,
+    /// Names of the fields that differ
+    pub fields: Vec<&'static str>,
+}
+
+/// Differences between two inventories, see [diff].
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InventoryDiff {
+    pub added: Vec<InventoryEntry>,
+    pub removed: Vec<InventoryEntry>,
+    pub changed: Vec<EntryChange>,
+}
+
+impl InventoryDiff {
+    /// Returns whether both inventories hold the same entries
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl fmt::Display for InventoryDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no differences");
+        }
+        for entry in &self.added {
+            writeln!(f, "+ {} {}", entry.kind, entry.id)?;
+        }
+        for entry in &self.removed {
+            writeln!(f, "- {} {}", entry.kind, entry.id)?;
+        }
+        for change in &self.changed {
+            let fields = change.fields.join(", ");
+            writeln!(f, "~ {} {}: {fields}", change.to.kind, change.to.id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the entries of two inventories, matched by kind and id. The mount and export
+/// time are not compared, so exports of a mount moved elsewhere can be checked.
+pub fn diff(old: &Inventory, new: &Inventory) -> InventoryDiff {
+    let index = |inventory: &'_ Inventory| -> BTreeMap<(EntryKind, String), InventoryEntry> {
+        inventory
+            .entries
+            .iter()
+            .map(|entry| ((entry.kind, entry.id.clone()), entry.clone()))
+            .collect()
+    };
+    let (mut old, new) = (index(old), index(new));
+
+    let mut diff = InventoryDiff::default();
+    for (id, entry) in new {
+        match old.remove(&id) {
+            None => diff.added.push(entry),
+            Some(previous) => {
+                let fields = changed_fields(&previous, &entry);
+                if !fields.is_empty() {
+                    diff.changed.push(EntryChange {
+                        from: previous,
+                        to: entry,
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    diff.removed = old.into_values().collect();
+    diff
+}
+
+fn changed_fields(from: &InventoryEntry, to: &InventoryEntry) -> Vec<&'static str> {
+    [
+        ("curve", from.curve != to.curve),
+        (
+            "signing_algorithm",
+            from.signing_algorithm != to.signing_algorithm,
+        ),
+        ("public_key", from.public_key != to.public_key),
+        (
+            "compressed_public_key",
+            from.compressed_public_key != to.compressed_public_key,
+        ),
+        ("namespace", from.namespace != to.namespace),
+        ("tags", from.tags != to.tags),
+        ("created_at", from.created_at != to.created_at),
+        ("updated_at", from.updated_at != to.updated_at),
+        ("key_version", from.key_version != to.key_version),
+    ]
+    .into_iter()
+    .filter_map(|(field, changed)| changed.then_some(field))
+    .collect()
+}
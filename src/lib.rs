@@ -12,6 +12,10 @@
 //!     * List Ethereum Accounts
 //!     * Read Ethereum Account by Address
 //!     * Sign Ethereum Transaction (only Legacy)
+//!     * Automatic nonce management
+//!     * Populate missing transaction fields before signing
+//!     * Speed up or cancel pending transactions
+//!     * Client-side signing policy
 //! * Keys
 //!     * Create Key
 //!     * List Keys
@@ -19,6 +23,27 @@
 //!     * Delete Key
 //!     * Sign Data
 //!     * Import Private Key
+//! * Audit trail of every signing operation
+//! * Client configuration from environment variables and TOML/YAML files
+//! * AppRole, Kubernetes and JWT login with background token renewal
+//! * Token file reloading for Vault Agent sinks
+//! * Retries with exponential backoff and jitter for transient errors, applied automatically
+//!   to reads and lists
+//! * Failover across Vault HA nodes with a circuit breaker
+//! * Caching of account and key lookups with TTL and size bounds
+//! * Batch signing with bounded concurrency
+//! * Streaming listings of account and key details
+//! * Tag queries over keys with an optional cached index
+//! * Patch updates of key tags with conflict detection
+//! * Idempotent `ensure_key` and `ensure_account` (`in-memory` feature), checked `ensure_zksnarks_account`
+//! * Declarative key reconciliation with plan and apply
+//! * Inventory exports of accounts and keys as JSON or CSV, with diffs between exports
+//! * Bulk imports of keys and Ethereum accounts from a manifest, verified against locally derived public keys (`in-memory` feature)
+//! * Signer traits with Vault and in-memory (`in-memory` feature) backends
+//! * `quorum-vault` command-line tool (`cli` feature)
+//! * Web3Signer compatible signing service (`web3signer` feature)
+//! * `eth-signer-proxy` JSON-RPC signing proxy in front of a node (`proxy` feature)
+//! * In-process mock of the plugin and wiremock fixtures for tests (`testing` feature)
 //!
 //! ## Installation
 //! Add the following to your `Cargo.toml`:
@@ -328,7 +353,25 @@
 //! ```
 
 pub mod api;
+pub mod audit;
+pub mod auth;
+pub mod cache;
+pub mod config;
 pub mod error;
+pub mod failover;
+#[cfg(feature = "in-memory")]
+pub mod import;
+pub mod inventory;
+pub mod policy;
+#[cfg(feature = "proxy")]
+pub mod proxy;
+pub mod reconcile;
+pub mod retry;
+pub mod signer;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "web3signer")]
+pub mod web3signer;
 
 #[macro_use]
 extern crate derive_builder;
@@ -0,0 +1,481 @@
+//! Client-side policy evaluated before Ethereum signing requests are sent to Vault.
+//!
+//! A policy is usually loaded from a JSON or TOML file:
+//!
+//! ```toml
+//! allowed_chain_ids = [1, 1337]
+//! allowed_recipients = ["0x1dabe0acaaa4d1f81b9b43eaf51c8439378231a0"]
+//! max_value_per_transaction = "1000000000000000000"
+//! forbidden_selectors = ["0x095ea7b3"]
+//!
+//! allowed_message_signers = ["0xad38e61db0d3f8fef9b4c5dd0c1a9f691cdccff5"]
+//! max_message_size = 1024
+//!
+//! [max_value_per_window]
+//! value = "5000000000000000000"
+//! window_seconds = 86400
+//! ```
+//!
+//! The policy is opt-in: it is only enforced by
+//! [sign_transaction_with_policy][crate::api::ethereum::sign_transaction_with_policy],
+//! [sign_with_policy][crate::api::ethereum::sign_with_policy] and a [PolicySigner]. Every
+//! other function of [api::ethereum][crate::api::ethereum], including the nonce manager,
+//! filler, speed-up and cancellation helpers, signs without checking it. Services signing
+//! through a signer, like the [proxy](crate::proxy) or the Web3Signer service, enforce it
+//! when given a [PolicySigner].
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+use web3::types::{Address, Bytes, TransactionRequest, U256};
+
+use crate::api::ethereum::responses::{
+    EthereumAccountResponse, EthereumAccountsResponse, EthereumSignResponse,
+    EthereumSignTransactionResponse,
+};
+use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::zksnarks::responses::{
+    ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
+};
+use crate::error::ClientError;
+use crate::signer::{EthereumSigner, KeySigner, ZkSigner};
+
+/// Rule of a [SigningPolicy] that rejected a signing request.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    #[error("chain id {0} is not allowed")]
+    ChainNotAllowed(u64),
+    #[error("recipient {0:?} is not allowed")]
+    RecipientNotAllowed(Address),
+    #[error("contract creation is not allowed")]
+    ContractCreationNotAllowed,
+    #[error("value {value} exceeds the limit of {limit} per transaction")]
+    ValueLimitExceeded { value: U256, limit: U256 },
+    #[error("value {value} exceeds the limit of {limit} per {window:?}, {spent} already spent")]
+    WindowLimitExceeded {
+        value: U256,
+        spent: U256,
+        limit: U256,
+        window: Duration,
+    },
+    #[error("function selector 0x{} is forbidden", hex::encode(&selector.0))]
+    ForbiddenSelector { selector: Bytes },
+    #[error("message signing is not allowed")]
+    MessageSigningNotAllowed,
+    #[error("message signing with {0:?} is not allowed")]
+    MessageSignerNotAllowed(Address),
+    #[error("message of {size} bytes exceeds the limit of {limit} bytes")]
+    MessageTooLarge { size: usize, limit: usize },
+}
+
+/// Limit of the total value signed by an account within a rolling window.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ValueWindow {
+    #[serde(deserialize_with = "deserialize_value")]
+    pub value: U256,
+    pub window_seconds: u64,
+}
+
+/// Rules a signing request must satisfy. Unset rules allow everything.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SigningPolicy {
+    #[serde(default)]
+    pub allowed_chain_ids: Option<Vec<u64>>,
+    #[serde(default)]
+    pub allowed_recipients: Option<Vec<Address>>,
+    #[serde(default, deserialize_with = "deserialize_optional_value")]
+    pub max_value_per_transaction: Option<U256>,
+    #[serde(default)]
+    pub max_value_per_window: Option<ValueWindow>,
+    #[serde(default)]
+    pub forbidden_selectors: Vec<Bytes>,
+    #[serde(default = "default_allow_message_signing")]
+    pub allow_message_signing: bool,
+    /// Accounts allowed to sign messages
+    #[serde(default)]
+    pub allowed_message_signers: Option<Vec<Address>>,
+    /// Size limit of signed messages, in bytes
+    #[serde(default)]
+    pub max_message_size: Option<usize>,
+}
+
+impl Default for SigningPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_chain_ids: None,
+            allowed_recipients: None,
+            max_value_per_transaction: None,
+            max_value_per_window: None,
+            forbidden_selectors: Vec::new(),
+            allow_message_signing: default_allow_message_signing(),
+            allowed_message_signers: None,
+            max_message_size: None,
+        }
+    }
+}
+
+impl SigningPolicy {
+    /// Parses a policy from JSON
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        serde_json::from_str(json).map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Parses a policy from TOML
+    pub fn from_toml(toml: &str) -> Result<Self, ClientError> {
+        toml::from_str(toml).map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Reads a policy from a file, parsed as TOML when the extension is `.toml` and as
+    /// JSON otherwise.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+}
+
+/// Reservation id, time and value of a transaction counted in a rolling window
+type Spent = (u64, Instant, U256);
+
+/// Value of a transaction reserved in the rolling window of its sender by
+/// [PolicyEngine::check_transaction], returned with [PolicyEngine::release] when the
+/// transaction is not signed after all.
+#[derive(Debug)]
+#[must_use]
+pub struct Reservation {
+    from: Address,
+    id: Option<u64>,
+}
+
+/// Evaluates a [SigningPolicy] and keeps track of the value signed per account for the
+/// rolling window limit.
+pub struct PolicyEngine {
+    policy: SigningPolicy,
+    spent: Mutex<HashMap<Address, VecDeque<Spent>>>,
+    next_reservation: AtomicU64,
+}
+
+impl PolicyEngine {
+    pub fn new(policy: SigningPolicy) -> Self {
+        Self {
+            policy,
+            spent: Mutex::new(HashMap::new()),
+            next_reservation: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the evaluated policy
+    pub fn policy(&self) -> &SigningPolicy {
+        &self.policy
+    }
+
+    /// Checks a transaction against the policy and, when it passes, reserves its value in
+    /// the rolling window of the sender.
+    ///
+    /// The returned reservation is given back with [PolicyEngine::release] when the
+    /// transaction is not signed after all.
+    pub fn check_transaction(
+        &self,
+        chain_id: u64,
+        transaction: &TransactionRequest,
+    ) -> Result<Reservation, PolicyViolation> {
+        let policy = &self.policy;
+        if let Some(chain_ids) = &policy.allowed_chain_ids {
+            if !chain_ids.contains(&chain_id) {
+                return Err(PolicyViolation::ChainNotAllowed(chain_id));
+            }
+        }
+        if let Some(recipients) = &policy.allowed_recipients {
+            match transaction.to {
+                Some(to) if !recipients.contains(&to) => {
+                    return Err(PolicyViolation::RecipientNotAllowed(to))
+                }
+                None => return Err(PolicyViolation::ContractCreationNotAllowed),
+                _ => {}
+            }
+        }
+        let value = transaction.value.unwrap_or_default();
+        if let Some(limit) = policy.max_value_per_transaction {
+            if value > limit {
+                return Err(PolicyViolation::ValueLimitExceeded { value, limit });
+            }
+        }
+        if let Some(data) = &transaction.data {
+            if let Some(selector) = policy
+                .forbidden_selectors
+                .iter()
+                .find(|selector| !selector.0.is_empty() && data.0.starts_with(&selector.0))
+            {
+                return Err(PolicyViolation::ForbiddenSelector {
+                    selector: selector.clone(),
+                });
+            }
+        }
+        let mut reservation = Reservation {
+            from: transaction.from,
+            id: None,
+        };
+        if let Some(window) = &policy.max_value_per_window {
+            let period = Duration::from_secs(window.window_seconds);
+            let now = Instant::now();
+            let mut spent = self.spent.lock().unwrap();
+            let entries = spent.entry(transaction.from).or_default();
+            while let Some((_, at, _)) = entries.front() {
+                if now.duration_since(*at) < period {
+                    break;
+                }
+                entries.pop_front();
+            }
+            let total = entries.iter().fold(U256::zero(), |total, (_, _, value)| {
+                total.saturating_add(*value)
+            });
+            if total.saturating_add(value) > window.value {
+                return Err(PolicyViolation::WindowLimitExceeded {
+                    value,
+                    spent: total,
+                    limit: window.value,
+                    window: period,
+                });
+            }
+            if !value.is_zero() {
+                let id = self.next_reservation.fetch_add(1, Ordering::Relaxed);
+                entries.push_back((id, now, value));
+                reservation.id = Some(id);
+            }
+        }
+        Ok(reservation)
+    }
+
+    /// Checks that signing the message `data` with `address` is allowed.
+    pub fn check_message(&self, address: Address, data: &[u8]) -> Result<(), PolicyViolation> {
+        let policy = &self.policy;
+        if !policy.allow_message_signing {
+            return Err(PolicyViolation::MessageSigningNotAllowed);
+        }
+        if let Some(signers) = &policy.allowed_message_signers {
+            if !signers.contains(&address) {
+                return Err(PolicyViolation::MessageSignerNotAllowed(address));
+            }
+        }
+        if let Some(limit) = policy.max_message_size {
+            if data.len() > limit {
+                return Err(PolicyViolation::MessageTooLarge {
+                    size: data.len(),
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the value reserved by [PolicyEngine::check_transaction] for a transaction
+    /// that was not signed.
+    pub fn release(&self, reservation: Reservation) {
+        let Some(id) = reservation.id else {
+            return;
+        };
+        let mut spent = self.spent.lock().unwrap();
+        if let Some(entries) = spent.get_mut(&reservation.from) {
+            entries.retain(|(reserved, _, _)| *reserved != id);
+        }
+    }
+}
+
+/// Signer checking transactions and messages against a [PolicyEngine] before the wrapped
+/// signer signs them. Other operations are passed through unchecked.
+pub struct PolicySigner<S> {
+    signer: S,
+    engine: PolicyEngine,
+}
+
+impl<S> PolicySigner<S> {
+    pub fn new(signer: S, engine: PolicyEngine) -> Self {
+        Self { signer, engine }
+    }
+
+    /// Returns the wrapped signer
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Returns the policy engine
+    pub fn engine(&self) -> &PolicyEngine {
+        &self.engine
+    }
+}
+
+#[async_trait]
+impl<S: EthereumSigner> EthereumSigner for PolicySigner<S> {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError> {
+        self.signer.create_account().await
+    }
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError> {
+        self.signer.list_accounts().await
+    }
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError> {
+        self.signer.read_account(address).await
+    }
+
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        self.signer.import_private_key(private_key).await
+    }
+
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError> {
+        let reservation = self.engine.check_transaction(chain_id, &transaction)?;
+        let result = self.signer.sign_transaction(chain_id, transaction).await;
+        if result.is_err() {
+            self.engine.release(reservation);
+        }
+        result
+    }
+
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError> {
+        self.engine.check_message(address, data)?;
+        self.signer.sign(address, data).await
+    }
+}
+
+#[async_trait]
+impl<S: KeySigner> KeySigner for PolicySigner<S> {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        self.signer.create_key(id, algorithm, tags).await
+    }
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        self.signer.read_key(id).await
+    }
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError> {
+        self.signer.list_keys().await
+    }
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        self.signer.update_key_tags(id, tags).await
+    }
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError> {
+        self.signer.destroy_key(id).await
+    }
+
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError> {
+        self.signer
+            .import_key(id, algorithm, tags, private_key)
+            .await
+    }
+
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError> {
+        self.signer.sign_data(id, data).await
+    }
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError> {
+        self.signer.sign_hash(id, hash).await
+    }
+}
+
+#[async_trait]
+impl<S: ZkSigner> ZkSigner for PolicySigner<S> {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError> {
+        self.signer.create_zksnarks_account().await
+    }
+
+    async fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        self.signer.read_zksnarks_account(id).await
+    }
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError> {
+        self.signer.list_zksnarks_accounts().await
+    }
+
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.signer.zksnarks_sign(id, data).await
+    }
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.signer.zksnarks_sign_hash(id, hash).await
+    }
+}
+
+fn default_allow_message_signing() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ValueRepr {
+    Number(u64),
+    Text(String),
+}
+
+fn parse_value(value: ValueRepr) -> Result<U256, String> {
+    match value {
+        ValueRepr::Number(value) => Ok(U256::from(value)),
+        ValueRepr::Text(text) => match text.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| e.to_string()),
+            None => U256::from_dec_str(&text).map_err(|e| e.to_string()),
+        },
+    }
+}
+
+/// Accepts values as integers, decimal strings or `0x` prefixed hex strings.
+fn deserialize_value<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+    parse_value(ValueRepr::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_optional_value<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<U256>, D::Error> {
+    Option::<ValueRepr>::deserialize(deserializer)?
+        .map(parse_value)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,234 @@
+//! JSON-RPC signing proxy in front of an Ethereum node, enabled with the `proxy` feature.
+//!
+//! Like [EthSigner](https://github.com/ConsenSys/ethsigner), the proxy answers
+//! `eth_accounts` with the accounts of a signer and turns `eth_sendTransaction` into an
+//! `eth_sendRawTransaction` signed by it, so dapps expecting an unlocked node work with
+//! keys held in Vault. Missing nonce, gas and gas price are populated by a
+//! [TransactionFiller]. Every other request is forwarded to the node unchanged.
+//!
+//! The `eth-signer-proxy` binary serves it in front of Vault.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use web3::transports::Http;
+use web3::types::{Address, TransactionRequest};
+use web3::Web3;
+
+use crate::api::ethereum::filler::{FillProvider, FilledTransaction, TransactionFiller};
+use crate::api::ethereum::replacement::encode_signed_transaction;
+use crate::error::ClientError;
+use crate::signer::EthereumSigner;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+const SERVER_ERROR: i64 = -32000;
+
+/// Signing proxy for a node.
+pub struct SigningProxy<S, P> {
+    signer: S,
+    filler: TransactionFiller<P>,
+    node: reqwest::Client,
+    node_url: String,
+    accounts: RwLock<HashSet<Address>>,
+}
+
+impl<S: EthereumSigner> SigningProxy<S, Web3<Http>> {
+    /// Creates a proxy for the node at `node_url`, which also populates transactions.
+    pub fn new(signer: S, node_url: &str) -> Result<Self, ClientError> {
+        let provider = Web3::new(Http::new(node_url)?);
+        Ok(Self::with_filler(
+            signer,
+            TransactionFiller::new(provider),
+            node_url,
+        ))
+    }
+}
+
+impl<S: EthereumSigner, P: FillProvider> SigningProxy<S, P> {
+    pub fn with_filler(signer: S, filler: TransactionFiller<P>, node_url: &str) -> Self {
+        Self {
+            signer,
+            filler,
+            node: reqwest::Client::new(),
+            node_url: node_url.to_string(),
+            accounts: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Returns the signer
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Handles a JSON-RPC request or batch of requests.
+    pub async fn handle(&self, request: Value) -> Value {
+        match request {
+            Value::Array(requests) if !requests.is_empty() => {
+                let mut responses = Vec::with_capacity(requests.len());
+                for request in requests {
+                    responses.push(self.handle_single(request).await);
+                }
+                Value::Array(responses)
+            }
+            request => self.handle_single(request).await,
+        }
+    }
+
+    async fn handle_single(&self, request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return error(id, INVALID_REQUEST, "invalid request");
+        };
+        match method {
+            "eth_accounts" => match self.refresh_accounts().await {
+                Ok(accounts) => {
+                    let mut accounts: Vec<Address> = accounts.into_iter().collect();
+                    accounts.sort();
+                    json!({ "jsonrpc": "2.0", "id": id, "result": accounts })
+                }
+                Err(e) => error(id, SERVER_ERROR, &e.to_string()),
+            },
+            "eth_sendTransaction" => {
+                let transaction = request
+                    .get("params")
+                    .and_then(|params| params.get(0))
+                    .cloned()
+                    .map(serde_json::from_value::<TransactionRequest>);
+                match transaction {
+                    Some(Ok(transaction)) => self.send_transaction(id, transaction).await,
+                    _ => error(id, INVALID_PARAMS, "expected a transaction object"),
+                }
+            }
+            _ => self.forward(id, &request).await,
+        }
+    }
+
+    async fn send_transaction(&self, id: Value, transaction: TransactionRequest) -> Value {
+        let from = transaction.from;
+        // Vault signs transactions to an address, a deployment would be a transfer to 0x0
+        if transaction.to.is_none() {
+            return error(
+                id,
+                INVALID_PARAMS,
+                "contract deployments are not supported, `to` is required",
+            );
+        }
+        match self.is_managed(from).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return error(
+                    id,
+                    SERVER_ERROR,
+                    &format!("account {from:?} is not managed by the signer"),
+                )
+            }
+            Err(e) => return error(id, SERVER_ERROR, &e.to_string()),
+        }
+
+        // the nonce is reserved last, a failed fill leaves none to release
+        let filled = match self.filler.fill(transaction).await {
+            Ok(filled) => filled,
+            Err(e) => return error(id, SERVER_ERROR, &e.to_string()),
+        };
+        let raw = match self
+            .signer
+            .sign_transaction(filled.chain_id, filled.transaction.clone())
+            .await
+            .and_then(|response| encode_signed_transaction(&filled, &response.signature))
+        {
+            Ok(raw) => raw,
+            Err(e) => {
+                self.release_nonce(&filled).await;
+                return error(id, SERVER_ERROR, &e.to_string());
+            }
+        };
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "eth_sendRawTransaction",
+            "params": [raw],
+        });
+        let response = self.forward(id, &request).await;
+        if response.get("error").is_some() {
+            self.release_nonce(&filled).await;
+        }
+        response
+    }
+
+    async fn release_nonce(&self, filled: &FilledTransaction) {
+        if let Some(nonce) = filled.reserved_nonce {
+            self.filler
+                .nonce_manager()
+                .release(filled.chain_id, filled.transaction.from, nonce)
+                .await;
+        }
+    }
+
+    async fn is_managed(&self, address: Address) -> Result<bool, ClientError> {
+        if self.accounts.read().await.contains(&address) {
+            return Ok(true);
+        }
+        Ok(self.refresh_accounts().await?.contains(&address))
+    }
+
+    async fn refresh_accounts(&self) -> Result<HashSet<Address>, ClientError> {
+        let accounts: HashSet<Address> = self
+            .signer
+            .list_accounts()
+            .await?
+            .keys
+            .into_iter()
+            .collect();
+        *self.accounts.write().await = accounts.clone();
+        Ok(accounts)
+    }
+
+    async fn forward(&self, id: Value, request: &Value) -> Value {
+        let response = self.node.post(&self.node_url).json(request).send().await;
+        match response {
+            Ok(response) => match response.json::<Value>().await {
+                Ok(response) => response,
+                Err(e) => error(id, INTERNAL_ERROR, &format!("invalid node response: {e}")),
+            },
+            Err(e) => error(id, INTERNAL_ERROR, &format!("node unreachable: {e}")),
+        }
+    }
+}
+
+/// Routes of the proxy, JSON-RPC requests are served on `/`.
+pub fn router<S, P>(proxy: Arc<SigningProxy<S, P>>) -> Router
+where
+    S: EthereumSigner + 'static,
+    P: FillProvider + 'static,
+{
+    Router::new()
+        .route("/", post(handle::<S, P>))
+        .with_state(proxy)
+}
+
+async fn handle<S: EthereumSigner, P: FillProvider>(
+    State(proxy): State<Arc<SigningProxy<S, P>>>,
+    body: Bytes,
+) -> Json<Value> {
+    match serde_json::from_slice(&body) {
+        Ok(request) => Json(proxy.handle(request).await),
+        Err(e) => Json(error(Value::Null, PARSE_ERROR, &e.to_string())),
+    }
+}
+
+fn error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
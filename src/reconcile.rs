@@ -0,0 +1,295 @@
+//! Declarative reconciliation of the keys of a mount.
+//!
+//! A [DesiredState] lists the keys a mount should hold with their algorithm and tags,
+//! typically kept in Git as YAML or JSON. [plan] compares it with the keys reported by
+//! Vault and returns the [Change]s needed to reach it, which [apply] then makes:
+//!
+//! ```no_run
+//! use quorum_vault_client::config::Config;
+//! use quorum_vault_client::reconcile::{self, DesiredState};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let config = Config::from_env();
+//!     let client = config.client().unwrap();
+//!     let desired = DesiredState::from_file("keys.yaml").unwrap();
+//!     let plan = reconcile::plan(&client, config.mount(), &desired, 16).await.unwrap();
+//!     print!("{plan}");
+//!     reconcile::apply(&client, config.mount(), &plan).await.unwrap();
+//! }
+//! ```
+//!
+//! with `keys.yaml`:
+//!
+//! ```yaml
+//! keys:
+//!   - id: payments-signer
+//!     algorithm: secp256k1
+//!     tags:
+//!       env: prod
+//!       team: payments
+//!   - id: rollup-prover
+//!     algorithm: babyjubjub
+//! # soft-delete the keys tagged team=payments missing from the list
+//! prune: true
+//! selector:
+//!   team: payments
+//! ```
+//!
+//! Keys are never destroyed: pruned keys are soft-deleted and can be restored with
+//! [restore_key][crate::api::keys::restore_key]. Soft-deleted keys are not listed, so a
+//! desired key whose id was soft-deleted is planned for creation, which fails until the
+//! key is restored.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use vaultrs::client::Client;
+
+use crate::api::keys::patch::TagPatch;
+use crate::api::keys::query::{self, TagQuery};
+use crate::api::keys::{self, KeyCryptoAlgorithm};
+use crate::error::ClientError;
+
+/// Keys a mount should hold.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredState {
+    #[serde(default)]
+    pub keys: Vec<DesiredKey>,
+    /// Soft-deletes the keys missing from `keys`
+    #[serde(default)]
+    pub prune: bool,
+    /// Tags a key must have to be pruned, every key is pruned when empty
+    #[serde(default)]
+    pub selector: HashMap<String, String>,
+}
+
+/// Key of a [DesiredState].
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct DesiredKey {
+    pub id: String,
+    #[serde(default = "default_algorithm")]
+    pub algorithm: KeyCryptoAlgorithm,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+fn default_algorithm() -> KeyCryptoAlgorithm {
+    KeyCryptoAlgorithm::Secp256k1
+}
+
+impl DesiredState {
+    /// Parses a desired state from JSON
+    pub fn from_json(json: &str) -> Result<Self, ClientError> {
+        serde_json::from_str(json).map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Parses a desired state from YAML
+    pub fn from_yaml(yaml: &str) -> Result<Self, ClientError> {
+        serde_yaml::from_str(yaml).map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Reads a desired state from a file, parsed as YAML when the extension is `.yaml` or
+    /// `.yml` and as JSON otherwise.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ClientError::ConfigError(format!("{}: {e}", path.display())))?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => Self::from_yaml(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+}
+
+/// Change bringing a key to its desired state.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Change {
+    Create {
+        id: String,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    },
+    /// Replaces the tags of a key
+    Retag {
+        id: String,
+        from: HashMap<String, String>,
+        to: HashMap<String, String>,
+    },
+    /// Soft-deletes a key missing from the desired state
+    Delete { id: String },
+    /// Key existing with another algorithm, which cannot be changed
+    Conflict { id: String, reason: String },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Create {
+                id,
+                algorithm,
+                tags: key_tags,
+            } => write!(
+                f,
+                "+ create {id} ({}) {}",
+                algorithm.curve(),
+                tags(key_tags)
+            ),
+            Change::Retag { id, from, to } => {
+                write!(f, "~ retag {id} {} -> {}", tags(from), tags(to))
+            }
+            Change::Delete { id } => write!(f, "- delete {id}"),
+            Change::Conflict { id, reason } => write!(f, "! {id}: {reason}"),
+        }
+    }
+}
+
+fn tags(tags: &HashMap<String, String>) -> String {
+    let sorted: BTreeMap<_, _> = tags.iter().collect();
+    let pairs: Vec<String> = sorted.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    format!("{{{}}}", pairs.join(", "))
+}
+
+/// Changes bringing a mount to a [DesiredState], see [plan].
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan {
+    pub changes: Vec<Change>,
+}
+
+impl Plan {
+    /// Returns whether the mount is in its desired state
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns whether the plan holds conflicts, which [apply] refuses
+    pub fn has_conflicts(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|change| matches!(change, Change::Conflict { .. }))
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares `desired` with the keys of `mount`, reading at most `concurrency` keys at a
+/// time, and returns the changes to make. Nothing is changed in Vault.
+pub async fn plan(
+    client: &impl Client,
+    mount: &str,
+    desired: &DesiredState,
+    concurrency: usize,
+) -> Result<Plan, ClientError> {
+    let mut ids = HashSet::new();
+    if let Some(key) = desired.keys.iter().find(|key| !ids.insert(key.id.as_str())) {
+        return Err(ClientError::ConfigError(format!(
+            "key {} is listed more than once",
+            key.id
+        )));
+    }
+
+    let current = query::read_keys(client, mount, &TagQuery::And(vec![]), concurrency).await?;
+    let current: BTreeMap<&str, _> = current.iter().map(|key| (key.id.as_str(), key)).collect();
+
+    let mut changes = Vec::new();
+    for key in &desired.keys {
+        let change = match current.get(key.id.as_str()) {
+            None => Change::Create {
+                id: key.id.clone(),
+                algorithm: key.algorithm,
+                tags: key.tags.clone(),
+            },
+            Some(existing)
+                if existing.curve != key.algorithm.curve()
+                    || existing.signing_algorithm != key.algorithm.signing_algorithm() =>
+            {
+                Change::Conflict {
+                    id: key.id.clone(),
+                    reason: format!(
+                        "exists with curve {} and signing algorithm {}, expected {}",
+                        existing.curve,
+                        existing.signing_algorithm,
+                        key.algorithm.curve()
+                    ),
+                }
+            }
+            Some(existing) if existing.tags != key.tags => Change::Retag {
+                id: key.id.clone(),
+                from: existing.tags.clone(),
+                to: key.tags.clone(),
+            },
+            Some(_) => continue,
+        };
+        changes.push(change);
+    }
+
+    if desired.prune {
+        let selected = current.values().filter(|key| {
+            !ids.contains(key.id.as_str())
+                && desired
+                    .selector
+                    .iter()
+                    .all(|(tag, value)| key.tags.get(tag) == Some(value))
+        });
+        changes.extend(selected.map(|key| Change::Delete { id: key.id.clone() }));
+    }
+    Ok(Plan { changes })
+}
+
+/// Makes the changes of `plan` in order, stopping at the first failure. Plans holding
+/// conflicts are refused before anything is changed, and a key retagged since the plan
+/// was made fails with [Conflict][ClientError::Conflict] rather than being overwritten.
+pub async fn apply(client: &impl Client, mount: &str, plan: &Plan) -> Result<(), ClientError> {
+    if plan.has_conflicts() {
+        return Err(ClientError::Conflict(
+            "the plan holds conflicts, resolve them first".to_string(),
+        ));
+    }
+    for change in &plan.changes {
+        log::info!("applying {change}");
+        match change {
+            Change::Create {
+                id,
+                algorithm,
+                tags,
+            } => {
+                keys::create_key(client, mount, id, *algorithm, tags.clone()).await?;
+            }
+            Change::Retag { id, from, to } => {
+                let key = keys::read_key(client, mount, id).await?;
+                if key.tags != *from {
+                    return Err(ClientError::Conflict(format!(
+                        "key {id} was retagged since the plan was made"
+                    )));
+                }
+                let patch = TagPatch {
+                    set: to.clone(),
+                    remove: from
+                        .keys()
+                        .filter(|tag| !to.contains_key(*tag))
+                        .cloned()
+                        .collect(),
+                };
+                keys::patch_key_tags_from(client, mount, &key, &patch).await?;
+            }
+            Change::Delete { id } => keys::delete_key(client, mount, id).await?,
+            Change::Conflict { .. } => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,354 @@
+//! Retries of plugin calls failing with transient errors.
+//!
+//! A [RetryPolicy] decides which errors are worth another attempt and how long to wait
+//! before it, doubling the delay after every failure and adding random jitter so clients
+//! do not retry in lockstep. [RetryPolicy::run] retries any call:
+//!
+//! ```no_run
+//! use quorum_vault_client::retry::RetryPolicy;
+//! use quorum_vault_client::{VaultClient, VaultClientSettingsBuilder};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = VaultClient::new(
+//!         VaultClientSettingsBuilder::default()
+//!             .address("https://127.0.0.1:8200")
+//!             .token("TOKEN")
+//!             .build()
+//!             .unwrap()
+//!     ).unwrap();
+//!     let policy = RetryPolicy::default();
+//!     let keys = policy
+//!         .run(|| quorum_vault_client::api::keys::list_keys(&client, "quorum"))
+//!         .await
+//!         .unwrap();
+//!     println!("keys: {:?}", keys.keys);
+//! }
+//! ```
+//!
+//! [RetryingSigner] applies a policy to a signer: reads and lists are always retried,
+//! signing only when [RetryPolicy::retry_signing] is set, and calls creating, importing,
+//! updating or destroying keys are never retried.
+//!
+//! The read and list functions of [api][crate::api], such as
+//! [read_account][crate::api::ethereum::read_account] or
+//! [list_keys][crate::api::keys::list_keys], are idempotent and retry with the default
+//! policy on their own. Their `_with_retry` forms take another policy, e.g.
+//! [RetryPolicy::never] for a single attempt. Signers make single attempts and leave
+//! retries to a [RetryingSigner], or to failing over for a
+//! [FailoverSigner][crate::failover::FailoverSigner].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rand::Rng;
+use web3::types::{Address, TransactionRequest};
+
+use crate::api::ethereum::responses::{
+    EthereumAccountResponse, EthereumAccountsResponse, EthereumSignResponse,
+    EthereumSignTransactionResponse,
+};
+use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::zksnarks::responses::{
+    ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
+};
+use crate::error::ClientError;
+use crate::signer::{EthereumSigner, KeySigner, ZkSigner};
+
+/// When and how often failed calls are attempted again.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Attempts made in total, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every following one
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Waits a random delay between zero and the backoff instead of the backoff itself
+    pub jitter: bool,
+    /// Retries responses with a `5xx` status
+    pub retry_server_errors: bool,
+    /// Other statuses to retry, such as `429` or the `412` of a lagging standby
+    pub retry_statuses: Vec<u16>,
+    /// Retries requests failing before a response is received, e.g. on connection reset
+    pub retry_connection_errors: bool,
+    /// Retries signing calls of a [RetryingSigner], a retried request may have been
+    /// signed by Vault already
+    pub retry_signing: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            jitter: true,
+            retry_server_errors: true,
+            retry_statuses: vec![412, 429],
+            retry_connection_errors: true,
+            retry_signing: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy making a single attempt.
+    pub fn never() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Returns whether a call failing with `error` may succeed when attempted again.
+    pub fn is_retryable(&self, error: &ClientError) -> bool {
+        match error.status() {
+            Some(status) => {
+                (self.retry_server_errors && (500..600).contains(&status))
+                    || self.retry_statuses.contains(&status)
+            }
+            None => self.retry_connection_errors && error.is_connection_error(),
+        }
+    }
+
+    /// Returns the delay before the retry following the failed attempt `attempt`,
+    /// starting at 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.jitter && !backoff.is_zero() {
+            rand::thread_rng().gen_range(Duration::ZERO..=backoff)
+        } else {
+            backoff
+        }
+    }
+
+    /// Runs `call` until it succeeds, fails with an error that is not retryable or
+    /// [RetryPolicy::max_attempts] is reached.
+    pub async fn run<F, Fut, T, E>(&self, mut call: F) -> Result<T, ClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Into<ClientError>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await.map_err(Into::into) {
+                Err(e) if attempt < self.max_attempts && self.is_retryable(&e) => {
+                    let backoff = self.backoff(attempt);
+                    log::warn!("attempt {attempt} failed, retrying in {backoff:?}: {e}");
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Same as [RetryPolicy::run], returning the error of vaultrs as is.
+    pub(crate) async fn run_vault<F, Fut, T>(
+        &self,
+        mut call: F,
+    ) -> Result<T, vaultrs::error::ClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, vaultrs::error::ClientError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let error = match call().await {
+                Err(e) if attempt < self.max_attempts => ClientError::VaultClientError(e),
+                result => return result,
+            };
+            if !self.is_retryable(&error) {
+                let ClientError::VaultClientError(e) = error else {
+                    unreachable!()
+                };
+                return Err(e);
+            }
+            let backoff = self.backoff(attempt);
+            log::warn!("attempt {attempt} failed, retrying in {backoff:?}: {error}");
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    async fn run_signing<F, Fut, T>(&self, call: F) -> Result<T, ClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        if self.retry_signing {
+            self.run(call).await
+        } else {
+            Self::never().run(call).await
+        }
+    }
+}
+
+/// Signer retrying the calls of another signer with a [RetryPolicy].
+pub struct RetryingSigner<S> {
+    signer: S,
+    policy: RetryPolicy,
+}
+
+impl<S> RetryingSigner<S> {
+    pub fn new(signer: S, policy: RetryPolicy) -> Self {
+        Self { signer, policy }
+    }
+
+    /// Returns the wrapped signer
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Returns the retry policy
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+}
+
+#[async_trait]
+impl<S: EthereumSigner> EthereumSigner for RetryingSigner<S> {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError> {
+        self.signer.create_account().await
+    }
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError> {
+        self.policy.run(|| self.signer.list_accounts()).await
+    }
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError> {
+        self.policy.run(|| self.signer.read_account(address)).await
+    }
+
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        self.signer.import_private_key(private_key).await
+    }
+
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError> {
+        self.policy
+            .run_signing(|| self.signer.sign_transaction(chain_id, transaction.clone()))
+            .await
+    }
+
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError> {
+        self.policy
+            .run_signing(|| self.signer.sign(address, data))
+            .await
+    }
+}
+
+#[async_trait]
+impl<S: KeySigner> KeySigner for RetryingSigner<S> {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        self.signer.create_key(id, algorithm, tags).await
+    }
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        self.policy.run(|| self.signer.read_key(id)).await
+    }
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError> {
+        self.policy.run(|| self.signer.list_keys()).await
+    }
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        self.signer.update_key_tags(id, tags).await
+    }
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError> {
+        self.signer.destroy_key(id).await
+    }
+
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError> {
+        self.signer
+            .import_key(id, algorithm, tags, private_key)
+            .await
+    }
+
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError> {
+        self.policy
+            .run_signing(|| self.signer.sign_data(id, data))
+            .await
+    }
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError> {
+        self.policy
+            .run_signing(|| self.signer.sign_hash(id, hash))
+            .await
+    }
+}
+
+#[async_trait]
+impl<S: ZkSigner> ZkSigner for RetryingSigner<S> {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError> {
+        self.signer.create_zksnarks_account().await
+    }
+
+    async fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        self.policy
+            .run(|| self.signer.read_zksnarks_account(id))
+            .await
+    }
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError> {
+        self.policy
+            .run(|| self.signer.list_zksnarks_accounts())
+            .await
+    }
+
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.policy
+            .run_signing(|| self.signer.zksnarks_sign(id, data))
+            .await
+    }
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.policy
+            .run_signing(|| self.signer.zksnarks_sign_hash(id, hash))
+            .await
+    }
+}
@@ -0,0 +1,520 @@
+//! Backend-agnostic signing.
+//!
+//! [EthereumSigner], [KeySigner] and [ZkSigner] expose the operations of
+//! [api::ethereum][crate::api::ethereum], [api::keys][crate::api::keys] and
+//! [api::zksnarks][crate::api::zksnarks] so application code can be written against a
+//! trait. [VaultSigner] implements them on top of a Vault client and a mount, while
+//! [InMemorySigner] (`in-memory` feature) keeps the keys in process for local
+//! development and unit tests. [Config::signer][crate::config::Config::signer] picks
+//! one of them from the `backend` of the configuration, returned as a [BackendSigner]:
+//!
+//! ```no_run
+//! use quorum_vault_client::signer::{EthereumSigner, VaultSigner};
+//! use quorum_vault_client::{VaultClient, VaultClientSettingsBuilder};
+//!
+//! async fn new_account(signer: &impl EthereumSigner) {
+//!     let account = signer.create_account().await.unwrap();
+//!     println!("created: {:?}", account.address);
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let client = VaultClient::new(
+//!         VaultClientSettingsBuilder::default()
+//!             .address("https://127.0.0.1:8200")
+//!             .token("TOKEN")
+//!             .build()
+//!             .unwrap()
+//!     ).unwrap();
+//!     new_account(&VaultSigner::new(client, "quorum")).await;
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use vaultrs::client::{Client, VaultClient};
+use web3::types::{Address, TransactionRequest};
+
+use crate::api;
+use crate::api::ethereum::responses::{
+    EthereumAccountResponse, EthereumAccountsResponse, EthereumSignResponse,
+    EthereumSignTransactionResponse,
+};
+use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::zksnarks::responses::{
+    ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
+};
+use crate::audit::AuditSink;
+use crate::error::ClientError;
+use crate::retry::RetryPolicy;
+
+#[cfg(feature = "in-memory")]
+pub mod babyjubjub;
+#[cfg(feature = "in-memory")]
+pub(crate) mod memory;
+
+#[cfg(feature = "in-memory")]
+pub use memory::InMemorySigner;
+
+/// Operations of the Ethereum backend, see [api::ethereum][crate::api::ethereum].
+#[async_trait]
+pub trait EthereumSigner: Send + Sync {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError>;
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError>;
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError>;
+
+    /// Imports a hex encoded private key.
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError>;
+
+    /// Signs a legacy transaction with the `from` account.
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError>;
+
+    /// Signs the keccak256 hash of `data`.
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError>;
+}
+
+/// Operations of the Keys backend, see [api::keys][crate::api::keys].
+#[async_trait]
+pub trait KeySigner: Send + Sync {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError>;
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError>;
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError>;
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError>;
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError>;
+
+    /// Imports a base64url encoded private key.
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError>;
+
+    /// Signs the keccak256 hash of `data`, named apart from [EthereumSigner::sign] so
+    /// both traits can be used on the same signer.
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError>;
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError>;
+}
+
+/// Operations of the zk-SNARKs backend, see [api::zksnarks][crate::api::zksnarks].
+#[async_trait]
+pub trait ZkSigner: Send + Sync {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError>;
+
+    async fn read_zksnarks_account(&self, id: &str)
+        -> Result<ZkSnarksAccountResponse, ClientError>;
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError>;
+
+    /// Signs the keccak256 hash of `data`.
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError>;
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError>;
+}
+
+/// Signer backed by the plugin mounted at `mount` in Vault. Every call makes a single
+/// attempt, wrap it in a [RetryingSigner][crate::retry::RetryingSigner] to retry them.
+pub struct VaultSigner<C> {
+    client: C,
+    mount: String,
+    audit: Option<Arc<dyn AuditSink>>,
+}
+
+impl<C: Client> VaultSigner<C> {
+    pub fn new(client: C, mount: impl Into<String>) -> Self {
+        Self {
+            client,
+            mount: mount.into(),
+            audit: None,
+        }
+    }
+
+    /// Returns the signer reporting its signing operations to `sink`, see
+    /// [audit][crate::audit].
+    pub fn with_audit_sink(mut self, sink: impl AuditSink + 'static) -> Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Returns the Vault client
+    pub fn client(&self) -> &C {
+        &self.client
+    }
+
+    /// Returns the mount of the plugin
+    pub fn mount(&self) -> &str {
+        &self.mount
+    }
+}
+
+#[async_trait]
+impl<C: Client> EthereumSigner for VaultSigner<C> {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError> {
+        Ok(api::ethereum::create_account(&self.client, &self.mount).await?)
+    }
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError> {
+        Ok(api::ethereum::list_accounts_with_retry(
+            &self.client,
+            &self.mount,
+            &RetryPolicy::never(),
+        )
+        .await?)
+    }
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError> {
+        Ok(api::ethereum::read_account_with_retry(
+            &self.client,
+            &self.mount,
+            address,
+            &RetryPolicy::never(),
+        )
+        .await?)
+    }
+
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        Ok(api::ethereum::import_private_key(&self.client, &self.mount, private_key).await?)
+    }
+
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError> {
+        Ok(api::ethereum::sign_transaction_audited(
+            &self.client,
+            &self.mount,
+            chain_id,
+            transaction,
+            self.audit.as_deref(),
+        )
+        .await?)
+    }
+
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError> {
+        Ok(api::ethereum::sign_audited(
+            &self.client,
+            &self.mount,
+            address,
+            data,
+            self.audit.as_deref(),
+        )
+        .await?)
+    }
+}
+
+#[async_trait]
+impl<C: Client> KeySigner for VaultSigner<C> {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        Ok(api::keys::create_key(&self.client, &self.mount, id, algorithm, tags).await?)
+    }
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        Ok(
+            api::keys::read_key_with_retry(&self.client, &self.mount, id, &RetryPolicy::never())
+                .await?,
+        )
+    }
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError> {
+        Ok(
+            api::keys::list_keys_with_retry(&self.client, &self.mount, &RetryPolicy::never())
+                .await?,
+        )
+    }
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        Ok(api::keys::update_key_tags(&self.client, &self.mount, id, tags).await?)
+    }
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError> {
+        Ok(api::keys::destroy_key(&self.client, &self.mount, id).await?)
+    }
+
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError> {
+        Ok(
+            api::keys::import_key(&self.client, &self.mount, id, algorithm, tags, private_key)
+                .await?,
+        )
+    }
+
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError> {
+        Ok(
+            api::keys::sign_audited(&self.client, &self.mount, id, data, self.audit.as_deref())
+                .await?,
+        )
+    }
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError> {
+        Ok(
+            api::keys::sign_hash_audited(
+                &self.client,
+                &self.mount,
+                id,
+                hash,
+                self.audit.as_deref(),
+            )
+            .await?,
+        )
+    }
+}
+
+#[async_trait]
+impl<C: Client> ZkSigner for VaultSigner<C> {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError> {
+        api::zksnarks::create_zksnarks_account(&self.client, &self.mount).await
+    }
+
+    async fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        api::zksnarks::read_zksnarks_account_with_retry(
+            &self.client,
+            &self.mount,
+            id,
+            &RetryPolicy::never(),
+        )
+        .await
+    }
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError> {
+        api::zksnarks::list_zksnarks_accounts_with_retry(
+            &self.client,
+            &self.mount,
+            &RetryPolicy::never(),
+        )
+        .await
+    }
+
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        api::zksnarks::zksnarks_sign_audited(
+            &self.client,
+            &self.mount,
+            id,
+            data,
+            self.audit.as_deref(),
+        )
+        .await
+    }
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        api::zksnarks::zksnarks_sign_hash_audited(
+            &self.client,
+            &self.mount,
+            id,
+            hash,
+            self.audit.as_deref(),
+        )
+        .await
+    }
+}
+
+/// Signer of the backend selected by [Config::signer][crate::config::Config::signer].
+pub enum BackendSigner {
+    Vault(VaultSigner<VaultClient>),
+    #[cfg(feature = "in-memory")]
+    InMemory(InMemorySigner),
+}
+
+/// Calls the same method on whichever backend the signer wraps.
+macro_rules! dispatch {
+    ($signer:expr, $backend:ident => $call:expr) => {
+        match $signer {
+            BackendSigner::Vault($backend) => $call,
+            #[cfg(feature = "in-memory")]
+            BackendSigner::InMemory($backend) => $call,
+        }
+    };
+}
+
+#[async_trait]
+impl EthereumSigner for BackendSigner {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError> {
+        dispatch!(self, signer => signer.create_account().await)
+    }
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError> {
+        dispatch!(self, signer => signer.list_accounts().await)
+    }
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError> {
+        dispatch!(self, signer => signer.read_account(address).await)
+    }
+
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        dispatch!(self, signer => signer.import_private_key(private_key).await)
+    }
+
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError> {
+        dispatch!(self, signer => signer.sign_transaction(chain_id, transaction).await)
+    }
+
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError> {
+        dispatch!(self, signer => EthereumSigner::sign(signer, address, data).await)
+    }
+}
+
+#[async_trait]
+impl KeySigner for BackendSigner {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        dispatch!(self, signer => signer.create_key(id, algorithm, tags).await)
+    }
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        dispatch!(self, signer => signer.read_key(id).await)
+    }
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError> {
+        dispatch!(self, signer => signer.list_keys().await)
+    }
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        dispatch!(self, signer => signer.update_key_tags(id, tags).await)
+    }
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError> {
+        dispatch!(self, signer => signer.destroy_key(id).await)
+    }
+
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError> {
+        dispatch!(self, signer => signer.import_key(id, algorithm, tags, private_key).await)
+    }
+
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError> {
+        dispatch!(self, signer => signer.sign_data(id, data).await)
+    }
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError> {
+        dispatch!(self, signer => signer.sign_hash(id, hash).await)
+    }
+}
+
+#[async_trait]
+impl ZkSigner for BackendSigner {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError> {
+        dispatch!(self, signer => signer.create_zksnarks_account().await)
+    }
+
+    async fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        dispatch!(self, signer => signer.read_zksnarks_account(id).await)
+    }
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError> {
+        dispatch!(self, signer => signer.list_zksnarks_accounts().await)
+    }
+
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        dispatch!(self, signer => signer.zksnarks_sign(id, data).await)
+    }
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        dispatch!(self, signer => signer.zksnarks_sign_hash(id, hash).await)
+    }
+}
@@ -0,0 +1,179 @@
+//! EdDSA over the BabyJubJub curve ([EIP-2494](https://eips.ethereum.org/EIPS/eip-2494)).
+//!
+//! Keys and points use the usual 32 byte compressed encoding: the little-endian `y`
+//! coordinate with the sign of `x` in the most significant bit. Signatures are the
+//! compressed `R` point followed by the little-endian `S` scalar. Challenges are
+//! derived with keccak256, so signatures verify with [verify] but are not
+//! interchangeable with the MiMC based signatures of the plugin.
+
+use std::str::FromStr;
+
+use ark_bn254::Fr as Fq;
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use num_bigint::BigUint;
+use web3::signing::keccak256;
+
+const A: u64 = 168700;
+const D: u64 = 168696;
+const BASE_X: &str = "5299619240641551281634865583518297030282874472190772894086521144482721001553";
+const BASE_Y: &str =
+    "16950150798460657717958625567821834550301663161624707787222815936182638968203";
+const ORDER: &str = "2736030358979909402780800718157159386076813972158567259200215660948447373041";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Point {
+    x: Fq,
+    y: Fq,
+}
+
+impl Point {
+    fn identity() -> Self {
+        Self {
+            x: Fq::zero(),
+            y: Fq::one(),
+        }
+    }
+
+    fn base() -> Self {
+        Self {
+            x: Fq::from_str(BASE_X).unwrap(),
+            y: Fq::from_str(BASE_Y).unwrap(),
+        }
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        let a = Fq::from(A);
+        let d = Fq::from(D);
+        let xx = self.x * other.x;
+        let yy = self.y * other.y;
+        let dxxyy = d * xx * yy;
+        let x = (self.x * other.y + self.y * other.x) * (Fq::one() + dxxyy).inverse().unwrap();
+        let y = (yy - a * xx) * (Fq::one() - dxxyy).inverse().unwrap();
+        Self { x, y }
+    }
+
+    fn mul(&self, scalar: &BigUint) -> Self {
+        let mut result = Self::identity();
+        for bit in (0..scalar.bits()).rev() {
+            result = result.add(&result);
+            if scalar.bit(bit) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+
+    fn compress(&self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&self.y.into_bigint().to_bytes_le());
+        if is_negative(&self.x) {
+            bytes[31] |= 0x80;
+        }
+        bytes
+    }
+
+    fn decompress(bytes: &[u8; 32]) -> Option<Self> {
+        let negative = bytes[31] & 0x80 != 0;
+        let mut y_bytes = *bytes;
+        y_bytes[31] &= 0x7f;
+        let y = Fq::from_le_bytes_mod_order(&y_bytes);
+        if y.into_bigint().to_bytes_le() != y_bytes {
+            return None;
+        }
+        // a * x^2 + y^2 = 1 + d * x^2 * y^2  =>  x^2 = (1 - y^2) / (a - d * y^2)
+        let yy = y * y;
+        let xx = (Fq::one() - yy) * (Fq::from(A) - Fq::from(D) * yy).inverse()?;
+        let mut x = xx.sqrt()?;
+        if is_negative(&x) != negative {
+            x = -x;
+        }
+        Some(Self { x, y })
+    }
+}
+
+fn is_negative(value: &Fq) -> bool {
+    *value > -*value
+}
+
+fn order() -> BigUint {
+    BigUint::from_str(ORDER).unwrap()
+}
+
+fn scalar_from_bytes(bytes: &[u8]) -> BigUint {
+    BigUint::from_bytes_le(bytes) % order()
+}
+
+fn scalar_to_bytes(scalar: &BigUint) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    let le = scalar.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+fn challenge(r: &[u8; 32], public_key: &[u8; 32], message: &[u8]) -> BigUint {
+    let mut input = Vec::with_capacity(64 + message.len());
+    input.extend_from_slice(r);
+    input.extend_from_slice(public_key);
+    input.extend_from_slice(message);
+    scalar_from_bytes(&keccak256(&input))
+}
+
+/// BabyJubJub private key.
+#[derive(Clone)]
+pub struct PrivateKey {
+    secret: [u8; 32],
+    scalar: BigUint,
+}
+
+impl PrivateKey {
+    /// Creates a key from 32 secret bytes.
+    pub fn from_bytes(secret: [u8; 32]) -> Self {
+        let scalar = scalar_from_bytes(&secret);
+        Self { secret, scalar }
+    }
+
+    /// Creates a random key.
+    pub fn random() -> Self {
+        Self::from_bytes(rand::random())
+    }
+
+    /// Returns the secret bytes of the key
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.secret
+    }
+
+    /// Returns the compressed public key
+    pub fn public_key(&self) -> [u8; 32] {
+        Point::base().mul(&self.scalar).compress()
+    }
+
+    /// Signs the message, the signature is 64 bytes long.
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        let mut input = self.secret.to_vec();
+        input.extend_from_slice(message);
+        let r = scalar_from_bytes(&keccak256(&input));
+        let r_point = Point::base().mul(&r).compress();
+        let h = challenge(&r_point, &self.public_key(), message);
+        let s = (r + h * &self.scalar) % order();
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&r_point);
+        signature[32..].copy_from_slice(&scalar_to_bytes(&s));
+        signature
+    }
+}
+
+/// Verifies a signature produced by [PrivateKey::sign].
+pub fn verify(public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+    let r_bytes: [u8; 32] = signature[..32].try_into().unwrap();
+    let s_bytes: [u8; 32] = signature[32..].try_into().unwrap();
+    let (Some(a), Some(r)) = (Point::decompress(public_key), Point::decompress(&r_bytes)) else {
+        return false;
+    };
+    let s = BigUint::from_bytes_le(&s_bytes);
+    if s >= order() {
+        return false;
+    }
+    let h = challenge(&r_bytes, public_key, message);
+    Point::base().mul(&s) == r.add(&a.mul(&h))
+}
@@ -0,0 +1,551 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use base64::Engine;
+use rlp::RlpStream;
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use web3::signing::keccak256;
+use web3::types::{Address, TransactionRequest, U256};
+
+use super::babyjubjub;
+use super::{EthereumSigner, KeySigner, ZkSigner};
+use crate::api::ethereum::responses::{
+    EthereumAccountResponse, EthereumAccountsResponse, EthereumSignResponse,
+    EthereumSignTransactionResponse,
+};
+use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::api::zksnarks::responses::{
+    ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
+};
+use crate::error::ClientError;
+
+/// Signer keeping its keys in memory, for local development and unit tests.
+///
+/// Responses and signatures use the same encodings as the plugin, and errors are
+/// reported as the [APIError][vaultrs::error::ClientError::APIError] Vault would
+/// return, so code written against the traits behaves the same with both backends.
+/// zk-SNARKs accounts and `babyjubjub` keys sign with [babyjubjub], whose signatures
+/// are not byte compatible with the plugin.
+#[derive(Default)]
+pub struct InMemorySigner {
+    store: Mutex<Store>,
+}
+
+impl InMemorySigner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the hex encoded private key of an Ethereum account.
+    pub fn ethereum_private_key(&self, address: Address) -> Option<String> {
+        self.store.lock().unwrap().ethereum_private_key(address)
+    }
+}
+
+#[async_trait]
+impl EthereumSigner for InMemorySigner {
+    async fn create_account(&self) -> Result<EthereumAccountResponse, ClientError> {
+        Ok(self.store.lock().unwrap().create_account())
+    }
+
+    async fn list_accounts(&self) -> Result<EthereumAccountsResponse, ClientError> {
+        Ok(self.store.lock().unwrap().list_accounts())
+    }
+
+    async fn read_account(&self, address: Address) -> Result<EthereumAccountResponse, ClientError> {
+        self.store.lock().unwrap().read_account(address)
+    }
+
+    async fn import_private_key(
+        &self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        self.store.lock().unwrap().import_private_key(private_key)
+    }
+
+    async fn sign_transaction(
+        &self,
+        chain_id: u64,
+        transaction: TransactionRequest,
+    ) -> Result<EthereumSignTransactionResponse, ClientError> {
+        let hash = legacy_transaction_hash(
+            transaction.nonce.unwrap_or_default(),
+            transaction.gas_price.unwrap_or_default(),
+            transaction.gas.unwrap_or_else(|| U256::from(21000)),
+            transaction.to.unwrap_or_default(),
+            transaction.value.unwrap_or_default(),
+            &transaction.data.unwrap_or_default().0,
+            U256::from(chain_id),
+        );
+        let signature = self
+            .store
+            .lock()
+            .unwrap()
+            .ethereum_sign_hash(transaction.from, &hash)?;
+        Ok(EthereumSignTransactionResponse { signature })
+    }
+
+    async fn sign(
+        &self,
+        address: Address,
+        data: &[u8],
+    ) -> Result<EthereumSignResponse, ClientError> {
+        let signature = self
+            .store
+            .lock()
+            .unwrap()
+            .ethereum_sign_hash(address, &keccak256(data))?;
+        Ok(EthereumSignResponse { signature })
+    }
+}
+
+#[async_trait]
+impl KeySigner for InMemorySigner {
+    async fn create_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        let secret = KeySecret::generate(&algorithm);
+        self.store.lock().unwrap().insert_key(id, secret, tags)
+    }
+
+    async fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        self.store.lock().unwrap().read_key(id)
+    }
+
+    async fn list_keys(&self) -> Result<KeysResponse, ClientError> {
+        Ok(self.store.lock().unwrap().list_keys())
+    }
+
+    async fn update_key_tags(
+        &self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        self.store.lock().unwrap().update_key_tags(id, tags)
+    }
+
+    async fn destroy_key(&self, id: &str) -> Result<(), ClientError> {
+        self.store.lock().unwrap().destroy_key(id)
+    }
+
+    async fn import_key(
+        &self,
+        id: &str,
+        algorithm: KeyCryptoAlgorithm,
+        tags: HashMap<String, String>,
+        private_key: &str,
+    ) -> Result<KeyResponse, ClientError> {
+        let secret = KeySecret::decode(&algorithm, private_key)?;
+        self.store.lock().unwrap().insert_key(id, secret, tags)
+    }
+
+    async fn sign_data(&self, id: &str, data: &[u8]) -> Result<SignResponse, ClientError> {
+        self.sign_hash(id, keccak256(data)).await
+    }
+
+    async fn sign_hash(&self, id: &str, hash: [u8; 32]) -> Result<SignResponse, ClientError> {
+        let signature = self.store.lock().unwrap().key_sign(id, &hash)?;
+        Ok(SignResponse { signature })
+    }
+}
+
+#[async_trait]
+impl ZkSigner for InMemorySigner {
+    async fn create_zksnarks_account(&self) -> Result<ZkSnarksAccountResponse, ClientError> {
+        Ok(self.store.lock().unwrap().create_zksnarks_account())
+    }
+
+    async fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        self.store.lock().unwrap().read_zksnarks_account(id)
+    }
+
+    async fn list_zksnarks_accounts(&self) -> Result<ZkSnarksAccountsResponse, ClientError> {
+        Ok(self.store.lock().unwrap().list_zksnarks_accounts())
+    }
+
+    async fn zksnarks_sign(
+        &self,
+        id: &str,
+        data: &[u8],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        self.zksnarks_sign_hash(id, keccak256(data)).await
+    }
+
+    async fn zksnarks_sign_hash(
+        &self,
+        id: &str,
+        hash: [u8; 32],
+    ) -> Result<ZkSnarksSignResponse, ClientError> {
+        let signature = self.store.lock().unwrap().zksnarks_sign(id, &hash)?;
+        Ok(ZkSnarksSignResponse { signature })
+    }
+}
+
+/// Keys of a single plugin mount, shared with the mock server of the `testing` feature.
+#[derive(Default)]
+pub(crate) struct Store {
+    ethereum: BTreeMap<Address, SecretKey>,
+    keys: BTreeMap<String, StoredKey>,
+    deleted_keys: BTreeMap<String, StoredKey>,
+    zksnarks: BTreeMap<String, babyjubjub::PrivateKey>,
+}
+
+pub(crate) enum KeySecret {
+    Secp256k1(SecretKey),
+    Babyjubjub(babyjubjub::PrivateKey),
+}
+
+impl KeySecret {
+    pub(crate) fn generate(algorithm: &KeyCryptoAlgorithm) -> Self {
+        match algorithm {
+            KeyCryptoAlgorithm::Secp256k1 => {
+                KeySecret::Secp256k1(SecretKey::new(&mut rand::thread_rng()))
+            }
+            KeyCryptoAlgorithm::Babyjubjub => {
+                KeySecret::Babyjubjub(babyjubjub::PrivateKey::random())
+            }
+        }
+    }
+
+    /// Decodes a base64url private key as sent to the import endpoint.
+    pub(crate) fn decode(
+        algorithm: &KeyCryptoAlgorithm,
+        private_key: &str,
+    ) -> Result<Self, ClientError> {
+        let bytes = base64::prelude::BASE64_URL_SAFE
+            .decode(private_key)
+            .map_err(|e| api_error(400, e))?;
+        match algorithm {
+            KeyCryptoAlgorithm::Secp256k1 => Ok(KeySecret::Secp256k1(
+                SecretKey::from_slice(&bytes).map_err(|e| api_error(400, e))?,
+            )),
+            KeyCryptoAlgorithm::Babyjubjub => {
+                let bytes: [u8; 32] = bytes
+                    .try_into()
+                    .map_err(|_| api_error(400, "invalid private key"))?;
+                Ok(KeySecret::Babyjubjub(babyjubjub::PrivateKey::from_bytes(
+                    bytes,
+                )))
+            }
+        }
+    }
+}
+
+struct StoredKey {
+    secret: KeySecret,
+    tags: HashMap<String, String>,
+    created_at: String,
+    updated_at: String,
+    version: u64,
+}
+
+impl Store {
+    pub(crate) fn create_account(&mut self) -> EthereumAccountResponse {
+        self.insert_ethereum(SecretKey::new(&mut rand::thread_rng()))
+    }
+
+    pub(crate) fn import_private_key(
+        &mut self,
+        private_key: &str,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        let bytes =
+            hex::decode(private_key.trim_start_matches("0x")).map_err(|e| api_error(400, e))?;
+        let key = SecretKey::from_slice(&bytes).map_err(|e| api_error(400, e))?;
+        Ok(self.insert_ethereum(key))
+    }
+
+    pub(crate) fn list_accounts(&self) -> EthereumAccountsResponse {
+        EthereumAccountsResponse {
+            keys: self.ethereum.keys().copied().collect(),
+        }
+    }
+
+    pub(crate) fn read_account(
+        &self,
+        address: Address,
+    ) -> Result<EthereumAccountResponse, ClientError> {
+        Ok(ethereum_account(address, self.ethereum_key(address)?))
+    }
+
+    pub(crate) fn ethereum_private_key(&self, address: Address) -> Option<String> {
+        let key = self.ethereum.get(&address)?;
+        Some(hex::encode(key.secret_bytes()))
+    }
+
+    /// Signs a hash with an Ethereum account, returning the hex encoded `r || s || v`
+    /// signature.
+    pub(crate) fn ethereum_sign_hash(
+        &self,
+        address: Address,
+        hash: &[u8; 32],
+    ) -> Result<String, ClientError> {
+        let signature = sign_recoverable(self.ethereum_key(address)?, hash);
+        Ok(format!("0x{}", hex::encode(signature)))
+    }
+
+    pub(crate) fn insert_key(
+        &mut self,
+        id: &str,
+        secret: KeySecret,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        if self.keys.contains_key(id) || self.deleted_keys.contains_key(id) {
+            return Err(api_error(409, format!("key {id} already exists")));
+        }
+        let created_at = now();
+        let key = StoredKey {
+            secret,
+            tags,
+            updated_at: created_at.clone(),
+            created_at,
+            version: 1,
+        };
+        let response = key_response(id, &key);
+        self.keys.insert(id.to_string(), key);
+        Ok(response)
+    }
+
+    pub(crate) fn read_key(&self, id: &str) -> Result<KeyResponse, ClientError> {
+        Ok(key_response(id, self.key(id)?))
+    }
+
+    pub(crate) fn list_keys(&self) -> KeysResponse {
+        KeysResponse {
+            keys: self.keys.keys().cloned().collect(),
+        }
+    }
+
+    pub(crate) fn update_key_tags(
+        &mut self,
+        id: &str,
+        tags: HashMap<String, String>,
+    ) -> Result<KeyResponse, ClientError> {
+        let key = self.keys.get_mut(id).ok_or_else(key_not_found)?;
+        key.tags = tags;
+        key.updated_at = now();
+        key.version += 1;
+        Ok(key_response(id, key))
+    }
+
+    pub(crate) fn destroy_key(&mut self, id: &str) -> Result<(), ClientError> {
+        self.keys
+            .remove(id)
+            .or_else(|| self.deleted_keys.remove(id))
+            .ok_or_else(key_not_found)?;
+        Ok(())
+    }
+
+    pub(crate) fn delete_key(&mut self, id: &str) -> Result<(), ClientError> {
+        let key = self.keys.remove(id).ok_or_else(key_not_found)?;
+        self.deleted_keys.insert(id.to_string(), key);
+        Ok(())
+    }
+
+    pub(crate) fn restore_key(&mut self, id: &str) -> Result<(), ClientError> {
+        let key = self.deleted_keys.remove(id).ok_or_else(key_not_found)?;
+        self.keys.insert(id.to_string(), key);
+        Ok(())
+    }
+
+    /// Signs `data` with a key, returning the base64url encoded signature. `secp256k1`
+    /// keys only sign 32 byte hashes.
+    pub(crate) fn key_sign(&self, id: &str, data: &[u8]) -> Result<String, ClientError> {
+        let signature = match &self.key(id)?.secret {
+            KeySecret::Secp256k1(secret) => {
+                let hash: [u8; 32] = data
+                    .try_into()
+                    .map_err(|_| api_error(400, "data must be a 32 byte hash"))?;
+                sign_recoverable(secret, &hash)[..64].to_vec()
+            }
+            KeySecret::Babyjubjub(secret) => secret.sign(data).to_vec(),
+        };
+        Ok(base64::prelude::BASE64_URL_SAFE.encode(signature))
+    }
+
+    pub(crate) fn create_zksnarks_account(&mut self) -> ZkSnarksAccountResponse {
+        let key = babyjubjub::PrivateKey::random();
+        let response = zksnarks_account(&key);
+        self.zksnarks.insert(response.public_key.clone(), key);
+        response
+    }
+
+    pub(crate) fn read_zksnarks_account(
+        &self,
+        id: &str,
+    ) -> Result<ZkSnarksAccountResponse, ClientError> {
+        Ok(zksnarks_account(self.zksnarks_key(id)?))
+    }
+
+    pub(crate) fn list_zksnarks_accounts(&self) -> ZkSnarksAccountsResponse {
+        ZkSnarksAccountsResponse {
+            keys: self.zksnarks.keys().cloned().collect(),
+        }
+    }
+
+    /// Signs `data` with a zk-SNARKs account, returning the hex encoded signature.
+    pub(crate) fn zksnarks_sign(&self, id: &str, data: &[u8]) -> Result<String, ClientError> {
+        let signature = self.zksnarks_key(id)?.sign(data);
+        Ok(format!("0x{}", hex::encode(signature)))
+    }
+
+    fn insert_ethereum(&mut self, key: SecretKey) -> EthereumAccountResponse {
+        let address = address_of(&key);
+        self.ethereum.insert(address, key);
+        ethereum_account(address, &key)
+    }
+
+    fn ethereum_key(&self, address: Address) -> Result<&SecretKey, ClientError> {
+        self.ethereum
+            .get(&address)
+            .ok_or_else(|| api_error(404, "account not found"))
+    }
+
+    fn key(&self, id: &str) -> Result<&StoredKey, ClientError> {
+        self.keys.get(id).ok_or_else(key_not_found)
+    }
+
+    fn zksnarks_key(&self, id: &str) -> Result<&babyjubjub::PrivateKey, ClientError> {
+        self.zksnarks
+            .get(&id.to_lowercase())
+            .ok_or_else(|| api_error(404, "account not found"))
+    }
+}
+
+/// Error with the status and message Vault would return.
+pub(crate) fn api_error(code: u16, message: impl ToString) -> ClientError {
+    vaultrs::error::ClientError::APIError {
+        code,
+        errors: vec![message.to_string()],
+    }
+    .into()
+}
+
+fn key_not_found() -> ClientError {
+    api_error(404, "key not found")
+}
+
+/// Hash of a legacy transaction as signed under EIP-155.
+pub(crate) fn legacy_transaction_hash(
+    nonce: U256,
+    gas_price: U256,
+    gas: U256,
+    to: Address,
+    value: U256,
+    data: &[u8],
+    chain_id: U256,
+) -> [u8; 32] {
+    let mut stream = RlpStream::new_list(9);
+    stream.append(&nonce);
+    stream.append(&gas_price);
+    stream.append(&gas);
+    stream.append(&to);
+    stream.append(&value);
+    stream.append(&data);
+    stream.append(&chain_id);
+    stream.append(&0u8);
+    stream.append(&0u8);
+    keccak256(&stream.out())
+}
+
+/// Signs the hash, returning `r || s || v` with `v` being the recovery id.
+fn sign_recoverable(key: &SecretKey, hash: &[u8; 32]) -> [u8; 65] {
+    let message = Message::from_slice(hash).unwrap();
+    let (recovery_id, signature) = Secp256k1::new()
+        .sign_ecdsa_recoverable(&message, key)
+        .serialize_compact();
+    let mut result = [0u8; 65];
+    result[..64].copy_from_slice(&signature);
+    result[64] = recovery_id.to_i32() as u8;
+    result
+}
+
+/// Derives the address of a hex encoded private key.
+pub(crate) fn private_key_address(private_key: &str) -> Result<Address, ClientError> {
+    let bytes = hex::decode(private_key.trim_start_matches("0x"))
+        .map_err(|_| ClientError::ConfigError("invalid hex encoded private key".to_string()))?;
+    let key = SecretKey::from_slice(&bytes)
+        .map_err(|_| ClientError::ConfigError("invalid secp256k1 private key".to_string()))?;
+    Ok(address_of(&key))
+}
+
+fn address_of(key: &SecretKey) -> Address {
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), key).serialize_uncompressed();
+    Address::from_slice(&keccak256(&public_key[1..])[12..])
+}
+
+fn ethereum_account(address: Address, key: &SecretKey) -> EthereumAccountResponse {
+    let public_key = PublicKey::from_secret_key(&Secp256k1::new(), key);
+    EthereumAccountResponse {
+        address,
+        compressed_public_key: format!("0x{}", hex::encode(public_key.serialize())),
+        public_key: format!("0x{}", hex::encode(public_key.serialize_uncompressed())),
+        namespace: String::new(),
+    }
+}
+
+fn key_response(id: &str, key: &StoredKey) -> KeyResponse {
+    let (algorithm, public_key) = match &key.secret {
+        KeySecret::Secp256k1(secret) => (
+            KeyCryptoAlgorithm::Secp256k1,
+            PublicKey::from_secret_key(&Secp256k1::new(), secret)
+                .serialize_uncompressed()
+                .to_vec(),
+        ),
+        KeySecret::Babyjubjub(secret) => {
+            (KeyCryptoAlgorithm::Babyjubjub, secret.public_key().to_vec())
+        }
+    };
+    KeyResponse {
+        created_at: key.created_at.clone(),
+        curve: algorithm.curve().to_string(),
+        id: id.to_string(),
+        namespace: String::new(),
+        public_key: base64::prelude::BASE64_URL_SAFE.encode(public_key),
+        signing_algorithm: algorithm.signing_algorithm().to_string(),
+        tags: key.tags.clone(),
+        updated_at: key.updated_at.clone(),
+        version: key.version,
+    }
+}
+
+fn zksnarks_account(key: &babyjubjub::PrivateKey) -> ZkSnarksAccountResponse {
+    ZkSnarksAccountResponse {
+        curve: "babyjubjub".to_string(),
+        namespace: String::new(),
+        public_key: format!("0x{}", hex::encode(key.public_key())),
+        signing_algorithm: "eddsa".to_string(),
+    }
+}
+
+/// Current time in RFC 3339 format, as reported by the plugin.
+fn now() -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let secs = now.as_secs();
+    let (days, time) = (secs / 86400, secs % 86400);
+    // civil date from days since the epoch, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:09}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        now.subsec_nanos()
+    )
+}
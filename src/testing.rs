@@ -0,0 +1,28 @@
+//! Test helpers for code built on this crate, enabled with the `testing` feature.
+//!
+//! [MockVaultServer] is an in-process fake of the Quorum Vault plugin that can be used
+//! in place of a real Vault:
+//!
+//! ```no_run
+//! use quorum_vault_client::testing::MockVaultServer;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockVaultServer::start().await;
+//!     let client = server.client();
+//!
+//!     let account = quorum_vault_client::api::ethereum::create_account(&client, "quorum")
+//!         .await
+//!         .unwrap();
+//!     println!("result: {:?}", account);
+//! }
+//! ```
+//!
+//! For tests asserting exactly which requests are sent, [fixtures] builds wiremock
+//! mocks answering with the crate's own response types.
+
+pub mod fixtures;
+mod server;
+
+pub use crate::signer::babyjubjub;
+pub use server::MockVaultServer;
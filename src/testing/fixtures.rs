@@ -0,0 +1,281 @@
+//! [wiremock] building blocks for precise tests of code calling the plugin.
+//!
+//! Every endpoint has a function in [ethereum], [keys] or [zksnarks] returning a
+//! [MockBuilder] that matches its method and path, ready to be completed with
+//! [success] or [vault_error]. The `mock_*` functions are shortcuts answering with one of
+//! the crate's response types:
+//!
+//! ```no_run
+//! use quorum_vault_client::api::keys::responses::KeyResponse;
+//! use quorum_vault_client::testing::fixtures;
+//! use wiremock::MockServer;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let server = MockServer::start().await;
+//!     let key = KeyResponse {
+//!         created_at: "2023-01-01T00:00:00Z".to_string(),
+//!         curve: "secp256k1".to_string(),
+//!         id: "my-key".to_string(),
+//!         namespace: "".to_string(),
+//!         public_key: "BFxLsyfbvhx...".to_string(),
+//!         signing_algorithm: "ecdsa".to_string(),
+//!         tags: Default::default(),
+//!         updated_at: "2023-01-01T00:00:00Z".to_string(),
+//!         version: 1,
+//!     };
+//!     fixtures::keys::mock_create_key("quorum", &key)
+//!         .expect(1)
+//!         .mount(&server)
+//!         .await;
+//!     fixtures::keys::read_key("quorum", "other-key")
+//!         .respond_with(fixtures::not_found())
+//!         .mount(&server)
+//!         .await;
+//! }
+//! ```
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockBuilder, ResponseTemplate};
+
+/// Wraps `data` in the envelope Vault puts around every response.
+pub fn envelope(data: &impl Serialize) -> Value {
+    json!({
+        "request_id": "00000000-0000-0000-0000-000000000000",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": data,
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    })
+}
+
+/// Successful response carrying `data`, use `&()` for endpoints without data.
+pub fn success(data: &impl Serialize) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(envelope(data))
+}
+
+/// Error response with the given status and messages, as returned by Vault.
+pub fn vault_error(status: u16, errors: &[&str]) -> ResponseTemplate {
+    ResponseTemplate::new(status).set_body_json(json!({ "errors": errors }))
+}
+
+/// Response of Vault for a path that does not exist.
+pub fn not_found() -> ResponseTemplate {
+    vault_error(404, &[])
+}
+
+/// Response of Vault when the token is not allowed to use the path.
+pub fn permission_denied() -> ResponseTemplate {
+    vault_error(403, &["permission denied"])
+}
+
+fn endpoint(verb: &str, mount: &str, route: &str) -> MockBuilder {
+    Mock::given(method(verb)).and(path(format!("/v1/{mount}/{route}")))
+}
+
+/// Endpoints of the Ethereum backend.
+pub mod ethereum {
+    use web3::types::Address;
+    use wiremock::{Mock, MockBuilder};
+
+    use super::{endpoint, success};
+    use crate::api::ethereum::responses::{
+        EthereumAccountResponse, EthereumAccountsResponse, EthereumSignResponse,
+        EthereumSignTransactionResponse,
+    };
+
+    fn account(address: Address) -> String {
+        eth_checksum::checksum(&format!("{address:?}"))
+    }
+
+    pub fn create_account(mount: &str) -> MockBuilder {
+        endpoint("POST", mount, "ethereum/accounts")
+    }
+
+    pub fn list_accounts(mount: &str) -> MockBuilder {
+        endpoint("GET", mount, "ethereum/accounts")
+    }
+
+    pub fn read_account(mount: &str, address: Address) -> MockBuilder {
+        endpoint(
+            "GET",
+            mount,
+            &format!("ethereum/accounts/{}", account(address)),
+        )
+    }
+
+    pub fn import_private_key(mount: &str) -> MockBuilder {
+        endpoint("POST", mount, "ethereum/accounts/import")
+    }
+
+    pub fn sign_transaction(mount: &str, address: Address) -> MockBuilder {
+        endpoint(
+            "POST",
+            mount,
+            &format!("ethereum/accounts/{}/sign-transaction", account(address)),
+        )
+    }
+
+    pub fn sign(mount: &str, address: Address) -> MockBuilder {
+        endpoint(
+            "POST",
+            mount,
+            &format!("ethereum/accounts/{}/sign", account(address)),
+        )
+    }
+
+    pub fn mock_create_account(mount: &str, response: &EthereumAccountResponse) -> Mock {
+        create_account(mount).respond_with(success(response))
+    }
+
+    pub fn mock_list_accounts(mount: &str, response: &EthereumAccountsResponse) -> Mock {
+        list_accounts(mount).respond_with(success(response))
+    }
+
+    pub fn mock_read_account(mount: &str, response: &EthereumAccountResponse) -> Mock {
+        read_account(mount, response.address).respond_with(success(response))
+    }
+
+    pub fn mock_import_private_key(mount: &str, response: &EthereumAccountResponse) -> Mock {
+        import_private_key(mount).respond_with(success(response))
+    }
+
+    pub fn mock_sign_transaction(
+        mount: &str,
+        address: Address,
+        response: &EthereumSignTransactionResponse,
+    ) -> Mock {
+        sign_transaction(mount, address).respond_with(success(response))
+    }
+
+    pub fn mock_sign(mount: &str, address: Address, response: &EthereumSignResponse) -> Mock {
+        sign(mount, address).respond_with(success(response))
+    }
+}
+
+/// Endpoints of the Keys backend.
+pub mod keys {
+    use wiremock::{Mock, MockBuilder};
+
+    use super::{endpoint, success};
+    use crate::api::keys::responses::{KeyResponse, KeysResponse, SignResponse};
+
+    pub fn create_key(mount: &str) -> MockBuilder {
+        endpoint("POST", mount, "keys")
+    }
+
+    pub fn list_keys(mount: &str) -> MockBuilder {
+        endpoint("GET", mount, "keys")
+    }
+
+    pub fn read_key(mount: &str, id: &str) -> MockBuilder {
+        endpoint("GET", mount, &format!("keys/{id}"))
+    }
+
+    pub fn import_key(mount: &str) -> MockBuilder {
+        endpoint("POST", mount, "keys/import")
+    }
+
+    pub fn update_key_tags(mount: &str, id: &str) -> MockBuilder {
+        endpoint("POST", mount, &format!("keys/{id}"))
+    }
+
+    pub fn delete_key(mount: &str, id: &str) -> MockBuilder {
+        endpoint("DELETE", mount, &format!("keys/{id}"))
+    }
+
+    pub fn restore_key(mount: &str, id: &str) -> MockBuilder {
+        endpoint("POST", mount, &format!("keys/{id}/restore"))
+    }
+
+    pub fn destroy_key(mount: &str, id: &str) -> MockBuilder {
+        endpoint("DELETE", mount, &format!("keys/{id}/destroy"))
+    }
+
+    pub fn sign(mount: &str, id: &str) -> MockBuilder {
+        endpoint("POST", mount, &format!("keys/{id}/sign"))
+    }
+
+    pub fn mock_create_key(mount: &str, response: &KeyResponse) -> Mock {
+        create_key(mount).respond_with(success(response))
+    }
+
+    pub fn mock_list_keys(mount: &str, response: &KeysResponse) -> Mock {
+        list_keys(mount).respond_with(success(response))
+    }
+
+    pub fn mock_read_key(mount: &str, response: &KeyResponse) -> Mock {
+        read_key(mount, &response.id).respond_with(success(response))
+    }
+
+    pub fn mock_import_key(mount: &str, response: &KeyResponse) -> Mock {
+        import_key(mount).respond_with(success(response))
+    }
+
+    pub fn mock_update_key_tags(mount: &str, response: &KeyResponse) -> Mock {
+        update_key_tags(mount, &response.id).respond_with(success(response))
+    }
+
+    pub fn mock_delete_key(mount: &str, id: &str) -> Mock {
+        delete_key(mount, id).respond_with(success(&()))
+    }
+
+    pub fn mock_restore_key(mount: &str, id: &str) -> Mock {
+        restore_key(mount, id).respond_with(success(&()))
+    }
+
+    pub fn mock_destroy_key(mount: &str, id: &str) -> Mock {
+        destroy_key(mount, id).respond_with(success(&()))
+    }
+
+    pub fn mock_sign(mount: &str, id: &str, response: &SignResponse) -> Mock {
+        sign(mount, id).respond_with(success(response))
+    }
+}
+
+/// Endpoints of the zk-SNARKs backend.
+pub mod zksnarks {
+    use wiremock::{Mock, MockBuilder};
+
+    use super::{endpoint, success};
+    use crate::api::zksnarks::responses::{
+        ZkSnarksAccountResponse, ZkSnarksAccountsResponse, ZkSnarksSignResponse,
+    };
+
+    pub fn create_zksnarks_account(mount: &str) -> MockBuilder {
+        endpoint("POST", mount, "zk-snarks/accounts")
+    }
+
+    pub fn list_zksnarks_accounts(mount: &str) -> MockBuilder {
+        endpoint("GET", mount, "zk-snarks/accounts")
+    }
+
+    pub fn read_zksnarks_account(mount: &str, id: &str) -> MockBuilder {
+        endpoint("GET", mount, &format!("zk-snarks/accounts/{id}"))
+    }
+
+    pub fn zksnarks_sign(mount: &str, id: &str) -> MockBuilder {
+        endpoint("POST", mount, &format!("zk-snarks/accounts/{id}/sign"))
+    }
+
+    pub fn mock_create_zksnarks_account(mount: &str, response: &ZkSnarksAccountResponse) -> Mock {
+        create_zksnarks_account(mount).respond_with(success(response))
+    }
+
+    pub fn mock_list_zksnarks_accounts(mount: &str, response: &ZkSnarksAccountsResponse) -> Mock {
+        list_zksnarks_accounts(mount).respond_with(success(response))
+    }
+
+    pub fn mock_read_zksnarks_account(mount: &str, response: &ZkSnarksAccountResponse) -> Mock {
+        read_zksnarks_account(mount, &response.public_key).respond_with(success(response))
+    }
+
+    pub fn mock_zksnarks_sign(mount: &str, id: &str, response: &ZkSnarksSignResponse) -> Mock {
+        zksnarks_sign(mount, id).respond_with(success(response))
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use web3::signing::keccak256;
+use web3::types::{Address, U256};
+use wiremock::matchers::any;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use crate::api::ethereum::responses::EthereumAccountResponse;
+use crate::api::keys::KeyCryptoAlgorithm;
+use crate::error::ClientError;
+use crate::signer::memory::{legacy_transaction_hash, KeySecret, Store};
+
+/// In-process fake of the Quorum Vault plugin.
+///
+/// The server keeps Ethereum accounts, keys and zk-SNARKs accounts in memory for every
+/// mount, generates real key pairs and answers signing requests with valid signatures,
+/// so a [VaultClient] pointed at [MockVaultServer::uri] behaves like one talking to
+/// Vault with the plugin enabled. Any token is accepted.
+///
+/// zk-SNARKs accounts and `babyjubjub` keys sign with
+/// [babyjubjub][crate::signer::babyjubjub], whose signatures are not byte compatible
+/// with the plugin.
+pub struct MockVaultServer {
+    server: MockServer,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockVaultServer {
+    /// Starts the server on a random local port.
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+        let state = Arc::new(Mutex::new(State::default()));
+        Mock::given(any())
+            .respond_with(Responder {
+                state: state.clone(),
+                request_ids: AtomicU64::new(0),
+            })
+            .mount(&server)
+            .await;
+        Self { server, state }
+    }
+
+    /// Returns the address of the server
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// Creates a client pointed at the server.
+    pub fn client(&self) -> VaultClient {
+        VaultClient::new(
+            VaultClientSettingsBuilder::default()
+                .address(self.uri())
+                .token("root")
+                .build()
+                .unwrap(),
+        )
+        .unwrap()
+    }
+
+    /// Returns every request received by the server so far.
+    pub async fn received_requests(&self) -> Vec<Request> {
+        self.server.received_requests().await.unwrap_or_default()
+    }
+
+    /// Returns the private key of an Ethereum account, hex encoded.
+    pub fn ethereum_private_key(&self, mount: &str, address: Address) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.mounts.get(mount)?.ethereum_private_key(address)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    mounts: HashMap<String, Store>,
+}
+
+struct Responder {
+    state: Arc<Mutex<State>>,
+    request_ids: AtomicU64,
+}
+
+type Reply = Result<Option<Value>, (u16, String)>;
+
+impl Respond for Responder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let reply = self.handle(request);
+        match reply {
+            Ok(data) => {
+                let id = self.request_ids.fetch_add(1, Ordering::Relaxed);
+                ResponseTemplate::new(200).set_body_json(json!({
+                    "request_id": format!("00000000-0000-0000-0000-{id:012x}"),
+                    "lease_id": "",
+                    "renewable": false,
+                    "lease_duration": 0,
+                    "data": data,
+                    "wrap_info": null,
+                    "warnings": null,
+                    "auth": null
+                }))
+            }
+            Err((status, message)) => {
+                ResponseTemplate::new(status).set_body_json(json!({ "errors": [message] }))
+            }
+        }
+    }
+}
+
+impl Responder {
+    fn handle(&self, request: &Request) -> Reply {
+        let segments: Vec<&str> = request
+            .url
+            .path()
+            .trim_start_matches('/')
+            .split('/')
+            .collect();
+        if segments.first() != Some(&"v1") {
+            return Err((404, "unsupported path".to_string()));
+        }
+        let backend = segments
+            .iter()
+            .skip(2)
+            .position(|s| matches!(*s, "ethereum" | "keys" | "zk-snarks"))
+            .map(|i| i + 2)
+            .ok_or_else(|| (404, "unsupported path".to_string()))?;
+        let mount = segments[1..backend].join("/");
+        let route = &segments[backend..];
+        let method = request.method.to_string();
+        let body: Value = if request.body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&request.body).map_err(|e| (400, e.to_string()))?
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let store = state.mounts.entry(mount).or_default();
+        match (method.as_str(), route) {
+            ("POST", ["ethereum", "accounts"]) => reply(ethereum_account(store.create_account())),
+            ("GET", ["ethereum", "accounts"]) => Ok(Some(json!({
+                "keys": store.list_accounts().keys.iter().map(checksum).collect::<Vec<_>>()
+            }))),
+            ("POST", ["ethereum", "accounts", "import"]) => {
+                let body: ImportEthereumBody = parse(body)?;
+                reply(ethereum_account(
+                    store
+                        .import_private_key(&body.private_key)
+                        .map_err(status)?,
+                ))
+            }
+            ("GET", ["ethereum", "accounts", address]) => {
+                let account = store.read_account(parse_address(address)?);
+                reply(ethereum_account(account.map_err(status)?))
+            }
+            ("POST", ["ethereum", "accounts", address, "sign-transaction"]) => {
+                let address = parse_address(address)?;
+                let body: SignTransactionBody = parse(body)?;
+                let signature = store.ethereum_sign_hash(address, &body.hash()?);
+                Ok(Some(json!({ "signature": signature.map_err(status)? })))
+            }
+            ("POST", ["ethereum", "accounts", address, "sign"]) => {
+                let address = parse_address(address)?;
+                let body: DataBody = parse(body)?;
+                let data = hex::decode(body.data.trim_start_matches("0x"))
+                    .map_err(|e| (400, e.to_string()))?;
+                let signature = store.ethereum_sign_hash(address, &keccak256(&data));
+                Ok(Some(json!({ "signature": signature.map_err(status)? })))
+            }
+            ("POST", ["keys"]) => {
+                let body: CreateKeyBody = parse(body)?;
+                let secret = KeySecret::generate(&algorithm(&body.curve, &body.signing_algorithm)?);
+                reply(store.insert_key(&body.id, secret, body.tags))
+            }
+            ("POST", ["keys", "import"]) => {
+                let body: ImportKeyBody = parse(body)?;
+                let algorithm = algorithm(&body.curve, &body.signing_algorithm)?;
+                let secret = KeySecret::decode(&algorithm, &body.private_key).map_err(status)?;
+                reply(store.insert_key(&body.id, secret, body.tags))
+            }
+            ("GET", ["keys"]) => reply(Ok(store.list_keys())),
+            ("GET", ["keys", id]) => reply(store.read_key(id)),
+            ("POST", ["keys", id]) => {
+                let body: UpdateTagsBody = parse(body)?;
+                reply(store.update_key_tags(id, body.tags))
+            }
+            ("DELETE", ["keys", id]) => {
+                store.delete_key(id).map_err(status)?;
+                Ok(None)
+            }
+            ("POST", ["keys", id, "restore"]) => {
+                store.restore_key(id).map_err(status)?;
+                Ok(None)
+            }
+            ("DELETE", ["keys", id, "destroy"]) => {
+                store.destroy_key(id).map_err(status)?;
+                Ok(None)
+            }
+            ("POST", ["keys", id, "sign"]) => {
+                let body: DataBody = parse(body)?;
+                let data = base64::prelude::BASE64_URL_SAFE
+                    .decode(&body.data)
+                    .map_err(|e| (400, e.to_string()))?;
+                let signature = store.key_sign(id, &data).map_err(status)?;
+                Ok(Some(json!({ "signature": signature })))
+            }
+            ("POST", ["zk-snarks", "accounts"]) => reply(Ok(store.create_zksnarks_account())),
+            ("GET", ["zk-snarks", "accounts"]) => reply(Ok(store.list_zksnarks_accounts())),
+            ("GET", ["zk-snarks", "accounts", id]) => reply(store.read_zksnarks_account(id)),
+            ("POST", ["zk-snarks", "accounts", id, "sign"]) => {
+                let body: DataBody = parse(body)?;
+                let data = hex::decode(body.data.trim_start_matches("0x"))
+                    .map_err(|e| (400, e.to_string()))?;
+                let signature = store.zksnarks_sign(id, &data).map_err(status)?;
+                Ok(Some(json!({ "signature": signature })))
+            }
+            _ => Err((405, "unsupported operation".to_string())),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ImportEthereumBody {
+    private_key: String,
+}
+
+#[derive(Deserialize)]
+struct DataBody {
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct CreateKeyBody {
+    id: String,
+    curve: String,
+    signing_algorithm: String,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct ImportKeyBody {
+    id: String,
+    curve: String,
+    signing_algorithm: String,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+    private_key: String,
+}
+
+#[derive(Deserialize)]
+struct UpdateTagsBody {
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct SignTransactionBody {
+    chain_id: String,
+    amount: String,
+    data: String,
+    gas_limit: u64,
+    gas_price: String,
+    nonce: u64,
+    to: String,
+}
+
+impl SignTransactionBody {
+    /// Legacy EIP-155 transaction hash, as signed by the plugin.
+    fn hash(&self) -> Result<[u8; 32], (u16, String)> {
+        let invalid = |field: &str| (400, format!("invalid {field}"));
+        let chain_id = U256::from_dec_str(&self.chain_id).map_err(|_| invalid("chain_id"))?;
+        let amount = U256::from_dec_str(&self.amount).map_err(|_| invalid("amount"))?;
+        let gas_price = U256::from_dec_str(&self.gas_price).map_err(|_| invalid("gas_price"))?;
+        let to: Address = self.to.parse().map_err(|_| invalid("to"))?;
+        let data = hex::decode(self.data.trim_start_matches("0x")).map_err(|_| invalid("data"))?;
+        Ok(legacy_transaction_hash(
+            U256::from(self.nonce),
+            gas_price,
+            U256::from(self.gas_limit),
+            to,
+            amount,
+            &data,
+            chain_id,
+        ))
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: Value) -> Result<T, (u16, String)> {
+    serde_json::from_value(body).map_err(|e| (400, e.to_string()))
+}
+
+fn parse_address(address: &str) -> Result<Address, (u16, String)> {
+    address
+        .parse()
+        .map_err(|_| (400, "invalid address".to_string()))
+}
+
+fn algorithm(curve: &str, signing_algorithm: &str) -> Result<KeyCryptoAlgorithm, (u16, String)> {
+    match (curve, signing_algorithm) {
+        ("secp256k1", "ecdsa") => Ok(KeyCryptoAlgorithm::Secp256k1),
+        ("babyjubjub", "eddsa") => Ok(KeyCryptoAlgorithm::Babyjubjub),
+        _ => Err((400, "invalid signing algorithm or curve".to_string())),
+    }
+}
+
+/// Status and message of an error returned by the [Store].
+fn status(error: ClientError) -> (u16, String) {
+    match error {
+        ClientError::VaultClientError(vaultrs::error::ClientError::APIError { code, errors }) => {
+            (code, errors.join(", "))
+        }
+        error => (500, error.to_string()),
+    }
+}
+
+fn reply<T: Serialize>(result: Result<T, ClientError>) -> Reply {
+    let value = result.map_err(status)?;
+    serde_json::to_value(value)
+        .map(Some)
+        .map_err(|e| (500, e.to_string()))
+}
+
+fn checksum(address: &Address) -> String {
+    eth_checksum::checksum(&format!("{address:?}"))
+}
+
+/// The plugin returns checksummed addresses.
+fn ethereum_account(account: EthereumAccountResponse) -> Result<Value, ClientError> {
+    let address = checksum(&account.address);
+    let mut value = serde_json::to_value(account).unwrap();
+    value["address"] = json!(address);
+    Ok(value)
+}
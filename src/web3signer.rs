@@ -0,0 +1,234 @@
+//! [Web3Signer](https://docs.web3signer.consensys.net/) compatible eth1 REST API,
+//! enabled with the `web3signer` feature.
+//!
+//! The API exposes the Ethereum accounts and the `secp256k1` keys of a signer,
+//! identified by their 64 byte hex encoded public key:
+//!
+//! * `GET /upcheck`
+//! * `GET /api/v1/eth1/publicKeys`
+//! * `POST /api/v1/eth1/sign/{identifier}` with a `{"data": "0x..."}` body, answered with
+//!   the `r || s || v` signature of the keccak256 hash of the data
+//!
+//! Public keys are resolved from the listing of the accounts and keys, which is refreshed
+//! for an unknown public key at most once per refresh interval. Failures are logged and
+//! answered with a generic `500`.
+//!
+//! The `quorum-vault-web3signer` binary serves it in front of Vault.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::Engine;
+use serde::Deserialize;
+use tokio::sync::{Mutex, RwLock};
+use web3::signing::{keccak256, recover};
+use web3::types::Address;
+
+use crate::error::ClientError;
+use crate::signer::{EthereumSigner, KeySigner};
+
+/// Minimum time between two listings triggered by unknown public keys.
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Account or key behind a public key.
+#[derive(Clone, Debug)]
+enum Identity {
+    Account(Address),
+    Key { id: String, address: Address },
+}
+
+/// Web3Signer eth1 API on top of a signer.
+pub struct Web3Signer<S> {
+    signer: S,
+    identities: RwLock<HashMap<String, Identity>>,
+    /// Time of the last listing, locked while listing
+    refreshed: Mutex<Option<Instant>>,
+    refresh_interval: Duration,
+}
+
+impl<S: EthereumSigner + KeySigner> Web3Signer<S> {
+    pub fn new(signer: S) -> Self {
+        Self {
+            signer,
+            identities: RwLock::new(HashMap::new()),
+            refreshed: Mutex::new(None),
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+        }
+    }
+
+    /// Returns the service refreshing the listing for unknown public keys at most once
+    /// per `interval`.
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Returns the signer
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Lists the public keys of the Ethereum accounts and `secp256k1` keys.
+    pub async fn public_keys(&self) -> Result<Vec<String>, ClientError> {
+        let mut refreshed = self.refreshed.lock().await;
+        let public_keys = self.list().await?;
+        *refreshed = Some(Instant::now());
+        Ok(public_keys)
+    }
+
+    /// Lists the accounts and keys again unless it was done less than a refresh interval
+    /// ago.
+    async fn refresh(&self) -> Result<(), ClientError> {
+        let mut refreshed = self.refreshed.lock().await;
+        if refreshed.is_some_and(|at| at.elapsed() < self.refresh_interval) {
+            return Ok(());
+        }
+        self.list().await?;
+        *refreshed = Some(Instant::now());
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>, ClientError> {
+        let mut identities = HashMap::new();
+        for address in self.signer.list_accounts().await?.keys {
+            let account = self.signer.read_account(address).await?;
+            let public_key = hex::decode(account.public_key.trim_start_matches("0x"))
+                .map_err(|e| ClientError::InvalidResponse(e.to_string()))?;
+            identities.insert(identifier(&public_key)?, Identity::Account(address));
+        }
+        for id in self.signer.list_keys().await?.keys {
+            let key = self.signer.read_key(&id).await?;
+            if key.curve != "secp256k1" || key.signing_algorithm != "ecdsa" {
+                continue;
+            }
+            let public_key = base64::prelude::BASE64_URL_SAFE
+                .decode(&key.public_key)
+                .map_err(|e| ClientError::InvalidResponse(e.to_string()))?;
+            let identifier = identifier(&public_key)?;
+            let address = Address::from_slice(&keccak256(&public_key[1..])[12..]);
+            identities.insert(identifier, Identity::Key { id, address });
+        }
+        let mut public_keys: Vec<String> = identities.keys().cloned().collect();
+        public_keys.sort();
+        *self.identities.write().await = identities;
+        Ok(public_keys)
+    }
+
+    /// Signs the keccak256 hash of `data` with the account or key identified by its
+    /// public key, returns `None` when there is no such account or key.
+    pub async fn sign(&self, identifier: &str, data: &[u8]) -> Result<Option<String>, ClientError> {
+        let identifier = normalize(identifier);
+        if identifier.len() != 130 || !identifier[2..].bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Ok(None);
+        }
+        let mut identity = self.identities.read().await.get(&identifier).cloned();
+        if identity.is_none() {
+            self.refresh().await?;
+            identity = self.identities.read().await.get(&identifier).cloned();
+        }
+        let signature = match identity {
+            None => return Ok(None),
+            Some(Identity::Account(address)) => {
+                let signature = self.signer.sign(address, data).await?.signature;
+                let mut signature = decode_signature(&signature)?;
+                if signature.len() != 65 {
+                    return Err(ClientError::InvalidResponse(
+                        "unexpected signature length".to_string(),
+                    ));
+                }
+                if signature[64] < 27 {
+                    signature[64] += 27;
+                }
+                signature
+            }
+            Some(Identity::Key { id, address }) => {
+                let hash = keccak256(data);
+                let signature = self.signer.sign_hash(&id, hash).await?.signature;
+                let signature = base64::prelude::BASE64_URL_SAFE
+                    .decode(signature)
+                    .map_err(|e| ClientError::InvalidResponse(e.to_string()))?;
+                // keys signatures carry no recovery id
+                let recovery_id = (0..2)
+                    .find(|id| recover(&hash, &signature, *id).ok() == Some(address))
+                    .ok_or_else(|| {
+                        ClientError::InvalidResponse("unrecoverable signature".to_string())
+                    })?;
+                let mut signature = signature;
+                signature.push(recovery_id as u8 + 27);
+                signature
+            }
+        };
+        Ok(Some(format!("0x{}", hex::encode(signature))))
+    }
+}
+
+/// Routes of the API.
+pub fn router<S>(service: Arc<Web3Signer<S>>) -> Router
+where
+    S: EthereumSigner + KeySigner + 'static,
+{
+    Router::new()
+        .route("/upcheck", get(|| async { "OK" }))
+        .route("/api/v1/eth1/publicKeys", get(public_keys::<S>))
+        .route("/api/v1/eth1/sign/:identifier", post(sign::<S>))
+        .with_state(service)
+}
+
+#[derive(Deserialize)]
+struct SignBody {
+    data: String,
+}
+
+async fn public_keys<S: EthereumSigner + KeySigner>(
+    State(service): State<Arc<Web3Signer<S>>>,
+) -> Response {
+    match service.public_keys().await {
+        Ok(public_keys) => Json(public_keys).into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+async fn sign<S: EthereumSigner + KeySigner>(
+    State(service): State<Arc<Web3Signer<S>>>,
+    Path(identifier): Path<String>,
+    Json(body): Json<SignBody>,
+) -> Response {
+    let Ok(data) = hex::decode(body.data.trim_start_matches("0x")) else {
+        return (StatusCode::BAD_REQUEST, "invalid data").into_response();
+    };
+    match service.sign(&identifier, &data).await {
+        Ok(Some(signature)) => signature.into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "public key not found").into_response(),
+        Err(e) => internal_error(e),
+    }
+}
+
+fn internal_error(error: ClientError) -> Response {
+    log::error!("Web3Signer request failed: {error}");
+    (StatusCode::INTERNAL_SERVER_ERROR, "internal error").into_response()
+}
+
+/// Hex encoded public key without the uncompressed point prefix.
+fn identifier(public_key: &[u8]) -> Result<String, ClientError> {
+    match public_key {
+        [0x04, point @ ..] if point.len() == 64 => Ok(format!("0x{}", hex::encode(point))),
+        _ => Err(ClientError::InvalidResponse(
+            "expected an uncompressed public key".to_string(),
+        )),
+    }
+}
+
+fn normalize(identifier: &str) -> String {
+    format!("0x{}", identifier.trim_start_matches("0x").to_lowercase())
+}
+
+fn decode_signature(signature: &str) -> Result<Vec<u8>, ClientError> {
+    hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|e| ClientError::InvalidResponse(e.to_string()))
+}
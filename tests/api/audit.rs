@@ -0,0 +1,186 @@
+use quorum_vault_client::api;
+use quorum_vault_client::audit::{AuditOperation, AuditRecord, AuditSink, JsonLinesAuditSink};
+use quorum_vault_client::policy::{PolicyEngine, SigningPolicy};
+use quorum_vault_client::signer::{EthereumSigner, KeySigner, VaultSigner, ZkSigner};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use web3::signing::keccak256;
+use web3::types::{Address, TransactionRequest, U256};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const MOUNT: &str = "audited";
+
+#[derive(Clone, Default)]
+struct MemorySink(Arc<Mutex<Vec<AuditRecord>>>);
+
+impl AuditSink for MemorySink {
+    fn record(&self, record: &AuditRecord) {
+        self.0.lock().unwrap().push(record.clone());
+    }
+}
+
+#[tokio::test]
+async fn test_audit_trail() {
+    let mock = MockServer::start().await;
+    let vault_client = || {
+        VaultClient::new(
+            VaultClientSettingsBuilder::default()
+                .address(mock.uri())
+                .token("s.1234567890abcdef")
+                .build()
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let response = serde_json::json!({
+        "request_id": "5cede0bc-f7ce-d7a7-cba5-2427cee48bd5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "signature": "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501"
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/audited/ethereum/accounts/0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5/sign-transaction"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/audited/zk-snarks/accounts/some-id/sign"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/audited/keys/some-id/sign"))
+        .respond_with(
+            ResponseTemplate::new(404)
+                .set_body_json(serde_json::json!({ "errors": ["key not found"] })),
+        )
+        .mount(&mock)
+        .await;
+
+    let sink = MemorySink::default();
+    let signer = VaultSigner::new(vault_client(), MOUNT).with_audit_sink(sink.clone());
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let to = Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap();
+    let mut tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(to)
+        .value(U256::from(5))
+        .nonce(U256::from(3))
+        .build();
+    tx.gas_price = Some(U256::from(1));
+
+    signer.sign_transaction(1, tx.clone()).await.unwrap();
+    signer
+        .zksnarks_sign("some-id", b"secret message")
+        .await
+        .unwrap();
+    signer
+        .sign_data("some-id", b"secret message")
+        .await
+        .unwrap_err();
+
+    let records = sink.0.lock().unwrap().clone();
+    assert_eq!(records.len(), 3);
+
+    assert_eq!(
+        records[0].operation,
+        AuditOperation::EthereumSignTransaction
+    );
+    assert_eq!(records[0].key, "0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5");
+    let transaction = records[0].transaction.as_ref().unwrap();
+    assert_eq!(transaction.chain_id, "1");
+    assert_eq!(transaction.to, "0x1dabe0acaaa4d1f81b9b43eaf51c8439378231a0");
+    assert_eq!(transaction.amount, "5");
+    assert_eq!(transaction.nonce, 3);
+    assert_eq!(transaction.gas_limit, 21000);
+    assert_eq!(
+        records[0].signature.as_deref(),
+        response["data"]["signature"].as_str()
+    );
+    assert!(records[0].error.is_none());
+
+    assert_eq!(records[1].operation, AuditOperation::ZkSnarksSign);
+    assert_eq!(records[1].key, "some-id");
+    assert_eq!(
+        records[1].payload_hash,
+        format!("0x{}", hex::encode(keccak256(b"secret message")))
+    );
+    assert!(records[1].payload.is_none());
+
+    assert_eq!(records[2].operation, AuditOperation::KeysSign);
+    assert!(records[2].signature.is_none());
+    assert!(records[2].error.is_some());
+
+    // composite signing calls report every signature they make
+    sink.0.lock().unwrap().clear();
+    let audit: &dyn AuditSink = &sink;
+    let client = signer.client();
+    api::ethereum::speed_up_transaction_audited(client, MOUNT, 1, &tx, 10, Some(audit))
+        .await
+        .unwrap();
+    let policy = PolicyEngine::new(SigningPolicy::default());
+    api::ethereum::sign_transaction_with_policy_audited(client, MOUNT, 1, tx, &policy, Some(audit))
+        .await
+        .unwrap();
+    let hashes = [[1u8; 32], [2u8; 32]];
+    let signatures = api::zksnarks::zksnarks_sign_hash_batch_audited(
+        client,
+        MOUNT,
+        "some-id",
+        hashes,
+        2,
+        Some(audit),
+    )
+    .await;
+    assert!(signatures.iter().all(Result::is_ok));
+
+    let records = sink.0.lock().unwrap().clone();
+    let operations: Vec<_> = records.iter().map(|record| record.operation).collect();
+    assert_eq!(
+        operations,
+        [
+            AuditOperation::EthereumSignTransaction,
+            AuditOperation::EthereumSignTransaction,
+            AuditOperation::ZkSnarksSign,
+            AuditOperation::ZkSnarksSign,
+        ]
+    );
+    assert_eq!(records[0].transaction.as_ref().unwrap().gas_price, "2");
+    assert_eq!(records[1].transaction.as_ref().unwrap().gas_price, "1");
+
+    let file = std::env::temp_dir().join(format!("audit-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&file);
+    let signer = VaultSigner::new(vault_client(), MOUNT)
+        .with_audit_sink(JsonLinesAuditSink::open(&file).unwrap().with_payload());
+
+    signer
+        .zksnarks_sign("some-id", b"secret message")
+        .await
+        .unwrap();
+
+    let lines = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(&file).unwrap();
+    let records: Vec<AuditRecord> = lines
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+
+    assert_eq!(record.operation, AuditOperation::ZkSnarksSign);
+    assert_eq!(
+        record.payload.as_deref(),
+        Some(format!("0x{}", hex::encode(b"secret message")).as_str())
+    );
+}
@@ -0,0 +1,233 @@
+use quorum_vault_client::api;
+use quorum_vault_client::auth::{AuthMethod, AuthenticatedClient, Jwt, TokenFileClient};
+use std::time::Duration;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use wiremock::matchers::{body_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(mock: &MockServer) -> VaultClient {
+    VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("static-token")
+            .build()
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+fn auth_response(token: &str, lease_duration: u64) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "request_id": "b5efba4e-9ed6-5e53-66ab-c072a078e6f5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": null,
+        "wrap_info": null,
+        "warnings": null,
+        "auth": {
+            "client_token": token,
+            "accessor": "accessor",
+            "policies": ["default", "quorum"],
+            "token_policies": ["default", "quorum"],
+            "metadata": null,
+            "lease_duration": lease_duration,
+            "renewable": true,
+            "entity_id": "",
+            "token_type": "service",
+            "orphan": true
+        }
+    }))
+}
+
+async fn mock_list_accounts(mock: &MockServer, token: &str, status: u16) {
+    let response = match status {
+        200 => ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "request_id": "b5efba4e-9ed6-5e53-66ab-c072a078e6f5",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": { "keys": ["0x8d3113e29CB92F44F1762E52D2a0276509b36b82"] },
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        })),
+        status => ResponseTemplate::new(status).set_body_json(serde_json::json!({
+            "errors": ["permission denied"]
+        })),
+    };
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/ethereum/accounts"))
+        .and(header("X-Vault-Token", token))
+        .respond_with(response)
+        .mount(mock)
+        .await;
+}
+
+#[tokio::test]
+async fn test_approle_login_and_reauthentication() {
+    let mock = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/approle/login"))
+        .and(body_json(
+            serde_json::json!({ "role_id": "role", "secret_id": "secret" }),
+        ))
+        .respond_with(auth_response("token-1", 3600))
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/approle/login"))
+        .respond_with(auth_response("token-2", 3600))
+        .expect(1)
+        .mount(&mock)
+        .await;
+    mock_list_accounts(&mock, "token-1", 403).await;
+    mock_list_accounts(&mock, "token-2", 200).await;
+
+    let client = AuthenticatedClient::login(client(&mock), AuthMethod::approle("role", "secret"))
+        .await
+        .unwrap();
+    assert_eq!(client.token(), "token-1");
+
+    // sessions use the managed token, without logging in again
+    let session = client.session();
+    assert!(api::ethereum::list_accounts(&*session, "quorum")
+        .await
+        .is_err());
+
+    let accounts = client
+        .call(async |client| api::ethereum::list_accounts(client, "quorum").await)
+        .await
+        .unwrap();
+    assert_eq!(accounts.keys.len(), 1);
+    assert_eq!(client.token(), "token-2");
+    assert!(api::ethereum::list_accounts(&*client.session(), "quorum")
+        .await
+        .is_ok());
+    // earlier sessions keep their token, and are freed once dropped
+    assert_eq!(session.settings.token, "token-1");
+    assert_eq!(std::sync::Arc::strong_count(&session), 1);
+    assert!(!format!("{:?}", client.method()).contains("secret"));
+}
+
+#[tokio::test]
+async fn test_kubernetes_login_and_renewal() {
+    let mock = MockServer::start().await;
+    let jwt = std::env::temp_dir().join(format!("{}-service-account-token", std::process::id()));
+    std::fs::write(&jwt, "service-account-jwt\n").unwrap();
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/k8s/login"))
+        .and(body_json(
+            serde_json::json!({ "role": "signer", "jwt": "service-account-jwt" }),
+        ))
+        .respond_with(auth_response("token-1", 1))
+        .expect(1)
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/token/renew-self"))
+        .and(header("X-Vault-Token", "token-1"))
+        .respond_with(auth_response("token-1", 1))
+        .mount(&mock)
+        .await;
+
+    let method = AuthMethod::Kubernetes {
+        mount: "k8s".to_string(),
+        role: "signer".to_string(),
+        jwt: Jwt::File(jwt.clone()),
+    };
+    let client = AuthenticatedClient::login(client(&mock), method)
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    std::fs::remove_file(&jwt).unwrap();
+
+    let renewals = mock
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/v1/auth/token/renew-self")
+        .count();
+    assert!(renewals >= 1);
+    assert_eq!(client.token(), "token-1");
+}
+
+#[tokio::test]
+async fn test_jwt_login_after_failed_renewal() {
+    let mock = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/jwt/login"))
+        .and(body_json(
+            serde_json::json!({ "role": "ci", "jwt": "jwt-1" }),
+        ))
+        .respond_with(auth_response("token-1", 3600))
+        .up_to_n_times(1)
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/jwt/login"))
+        .respond_with(auth_response("token-2", 3600))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/auth/token/renew-self"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(serde_json::json!({
+            "errors": ["permission denied"]
+        })))
+        .mount(&mock)
+        .await;
+
+    let method = AuthMethod::jwt(Some("ci"), Jwt::Token("jwt-1".to_string()));
+    let client = AuthenticatedClient::login(client(&mock), method)
+        .await
+        .unwrap();
+    assert_eq!(client.token(), "token-1");
+    client.renew().await.unwrap();
+    assert_eq!(client.token(), "token-2");
+}
+
+#[tokio::test]
+async fn test_token_file_reload() {
+    let mock = MockServer::start().await;
+    mock_list_accounts(&mock, "token-1", 200).await;
+    mock_list_accounts(&mock, "token-2", 403).await;
+    mock_list_accounts(&mock, "token-3", 200).await;
+    let sink = std::env::temp_dir().join(format!("{}-vault-agent-sink", std::process::id()));
+    std::fs::write(&sink, "token-1\n").unwrap();
+
+    let watched =
+        TokenFileClient::with_poll_interval(client(&mock), &sink, Duration::from_millis(50))
+            .unwrap();
+    assert_eq!(watched.token(), "token-1");
+    assert!(api::ethereum::list_accounts(&*watched.session(), "quorum")
+        .await
+        .is_ok());
+
+    // picked up by the watch
+    std::fs::write(&sink, "token-2\n").unwrap();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(watched.token(), "token-2");
+
+    // denied with an unchanged file
+    assert!(watched
+        .call(async |client| api::ethereum::list_accounts(client, "quorum").await)
+        .await
+        .is_err());
+
+    // reloaded on 403, before the watch runs
+    let unwatched =
+        TokenFileClient::with_poll_interval(client(&mock), &sink, Duration::from_secs(60)).unwrap();
+    std::fs::write(&sink, "token-3").unwrap();
+    let accounts = unwatched
+        .call(async |client| api::ethereum::list_accounts(client, "quorum").await)
+        .await
+        .unwrap();
+    assert_eq!(accounts.keys.len(), 1);
+    assert_eq!(unwatched.token(), "token-3");
+
+    std::fs::remove_file(&sink).unwrap();
+    assert!(TokenFileClient::new(client(&mock), &sink).is_err());
+}
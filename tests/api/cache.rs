@@ -0,0 +1,238 @@
+use quorum_vault_client::cache::{CacheConfig, CachingSigner};
+use quorum_vault_client::signer::{EthereumSigner, KeySigner, VaultSigner};
+use std::collections::HashMap;
+use std::time::Duration;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn signer(mock: &MockServer, config: CacheConfig) -> CachingSigner<VaultSigner<VaultClient>> {
+    let client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    CachingSigner::new(VaultSigner::new(client, "quorum"), config)
+}
+
+fn success(data: serde_json::Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "request_id": "b5efba4e-9ed6-5e53-66ab-c072a078e6f5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": data,
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    }))
+}
+
+fn key(id: &str, tags: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "created_at": "2023-01-28T13:33:28.583408531Z",
+        "curve": "secp256k1",
+        "id": id,
+        "namespace": "",
+        "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+        "signing_algorithm": "ecdsa",
+        "tags": tags,
+        "updated_at": "2023-01-28T13:33:28.583408531Z",
+        "version": 1
+    })
+}
+
+async fn mock_key(mock: &MockServer, id: &str) {
+    Mock::given(method("GET"))
+        .and(path(format!("/v1/quorum/keys/{id}")))
+        .respond_with(success(key(id, serde_json::json!({}))))
+        .mount(mock)
+        .await;
+}
+
+async fn requests(mock: &MockServer, verb: &str, route: &str) -> usize {
+    mock.received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.method.as_ref() == verb && request.url.path() == route)
+        .count()
+}
+
+#[tokio::test]
+async fn test_cache_reads_and_lists() {
+    let mock = MockServer::start().await;
+    mock_key(&mock, "some-id").await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys"))
+        .respond_with(success(serde_json::json!({ "keys": ["some-id"] })))
+        .mount(&mock)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/ethereum/accounts"))
+        .respond_with(success(serde_json::json!({
+            "keys": ["0x8d3113e29cb92f44f1762e52d2a0276509b36b82"]
+        })))
+        .mount(&mock)
+        .await;
+
+    let signer = signer(
+        &mock,
+        CacheConfig {
+            ttl: Duration::from_millis(200),
+            ..CacheConfig::default()
+        },
+    );
+    for _ in 0..3 {
+        assert_eq!(signer.read_key("some-id").await.unwrap().id, "some-id");
+        assert_eq!(signer.list_keys().await.unwrap().keys, vec!["some-id"]);
+        assert_eq!(signer.list_accounts().await.unwrap().keys.len(), 1);
+    }
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/some-id").await, 1);
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys").await, 1);
+    assert_eq!(
+        requests(&mock, "GET", "/v1/quorum/ethereum/accounts").await,
+        1
+    );
+
+    // expired
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    signer.read_key("some-id").await.unwrap();
+    signer.list_keys().await.unwrap();
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/some-id").await, 2);
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys").await, 2);
+
+    signer.invalidate();
+    signer.read_key("some-id").await.unwrap();
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/some-id").await, 3);
+
+    // errors are not cached
+    signer.read_key("other-id").await.unwrap_err();
+    signer.read_key("other-id").await.unwrap_err();
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/other-id").await, 2);
+}
+
+#[tokio::test]
+async fn test_cache_bounded() {
+    let mock = MockServer::start().await;
+    for id in ["key-1", "key-2", "key-3"] {
+        mock_key(&mock, id).await;
+    }
+
+    let signer = signer(
+        &mock,
+        CacheConfig {
+            max_entries: 2,
+            ..CacheConfig::default()
+        },
+    );
+    for id in ["key-1", "key-2", "key-3", "key-3", "key-2", "key-1"] {
+        signer.read_key(id).await.unwrap();
+    }
+    // key-1 was dropped to make room for key-3
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/key-1").await, 2);
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/key-2").await, 1);
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/key-3").await, 1);
+}
+
+#[tokio::test]
+async fn test_cache_invalidated_by_changes() {
+    let mock = MockServer::start().await;
+    mock_key(&mock, "some-id").await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys"))
+        .respond_with(success(serde_json::json!({ "keys": ["some-id"] })))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(success(key(
+            "some-id",
+            serde_json::json!({ "env": "prod" }),
+        )))
+        .mount(&mock)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/v1/quorum/keys/some-id/destroy"))
+        .respond_with(success(serde_json::Value::Null))
+        .mount(&mock)
+        .await;
+
+    let signer = signer(&mock, CacheConfig::default());
+    signer.read_key("some-id").await.unwrap();
+    signer.list_keys().await.unwrap();
+
+    // the updated key replaces the cached one
+    let tags = HashMap::from([("env".to_string(), "prod".to_string())]);
+    signer.update_key_tags("some-id", tags).await.unwrap();
+    let key = signer.read_key("some-id").await.unwrap();
+    assert_eq!(key.tags["env"], "prod");
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/some-id").await, 1);
+
+    signer.list_keys().await.unwrap();
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys").await, 2);
+
+    signer.destroy_key("some-id").await.unwrap();
+    signer.read_key("some-id").await.unwrap();
+    signer.list_keys().await.unwrap();
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/some-id").await, 2);
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys").await, 3);
+}
+
+#[tokio::test]
+async fn test_cache_read_racing_change() {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(
+            success(key("some-id", serde_json::json!({ "env": "dev" })))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(success(key(
+            "some-id",
+            serde_json::json!({ "env": "prod" }),
+        )))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&mock)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/ethereum/accounts"))
+        .respond_with(success(serde_json::json!({ "keys": [] })))
+        .mount(&mock)
+        .await;
+
+    // a read in flight while the key is updated does not cache the key it read
+    let signer = signer(&mock, CacheConfig::default());
+    let tags = HashMap::from([("env".to_string(), "prod".to_string())]);
+    let (read, updated) = tokio::join!(signer.read_key("some-id"), async {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        signer.update_key_tags("some-id", tags).await
+    });
+    assert_eq!(read.unwrap().tags["env"], "dev");
+    assert_eq!(updated.unwrap().tags["env"], "prod");
+    assert_eq!(
+        signer.read_key("some-id").await.unwrap().tags["env"],
+        "prod"
+    );
+    assert_eq!(requests(&mock, "GET", "/v1/quorum/keys/some-id").await, 1);
+
+    // a failed account creation may have been applied, the list is read again
+    signer.list_accounts().await.unwrap();
+    signer.create_account().await.unwrap_err();
+    signer.list_accounts().await.unwrap();
+    assert_eq!(
+        requests(&mock, "GET", "/v1/quorum/ethereum/accounts").await,
+        2
+    );
+}
@@ -0,0 +1,144 @@
+use quorum_vault_client::testing::MockVaultServer;
+use std::process::Command;
+
+async fn quorum_vault(server: &MockVaultServer, args: &[&str]) -> (bool, String) {
+    let address = server.uri();
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    let output = tokio::task::spawn_blocking(move || {
+        Command::new(env!("CARGO_BIN_EXE_quorum-vault"))
+            .env("VAULT_ADDR", address)
+            .env("VAULT_TOKEN", "root")
+            .args(args)
+            .output()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    (output.status.success(), stdout)
+}
+
+#[tokio::test]
+async fn test_cli() {
+    let server = MockVaultServer::start().await;
+
+    let (ok, out) = quorum_vault(&server, &["--json", "eth", "create"]).await;
+    assert!(ok);
+    let account: serde_json::Value = serde_json::from_str(&out).unwrap();
+    let address = account["address"].as_str().unwrap().to_string();
+
+    let (ok, out) = quorum_vault(&server, &["eth", "list"]).await;
+    assert!(ok);
+    assert_eq!(out.trim(), address);
+
+    let (ok, out) = quorum_vault(
+        &server,
+        &[
+            "eth",
+            "sign-tx",
+            &address,
+            "--chain-id",
+            "1",
+            "--to",
+            "0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0",
+            "--value",
+            "1000",
+            "--gas-price",
+            "1000000000",
+            "--nonce",
+            "0",
+        ],
+    )
+    .await;
+    assert!(ok);
+    assert!(out.starts_with("signature: 0x"));
+    let (ok, _) = quorum_vault(
+        &server,
+        &[
+            "eth",
+            "sign-tx",
+            &address,
+            "--chain-id",
+            "1",
+            "--to",
+            &address,
+        ],
+    )
+    .await;
+    assert!(!ok);
+
+    // private keys are read from a file, an environment variable or stdin
+    let dir = std::env::temp_dir().join(format!("quorum-vault-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let key_file = dir.join("account.hex");
+    std::fs::write(
+        &key_file,
+        "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318\n",
+    )
+    .unwrap();
+    let (ok, out) = quorum_vault(
+        &server,
+        &[
+            "eth",
+            "import",
+            "--private-key-file",
+            key_file.to_str().unwrap(),
+        ],
+    )
+    .await;
+    assert!(ok);
+    assert!(out
+        .to_lowercase()
+        .contains("0x2c7536e3605d9c16a7a3d7b1898e529396a65c23"));
+    let (ok, _) = quorum_vault(&server, &["eth", "import"]).await;
+    assert!(!ok);
+
+    let (ok, out) = quorum_vault(
+        &server,
+        &[
+            "keys", "create", "my-key", "--tag", "env=dev", "--tag", "team=ops",
+        ],
+    )
+    .await;
+    assert!(ok);
+    assert!(out.contains("id: my-key\n"));
+    assert!(out.contains("tags:\n  env: dev\n  team: ops\n"));
+
+    let (ok, out) = quorum_vault(&server, &["--json", "keys", "tag", "my-key"]).await;
+    assert!(ok);
+    let key: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(key["version"], 2);
+    assert_eq!(key["tags"], serde_json::json!({}));
+
+    let (ok, _) = quorum_vault(&server, &["keys", "sign", "my-key", "hello"]).await;
+    assert!(ok);
+    let (ok, _) = quorum_vault(&server, &["keys", "destroy", "my-key"]).await;
+    assert!(ok);
+    let (ok, _) = quorum_vault(&server, &["keys", "read", "my-key"]).await;
+    assert!(!ok);
+
+    let (ok, out) = quorum_vault(&server, &["--mount", "other", "zk", "create"]).await;
+    assert!(ok);
+    assert!(out.contains("curve: babyjubjub\n"));
+    let (ok, out) = quorum_vault(&server, &["zk", "list"]).await;
+    assert!(ok);
+    assert!(out.is_empty());
+
+    let old = dir.join("old.csv").to_str().unwrap().to_string();
+    let new = dir.join("new.json").to_str().unwrap().to_string();
+    let (ok, _) = quorum_vault(&server, &["export", "--format", "csv", "--output", &old]).await;
+    assert!(ok);
+    let csv = std::fs::read_to_string(&old).unwrap().to_lowercase();
+    assert!(csv.contains(&format!("ethereum_account,{}", address.to_lowercase())));
+    let (ok, _) = quorum_vault(&server, &["keys", "create", "new-key"]).await;
+    assert!(ok);
+    let (ok, _) = quorum_vault(&server, &["export", "--output", &new]).await;
+    assert!(ok);
+    let (ok, out) = quorum_vault(&server, &["diff", &old, &new]).await;
+    assert!(!ok);
+    assert_eq!(out, "+ key new-key\n");
+    let (ok, out) = quorum_vault(&server, &["diff", &new, &new]).await;
+    assert!(ok);
+    assert_eq!(out, "no differences\n");
+    std::fs::remove_dir_all(dir).unwrap();
+}
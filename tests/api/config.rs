@@ -0,0 +1,153 @@
+use quorum_vault_client::api;
+use quorum_vault_client::config::Config;
+use std::collections::HashMap;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn write_file(name: &str, content: &str) -> std::path::PathBuf {
+    let file = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+    std::fs::write(&file, content).unwrap();
+    file
+}
+
+#[test]
+fn test_config_files() {
+    let toml = write_file(
+        "config.toml",
+        r#"
+address = "https://vault.example.com:8200"
+namespace = "team"
+mount = "eth"
+"#,
+    );
+    let yaml = write_file(
+        "config.yaml",
+        r#"
+address: https://vault.example.com:8200
+namespace: team
+mount: eth
+"#,
+    );
+    let json = write_file(
+        "config.json",
+        r#"{"address": "https://vault.example.com:8200", "namespace": "team", "mount": "eth"}"#,
+    );
+    let expected = Config {
+        address: Some("https://vault.example.com:8200".to_string()),
+        namespace: Some("team".to_string()),
+        mount: Some("eth".to_string()),
+        ..Config::default()
+    };
+    for file in [&toml, &yaml, &json] {
+        assert_eq!(Config::from_file(file).unwrap(), expected);
+        std::fs::remove_file(file).unwrap();
+    }
+
+    assert!(Config::from_toml("adress = \"typo\"").is_err());
+    assert!(Config::from_file("/nonexistent/config.toml").is_err());
+}
+
+#[test]
+fn test_config_precedence() {
+    let defaults = Config::default();
+    assert_eq!(defaults.address(), "http://127.0.0.1:8200");
+    assert_eq!(defaults.mount(), "quorum");
+
+    let file =
+        Config::from_yaml("address: http://file:8200\ntoken: file-token\nmount: file\n").unwrap();
+    let env: HashMap<&str, &str> = [
+        ("VAULT_ADDR", "http://env:8200"),
+        ("VAULT_TOKEN", ""),
+        ("VAULT_NAMESPACE", "env-namespace"),
+    ]
+    .into_iter()
+    .collect();
+    let config = file.merge(Config::from_lookup(|name| {
+        env.get(name).map(|value| value.to_string())
+    }));
+    assert_eq!(config.address(), "http://env:8200");
+    // empty variables do not override the file
+    assert_eq!(config.token.as_deref(), Some("file-token"));
+    assert_eq!(config.namespace.as_deref(), Some("env-namespace"));
+    assert_eq!(config.mount(), "file");
+    assert_eq!(config.ca_cert, None);
+
+    let debug = format!("{config:?}");
+    assert!(!debug.contains("file-token"));
+    assert!(debug.contains("<redacted>"));
+}
+
+#[tokio::test]
+async fn test_config_client() {
+    let mock = MockServer::start().await;
+    let response = serde_json::json!({
+        "request_id": "b5efba4e-9ed6-5e53-66ab-c072a078e6f5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "keys": ["0x8d3113e29CB92F44F1762E52D2a0276509b36b82"]
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+    Mock::given(method("GET"))
+        .and(path("/v1/eth/ethereum/accounts"))
+        .and(header("X-Vault-Token", "s.1234567890abcdef"))
+        .and(header("X-Vault-Namespace", "team"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let config = Config {
+        address: Some(mock.uri()),
+        token: Some("s.1234567890abcdef".to_string()),
+        namespace: Some("team".to_string()),
+        mount: Some("eth".to_string()),
+        ..Config::default()
+    };
+    let client = config.client().unwrap();
+    let accounts = api::ethereum::list_accounts(&client, config.mount())
+        .await
+        .unwrap();
+    assert_eq!(accounts.keys.len(), 1);
+
+    let invalid = Config {
+        address: Some("not a url".to_string()),
+        ..Config::default()
+    };
+    assert!(invalid.client().is_err());
+}
+
+#[tokio::test]
+async fn test_config_signer_backend() {
+    use quorum_vault_client::signer::BackendSigner;
+
+    let vault = Config::from_toml("mount = \"eth\"")
+        .unwrap()
+        .signer()
+        .unwrap();
+    assert!(matches!(&vault, BackendSigner::Vault(signer) if signer.mount() == "eth"));
+
+    let config = Config::default().merge(Config::from_lookup(|name| {
+        (name == "QUORUM_VAULT_BACKEND").then(|| "in-memory".to_string())
+    }));
+    assert_eq!(config.backend(), "in-memory");
+    #[cfg(feature = "in-memory")]
+    {
+        use quorum_vault_client::signer::EthereumSigner;
+
+        let signer = config.signer().unwrap();
+        assert!(matches!(signer, BackendSigner::InMemory(_)));
+        let account = signer.create_account().await.unwrap();
+        let accounts = signer.list_accounts().await.unwrap();
+        assert_eq!(accounts.keys, vec![account.address]);
+    }
+    #[cfg(not(feature = "in-memory"))]
+    assert!(config.signer().is_err());
+
+    let unknown = Config::from_toml("backend = \"hsm\"").unwrap();
+    assert!(unknown.signer().is_err());
+}
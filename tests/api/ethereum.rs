@@ -1,8 +1,20 @@
 use quorum_vault_client::api;
+use quorum_vault_client::api::ethereum::filler::{FillProvider, TransactionFiller};
+use quorum_vault_client::api::ethereum::nonce::NonceManager;
+use quorum_vault_client::api::ethereum::replacement::{
+    bump_gas_price, decode_signed_transaction, DEFAULT_BUMP_PERCENT,
+};
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::policy::{PolicyEngine, PolicySigner, PolicyViolation, SigningPolicy};
+use quorum_vault_client::signer::{EthereumSigner, VaultSigner};
 use std::str::FromStr;
+use std::sync::Arc;
 use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
-use web3::types::{Address, TransactionRequest, U256};
-use wiremock::matchers::{body_json, method, path};
+use web3::signing::{Key, Signature, SigningError};
+use web3::transports::Http;
+use web3::types::{Address, Bytes, TransactionParameters, TransactionRequest, H256, U256};
+use web3::Web3;
+use wiremock::matchers::{body_json, body_partial_json, method, path};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 #[tokio::test]
@@ -277,6 +289,84 @@ async fn test_import_private_key() {
     assert_eq!(wallet.namespace, "");
 }
 
+#[cfg(feature = "in-memory")]
+#[tokio::test]
+async fn test_ensure_account() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let response = serde_json::json!({
+        "request_id": "a7e5cd28-2867-86d5-7f82-73650ec68950",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "address": "0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F",
+            "compressed_public_key": "0x026b5ae5ec570abb9c4c50746d08fb63c911641170581b07f5f531a993b8b6cbec",
+            "namespace": "",
+            "public_key": "0x046b5ae5ec570abb9c4c50746d08fb63c911641170581b07f5f531a993b8b6cbeced5f8e3de3f4c7416a7661ed2c7eef8fea416c62df47ec43896af26086b87594"
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+    let private_key = "0a1232595b77534d99364bfde13383accbcb40775967a7eacd15d355c96288a5";
+    let address = Address::from_str("0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F").unwrap();
+
+    // missing, then imported
+    Mock::given(method("GET"))
+        .and(path(
+            "/v1/quorum/ethereum/accounts/0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F",
+        ))
+        .respond_with(ResponseTemplate::new(404))
+        .up_to_n_times(1)
+        .mount(&mock)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(
+            "/v1/quorum/ethereum/accounts/0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts/import"))
+        .and(body_json(serde_json::json!({ "private_key": private_key })))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .mount(&mock)
+        .await;
+
+    for _ in 0..2 {
+        let account = api::ethereum::ensure_account(&vault_client, "quorum", address, private_key)
+            .await
+            .unwrap();
+        assert_eq!(account.address, address);
+    }
+    let imports = mock
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.method.as_ref() == "POST")
+        .count();
+    assert_eq!(imports, 1);
+
+    // the key of another account is not imported
+    let other = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let error = api::ethereum::ensure_account(&vault_client, "quorum", other, private_key)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ClientError::Conflict(_)));
+    assert_eq!(mock.received_requests().await.unwrap().len(), 3);
+}
+
 #[tokio::test]
 async fn test_sign_message() {
     let mock = MockServer::start().await;
@@ -325,3 +415,911 @@ async fn test_sign_message() {
 
     assert_eq!(signature.signature, "0xe7905251968e28d6a3696e0c01e5b20ce9e83f185848fe91804d74d958b2aadd28846e605f4e7efac7b4446508607b35e46151f72a6e917e82241781206418d601");
 }
+
+#[tokio::test]
+async fn test_sign_message_batch() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    for (data, signature) in [("0x6f6e65", "0x01"), ("0x74776f", "0x02")] {
+        Mock::given(method("POST"))
+            .and(path(
+                "/v1/quorum/ethereum/accounts/0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5/sign",
+            ))
+            .and(body_json(serde_json::json!({ "data": data })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "request_id": "e81af2c4-4e4c-a640-0f8f-99ce3f7d486a",
+                "lease_id": "",
+                "renewable": false,
+                "lease_duration": 0,
+                "data": {
+                    "signature": signature
+                },
+                "wrap_info": null,
+                "warnings": null,
+                "auth": null
+            })))
+            .mount(&mock)
+            .await;
+    }
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let messages = [b"one".to_vec(), b"two".to_vec(), b"three".to_vec()];
+
+    let signatures = api::ethereum::sign_batch(&vault_client, "quorum", address, messages, 2).await;
+
+    assert_eq!(signatures[0].as_ref().unwrap().signature, "0x01");
+    assert_eq!(signatures[1].as_ref().unwrap().signature, "0x02");
+    // no mock for the last message
+    assert!(signatures[2].is_err());
+}
+
+#[tokio::test]
+async fn test_nonce_manager() {
+    let node = MockServer::start().await;
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+
+    let expected_request = serde_json::json!({
+        "method": "eth_getTransactionCount",
+        "params": ["0xad38e61db0d3f8fef9b4c5dd0c1a9f691cdccff5", "pending"]
+    });
+    let response = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "result": "0x5"
+    });
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(&expected_request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(2)
+        .mount(&node)
+        .await;
+
+    let web3 = Web3::new(Http::new(&node.uri()).unwrap());
+    let nonce_manager = Arc::new(NonceManager::new(web3));
+
+    let handles = (0..10)
+        .map(|_| {
+            let nonce_manager = nonce_manager.clone();
+            tokio::spawn(async move { nonce_manager.next(1, address).await.unwrap() })
+        })
+        .collect::<Vec<_>>();
+    let mut nonces = Vec::new();
+    for handle in handles {
+        nonces.push(handle.await.unwrap().as_u64());
+    }
+    nonces.sort();
+
+    assert_eq!(nonces, (5..15).collect::<Vec<_>>());
+    assert_eq!(nonce_manager.peek(1, address).await, Some(U256::from(15)));
+    assert_eq!(nonce_manager.peek(2, address).await, None);
+
+    let resynced = nonce_manager.resync(1, address).await.unwrap();
+
+    assert_eq!(resynced, U256::from(5));
+    assert_eq!(nonce_manager.next(1, address).await.unwrap(), U256::from(5));
+}
+
+#[tokio::test]
+async fn test_sign_transaction_with_nonce_manager() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let node_response = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "result": "0x7"
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "eth_getTransactionCount" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&node_response))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let expected_request = serde_json::json!({
+      "chain_id": "1",
+      "amount": "0",
+      "data": "0x",
+      "gas_limit": 21000,
+      "gas_price": "1",
+      "nonce": 7,
+      "to": "0x1dabe0acaaa4d1f81b9b43eaf51c8439378231a0",
+    });
+    let response = serde_json::json!({
+        "request_id": "5cede0bc-f7ce-d7a7-cba5-2427cee48bd5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "signature": "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501"
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts/0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5/sign-transaction"))
+        .and(body_json(expected_request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let to = Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap();
+    let nonce_manager = NonceManager::new(Web3::new(Http::new(&mock.uri()).unwrap()));
+
+    let mut tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(to)
+        .gas(U256::from(21000))
+        .build();
+
+    tx.gas_price = Some(U256::from(1));
+
+    let signature = api::ethereum::sign_transaction_with_nonce_manager(
+        &vault_client,
+        "quorum",
+        1,
+        tx,
+        &nonce_manager,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(signature.signature, "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501");
+    assert_eq!(nonce_manager.peek(1, address).await, Some(U256::from(8)));
+}
+
+#[tokio::test]
+async fn test_sign_transaction_with_nonce_manager_releases_nonce() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    mock_rpc(&mock, "eth_getTransactionCount", "0x7", 1).await;
+
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts/0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5/sign-transaction"))
+        .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({ "errors": ["unavailable"] })))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let nonce_manager = NonceManager::new(Web3::new(Http::new(&mock.uri()).unwrap()));
+    let mut tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap())
+        .build();
+    tx.gas_price = Some(U256::from(1));
+
+    let result = api::ethereum::sign_transaction_with_nonce_manager(
+        &vault_client,
+        "quorum",
+        1,
+        tx,
+        &nonce_manager,
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(nonce_manager.peek(1, address).await, Some(U256::from(7)));
+    assert_eq!(nonce_manager.next(1, address).await.unwrap(), U256::from(7));
+}
+
+async fn mock_rpc(node: &MockServer, rpc_method: &str, result: &str, calls: u64) {
+    let response = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "result": result
+    });
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": rpc_method }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(calls)
+        .mount(node)
+        .await;
+}
+
+#[tokio::test]
+async fn test_sign_transaction_with_filler() {
+    let mock = MockServer::start().await;
+    let node = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    mock_rpc(&node, "eth_chainId", "0x539", 1).await;
+    mock_rpc(&node, "eth_getTransactionCount", "0x3", 1).await;
+    mock_rpc(&node, "eth_estimateGas", "0xb411", 2).await;
+    mock_rpc(&node, "eth_gasPrice", "0x3b9aca00", 2).await;
+
+    let expected_request = serde_json::json!({
+      "chain_id": "1337",
+      "amount": "0",
+      "data": "0xa9059cbb",
+      "gas_limit": 46097,
+      "gas_price": "1000000000",
+      "nonce": 3,
+      "to": "0x1dabe0acaaa4d1f81b9b43eaf51c8439378231a0",
+    });
+    let response = serde_json::json!({
+        "request_id": "5cede0bc-f7ce-d7a7-cba5-2427cee48bd5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "signature": "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501"
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts/0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5/sign-transaction"))
+        .and(body_json(expected_request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let to = Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap();
+    let filler = TransactionFiller::new(Web3::new(Http::new(&node.uri()).unwrap()));
+
+    let tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(to)
+        .data(Bytes(vec![0xa9, 0x05, 0x9c, 0xbb]))
+        .build();
+
+    let signature =
+        api::ethereum::sign_transaction_with_filler(&vault_client, "quorum", tx, &filler)
+            .await
+            .unwrap();
+
+    assert_eq!(signature.signature, "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501");
+
+    let tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(to)
+        .nonce(U256::from(10))
+        .build();
+
+    let filled = filler.fill(tx).await.unwrap();
+
+    assert_eq!(filled.chain_id, 1337);
+    assert_eq!(filled.transaction.nonce, Some(U256::from(10)));
+    assert_eq!(filled.transaction.gas, Some(U256::from(46097)));
+    assert_eq!(
+        filled.transaction.gas_price,
+        Some(U256::from(1_000_000_000))
+    );
+    assert_eq!(
+        filler.nonce_manager().peek(1337, address).await,
+        Some(U256::from(4))
+    );
+}
+
+#[tokio::test]
+async fn test_fill_transaction_failure_keeps_nonce() {
+    let node = MockServer::start().await;
+
+    mock_rpc(&node, "eth_getTransactionCount", "0x3", 0).await;
+    Mock::given(method("POST"))
+        .and(body_partial_json(
+            serde_json::json!({ "method": "eth_estimateGas" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "error": { "code": -32000, "message": "execution reverted" }
+        })))
+        .expect(1)
+        .mount(&node)
+        .await;
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let filler = TransactionFiller::with_chain_id(Web3::new(Http::new(&node.uri()).unwrap()), 5);
+    let tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(address)
+        .build();
+
+    assert!(filler.fill(tx).await.is_err());
+    assert_eq!(filler.nonce_manager().peek(5, address).await, None);
+}
+
+#[tokio::test]
+async fn test_fill_provider_chain_id_overflow() {
+    let node = MockServer::start().await;
+    mock_rpc(&node, "eth_chainId", "0x10000000000000000", 1).await;
+
+    let web3 = Web3::new(Http::new(&node.uri()).unwrap());
+    let error = web3.chain_id().await.unwrap_err();
+    assert!(matches!(error, ClientError::InvalidResponse(_)));
+}
+
+#[tokio::test]
+async fn test_fill_transaction_keeps_set_fields() {
+    let node = MockServer::start().await;
+
+    mock_rpc(&node, "eth_chainId", "0x1", 0).await;
+    mock_rpc(&node, "eth_getTransactionCount", "0x3", 0).await;
+    mock_rpc(&node, "eth_estimateGas", "0xb411", 0).await;
+    mock_rpc(&node, "eth_gasPrice", "0x3b9aca00", 0).await;
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let filler = TransactionFiller::with_chain_id(Web3::new(Http::new(&node.uri()).unwrap()), 5);
+
+    let mut tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(address)
+        .gas(U256::from(21000))
+        .nonce(U256::from(1))
+        .build();
+    tx.max_fee_per_gas = Some(U256::from(30));
+
+    let filled = filler.fill(tx).await.unwrap();
+
+    assert_eq!(filled.chain_id, 5);
+    assert_eq!(filled.transaction.nonce, Some(U256::from(1)));
+    assert_eq!(filled.transaction.gas, Some(U256::from(21000)));
+    assert_eq!(filled.transaction.gas_price, Some(U256::from(30)));
+}
+
+#[tokio::test]
+async fn test_speed_up_transaction() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let expected_request = serde_json::json!({
+      "chain_id": "1",
+      "amount": "1000000000000000000",
+      "data": "0x",
+      "gas_limit": 21000,
+      "gas_price": "1100000000",
+      "nonce": 3,
+      "to": "0x1dabe0acaaa4d1f81b9b43eaf51c8439378231a0",
+    });
+    let response = serde_json::json!({
+        "request_id": "5cede0bc-f7ce-d7a7-cba5-2427cee48bd5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "signature": "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501"
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts/0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5/sign-transaction"))
+        .and(body_json(expected_request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let to = Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap();
+
+    let mut tx: TransactionRequest = TransactionRequest::builder()
+        .from(address)
+        .to(to)
+        .value(U256::from_dec_str("1000000000000000000").unwrap())
+        .gas(U256::from(21000))
+        .nonce(U256::from(3))
+        .build();
+
+    tx.gas_price = Some(U256::from(1_000_000_000));
+
+    let signature =
+        api::ethereum::speed_up_transaction(&vault_client, "quorum", 1, &tx, DEFAULT_BUMP_PERCENT)
+            .await
+            .unwrap();
+
+    assert_eq!(signature.signature, "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501");
+
+    tx.nonce = None;
+
+    assert!(
+        api::ethereum::speed_up_transaction(&vault_client, "quorum", 1, &tx, 10)
+            .await
+            .is_err()
+    );
+
+    tx.nonce = Some(U256::from(3));
+    tx.to = None;
+
+    assert!(matches!(
+        api::ethereum::speed_up_transaction(&vault_client, "quorum", 1, &tx, 10).await,
+        Err(ClientError::InvalidTransaction(_))
+    ));
+}
+
+struct LocalKey(secp256k1::SecretKey);
+
+impl Key for &LocalKey {
+    fn sign(&self, message: &[u8], chain_id: Option<u64>) -> Result<Signature, SigningError> {
+        let signature = self.sign_message(message)?;
+        let v = match chain_id {
+            Some(chain_id) => signature.v + 35 + chain_id * 2,
+            None => signature.v + 27,
+        };
+        Ok(Signature { v, ..signature })
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature, SigningError> {
+        let message =
+            secp256k1::Message::from_slice(message).map_err(|_| SigningError::InvalidMessage)?;
+        let (recovery_id, signature) = secp256k1::Secp256k1::new()
+            .sign_ecdsa_recoverable(&message, &self.0)
+            .serialize_compact();
+        Ok(Signature {
+            v: recovery_id.to_i32() as u64,
+            r: H256::from_slice(&signature[..32]),
+            s: H256::from_slice(&signature[32..]),
+        })
+    }
+
+    fn address(&self) -> Address {
+        let public_key = self
+            .0
+            .public_key(&secp256k1::Secp256k1::new())
+            .serialize_uncompressed();
+        Address::from_slice(&web3::signing::keccak256(&public_key[1..])[12..])
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_signed_transaction() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let key = LocalKey(
+        secp256k1::SecretKey::from_str(
+            "0a1232595b77534d99364bfde13383accbcb40775967a7eacd15d355c96288a5",
+        )
+        .unwrap(),
+    );
+    let to = Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap();
+    let web3 = Web3::new(Http::new(&mock.uri()).unwrap());
+    let signed = web3
+        .accounts()
+        .sign_transaction(
+            TransactionParameters {
+                nonce: Some(U256::from(42)),
+                to: Some(to),
+                gas: U256::from(60000),
+                gas_price: Some(U256::from(20)),
+                value: U256::from(5),
+                data: Bytes(vec![0xde, 0xad, 0xbe, 0xef]),
+                chain_id: Some(1337),
+                ..Default::default()
+            },
+            &key,
+        )
+        .await
+        .unwrap();
+
+    let decoded = decode_signed_transaction(&signed.raw_transaction.0).unwrap();
+
+    assert_eq!(decoded.chain_id, 1337);
+    assert_eq!(
+        decoded.transaction.from,
+        Address::from_str("0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F").unwrap()
+    );
+    assert_eq!(decoded.transaction.to, Some(to));
+    assert_eq!(decoded.transaction.nonce, Some(U256::from(42)));
+    assert_eq!(decoded.transaction.gas, Some(U256::from(60000)));
+    assert_eq!(decoded.transaction.gas_price, Some(U256::from(20)));
+    assert_eq!(decoded.transaction.value, Some(U256::from(5)));
+    assert_eq!(
+        decoded.transaction.data,
+        Some(Bytes(vec![0xde, 0xad, 0xbe, 0xef]))
+    );
+
+    let expected_request = serde_json::json!({
+      "chain_id": "1337",
+      "amount": "0",
+      "data": "0x",
+      "gas_limit": 21000,
+      "gas_price": "30",
+      "nonce": 42,
+      "to": "0xecb96104c306df32aed607ef0b8a44cc94be782f",
+    });
+    let response = serde_json::json!({
+        "request_id": "5cede0bc-f7ce-d7a7-cba5-2427cee48bd5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "signature": "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501"
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts/0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F/sign-transaction"))
+        .and(body_json(expected_request))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(1)
+        .mount(&mock)
+        .await;
+
+    let signature = api::ethereum::cancel_signed_transaction(
+        &vault_client,
+        "quorum",
+        &signed.raw_transaction.0,
+        50,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(signature.signature, "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501");
+}
+
+#[test]
+fn test_bump_gas_price() {
+    assert_eq!(bump_gas_price(U256::from(100), 10), U256::from(110));
+    assert_eq!(bump_gas_price(U256::from(101), 10), U256::from(112));
+    assert_eq!(bump_gas_price(U256::from(1), 10), U256::from(2));
+    assert_eq!(bump_gas_price(U256::zero(), 10), U256::one());
+    assert_eq!(bump_gas_price(U256::from(100), 0), U256::from(110));
+    assert_eq!(bump_gas_price(U256::MAX, 10), U256::MAX);
+    assert_eq!(bump_gas_price(U256::MAX / 2, 200), U256::MAX);
+}
+
+#[tokio::test]
+async fn test_sign_transaction_with_policy() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let response = serde_json::json!({
+        "request_id": "5cede0bc-f7ce-d7a7-cba5-2427cee48bd5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": {
+            "signature": "0x3f3c03151a9451832d7b3abacec63cee23e4f697690db03e980ce5b02594a6e6657e236309cbcaa0de351162d69422aa3ebaf2349a8c5e612bd971d9de18be8501"
+        },
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/ethereum/accounts/0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5/sign-transaction"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&response))
+        .expect(2)
+        .mount(&mock)
+        .await;
+
+    let policy = SigningPolicy::from_toml(
+        r#"
+        allowed_chain_ids = [1]
+        allowed_recipients = ["0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0"]
+        max_value_per_transaction = "1000000000000000000"
+        forbidden_selectors = ["0x095ea7b3"]
+
+        [max_value_per_window]
+        value = "1500000000000000000"
+        window_seconds = 3600
+        "#,
+    )
+    .unwrap();
+    let policy = PolicyEngine::new(policy);
+
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let to = Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap();
+    let one_eth = U256::from_dec_str("1000000000000000000").unwrap();
+
+    let tx = |value: U256, to: Address, data: Vec<u8>| {
+        let mut tx: TransactionRequest = TransactionRequest::builder()
+            .from(address)
+            .to(to)
+            .value(value)
+            .data(Bytes(data))
+            .gas(U256::from(21000))
+            .nonce(U256::from(0))
+            .build();
+        tx.gas_price = Some(U256::from(1));
+        tx
+    };
+
+    let violation = |result: Result<_, ClientError>| match result {
+        Err(ClientError::PolicyViolation(violation)) => violation,
+        other => panic!("expected policy violation, got {other:?}"),
+    };
+
+    api::ethereum::sign_transaction_with_policy(
+        &vault_client,
+        "quorum",
+        1,
+        tx(one_eth, to, vec![]),
+        &policy,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        violation(
+            api::ethereum::sign_transaction_with_policy(
+                &vault_client,
+                "quorum",
+                5,
+                tx(one_eth, to, vec![]),
+                &policy
+            )
+            .await
+        ),
+        PolicyViolation::ChainNotAllowed(5)
+    );
+    assert_eq!(
+        violation(
+            api::ethereum::sign_transaction_with_policy(
+                &vault_client,
+                "quorum",
+                1,
+                tx(one_eth, address, vec![]),
+                &policy
+            )
+            .await
+        ),
+        PolicyViolation::RecipientNotAllowed(address)
+    );
+    assert!(matches!(
+        violation(
+            api::ethereum::sign_transaction_with_policy(
+                &vault_client,
+                "quorum",
+                1,
+                tx(one_eth * 2, to, vec![]),
+                &policy
+            )
+            .await
+        ),
+        PolicyViolation::ValueLimitExceeded { .. }
+    ));
+    assert_eq!(
+        violation(
+            api::ethereum::sign_transaction_with_policy(
+                &vault_client,
+                "quorum",
+                1,
+                tx(U256::zero(), to, vec![0x09, 0x5e, 0xa7, 0xb3, 0x00]),
+                &policy
+            )
+            .await
+        ),
+        PolicyViolation::ForbiddenSelector {
+            selector: Bytes(vec![0x09, 0x5e, 0xa7, 0xb3])
+        }
+    );
+    assert!(matches!(
+        violation(
+            api::ethereum::sign_transaction_with_policy(
+                &vault_client,
+                "quorum",
+                1,
+                tx(one_eth, to, vec![]),
+                &policy
+            )
+            .await
+        ),
+        PolicyViolation::WindowLimitExceeded { .. }
+    ));
+
+    api::ethereum::sign_transaction_with_policy(
+        &vault_client,
+        "quorum",
+        1,
+        tx(one_eth / 2, to, vec![]),
+        &policy,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_sign_with_policy() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&mock)
+        .await;
+
+    let policy = PolicyEngine::new(
+        SigningPolicy::from_json(r#"{ "allow_message_signing": false }"#).unwrap(),
+    );
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+
+    let result =
+        api::ethereum::sign_with_policy(&vault_client, "quorum", address, b"Hello", &policy).await;
+
+    assert!(matches!(
+        result,
+        Err(ClientError::PolicyViolation(
+            PolicyViolation::MessageSigningNotAllowed
+        ))
+    ));
+
+    let policy = PolicyEngine::new(
+        SigningPolicy::from_json(&format!(
+            r#"{{ "allowed_message_signers": ["{address:?}"], "max_message_size": 4 }}"#
+        ))
+        .unwrap(),
+    );
+    assert_eq!(policy.check_message(address, b"Hell"), Ok(()));
+    assert_eq!(
+        policy.check_message(address, b"Hello"),
+        Err(PolicyViolation::MessageTooLarge { size: 5, limit: 4 })
+    );
+    assert_eq!(
+        policy.check_message(Address::zero(), b"Hi"),
+        Err(PolicyViolation::MessageSignerNotAllowed(Address::zero()))
+    );
+}
+
+#[test]
+fn test_policy_reservations() {
+    let policy = PolicyEngine::new(
+        SigningPolicy::from_json(
+            r#"{ "max_value_per_window": { "value": "10", "window_seconds": 3600 } }"#,
+        )
+        .unwrap(),
+    );
+    let tx = TransactionRequest::builder()
+        .from(Address::from_low_u64_be(1))
+        .to(Address::from_low_u64_be(2))
+        .value(U256::from(5))
+        .build();
+
+    let first = policy.check_transaction(1, &tx).unwrap();
+    let second = policy.check_transaction(1, &tx).unwrap();
+    assert!(matches!(
+        policy.check_transaction(1, &tx),
+        Err(PolicyViolation::WindowLimitExceeded { .. })
+    ));
+
+    // identical transactions hold separate reservations, released one at a time
+    policy.release(second);
+    let third = policy.check_transaction(1, &tx).unwrap();
+    assert!(policy.check_transaction(1, &tx).is_err());
+    policy.release(first);
+    policy.release(third);
+    let _ = policy.check_transaction(1, &tx).unwrap();
+    let _ = policy.check_transaction(1, &tx).unwrap();
+}
+
+#[tokio::test]
+async fn test_policy_signer() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(2)
+        .mount(&mock)
+        .await;
+
+    let signer = PolicySigner::new(
+        VaultSigner::new(vault_client, "quorum"),
+        PolicyEngine::new(
+            SigningPolicy::from_json(
+                r#"{
+                    "allowed_chain_ids": [1],
+                    "allow_message_signing": false,
+                    "max_value_per_window": { "value": "10", "window_seconds": 3600 }
+                }"#,
+            )
+            .unwrap(),
+        ),
+    );
+    let address = Address::from_str("0xAd38E61dB0D3f8fEF9B4c5DD0C1A9F691cdCcfF5").unwrap();
+    let tx = TransactionRequest::builder()
+        .from(address)
+        .to(Address::from_low_u64_be(2))
+        .value(U256::from(10))
+        .build();
+
+    assert!(matches!(
+        signer.sign_transaction(5, tx.clone()).await,
+        Err(ClientError::PolicyViolation(
+            PolicyViolation::ChainNotAllowed(5)
+        ))
+    ));
+    assert!(matches!(
+        signer.sign(address, b"Hello").await,
+        Err(ClientError::PolicyViolation(
+            PolicyViolation::MessageSigningNotAllowed
+        ))
+    ));
+
+    // a transaction Vault fails to sign does not count towards the window limit
+    for _ in 0..2 {
+        assert!(matches!(
+            signer.sign_transaction(1, tx.clone()).await,
+            Err(ClientError::VaultClientError(_))
+        ));
+    }
+}
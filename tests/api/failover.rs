@@ -0,0 +1,298 @@
+use quorum_vault_client::api;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::failover::{CircuitBreaker, FailoverClient, FailoverSigner, Role};
+use quorum_vault_client::retry::RetryPolicy;
+use quorum_vault_client::signer::KeySigner;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(address: &str) -> VaultClient {
+    VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(address)
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+fn success(data: serde_json::Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "request_id": "b5efba4e-9ed6-5e53-66ab-c072a078e6f5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": data,
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    }))
+}
+
+fn health() -> serde_json::Value {
+    serde_json::json!({
+        "cluster_id": "c9abceea-4f46-4dab-a688-5ce55f89e228",
+        "cluster_name": "vault-cluster-5515c810",
+        "initialized": true,
+        "performance_standby": false,
+        "replication_dr_mode": "disabled",
+        "replication_perf_mode": "disabled",
+        "sealed": false,
+        "server_time_utc": 1674912808,
+        "standby": false,
+        "version": "1.12.2"
+    })
+}
+
+fn key() -> serde_json::Value {
+    serde_json::json!({
+        "created_at": "2023-01-28T13:33:28.583408531Z",
+        "curve": "secp256k1",
+        "id": "some-id",
+        "namespace": "",
+        "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+        "signing_algorithm": "ecdsa",
+        "tags": {},
+        "updated_at": "2023-01-28T13:33:28.583408531Z",
+        "version": 1
+    })
+}
+
+/// Starts an active node serving `some-id`.
+async fn active() -> MockServer {
+    let mock = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/sys/health"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(health()))
+        .mount(&mock)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(success(key()))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/some-id/sign"))
+        .respond_with(success(serde_json::json!({ "signature": "c2lnbmF0dXJl" })))
+        .mount(&mock)
+        .await;
+    mock
+}
+
+/// Starts a performance standby answering every request but health checks with
+/// `status` and `body`, as wiremock cannot answer with the `473` of its health check.
+/// Returns its address and the number of requests other than health checks.
+async fn performance_standby(status: u16, body: serde_json::Value) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                match stream.read(&mut buffer).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buffer[..n]),
+                }
+            }
+            let (status, body) = if request.starts_with(b"GET /v1/sys/health") {
+                (473, health())
+            } else {
+                counter.fetch_add(1, Ordering::SeqCst);
+                (status, body.clone())
+            };
+            let body = body.to_string();
+            let response = format!(
+                "HTTP/1.1 {status} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    (address, requests)
+}
+
+/// Requests received by `mock` other than health checks.
+async fn requests(mock: &MockServer) -> usize {
+    mock.received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() != "/v1/sys/health")
+        .count()
+}
+
+#[tokio::test]
+async fn test_failover_routes_by_role() {
+    let active = active().await;
+    let envelope = serde_json::json!({
+        "request_id": "b5efba4e-9ed6-5e53-66ab-c072a078e6f5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": key(),
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    });
+    let (standby, standby_requests) = performance_standby(200, envelope).await;
+
+    let failover = FailoverClient::new(vec![client(&active.uri()), client(&standby)]).unwrap();
+    failover.discover().await;
+    let roles: Vec<Role> = failover.nodes().iter().map(|node| node.role).collect();
+    assert_eq!(roles, vec![Role::Active, Role::PerformanceStandby]);
+
+    let signer = FailoverSigner::new(failover, "quorum");
+    let key = signer.read_key("some-id").await.unwrap();
+    assert_eq!(key.id, "some-id");
+    assert_eq!(standby_requests.load(Ordering::SeqCst), 1);
+    assert_eq!(requests(&active).await, 0);
+
+    let signature = signer.sign_data("some-id", b"data").await.unwrap();
+    assert_eq!(signature.signature, "c2lnbmF0dXJl");
+    assert_eq!(requests(&active).await, 1);
+    assert_eq!(standby_requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_failover_opens_circuit() {
+    let never = RetryPolicy::never();
+    let active = active().await;
+    let errors = serde_json::json!({ "errors": ["temporarily unavailable"] });
+    let (standby, standby_requests) = performance_standby(503, errors).await;
+
+    let failover = FailoverClient::new(vec![client(&active.uri()), client(&standby)])
+        .unwrap()
+        .with_circuit_breaker(CircuitBreaker {
+            failure_threshold: 2,
+            open_duration: Duration::from_millis(200),
+        });
+    failover.discover().await;
+
+    for _ in 0..3 {
+        let key = failover
+            .read(|node| api::keys::read_key_with_retry(node, "quorum", "some-id", &never))
+            .await
+            .unwrap();
+        assert_eq!(key.id, "some-id");
+    }
+    // the circuit opened after the second failure
+    assert_eq!(standby_requests.load(Ordering::SeqCst), 2);
+    assert_eq!(requests(&active).await, 3);
+    assert!(failover.nodes()[1].open);
+
+    // a single trial once the circuit is half-open, failing again
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    failover
+        .read(|node| api::keys::read_key_with_retry(node, "quorum", "some-id", &never))
+        .await
+        .unwrap();
+    assert_eq!(standby_requests.load(Ordering::SeqCst), 3);
+    assert!(failover.nodes()[1].open);
+
+    // not found is an answer, not a node failure
+    let error = failover
+        .write(|node| api::keys::read_key_with_retry(node, "quorum", "other-id", &never))
+        .await
+        .unwrap_err();
+    assert_eq!(error.status(), Some(404));
+    assert_eq!(failover.nodes()[0].failures, 0);
+}
+
+#[tokio::test]
+async fn test_failover_unreachable_nodes() {
+    let never = RetryPolicy::never();
+    let address = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let active = active().await;
+
+    let failover = FailoverClient::new(vec![client(&address), client(&active.uri())]).unwrap();
+    failover.discover().await;
+    assert_eq!(failover.nodes()[0].role, Role::Unknown);
+
+    let signer = FailoverSigner::new(failover, "quorum");
+    signer.sign_data("some-id", b"data").await.unwrap();
+    assert_eq!(requests(&active).await, 1);
+
+    let failover = FailoverClient::new(vec![client(&address)])
+        .unwrap()
+        .with_circuit_breaker(CircuitBreaker {
+            failure_threshold: 1,
+            open_duration: Duration::from_secs(30),
+        });
+    let error = failover
+        .read(|node| api::keys::list_keys_with_retry(node, "quorum", &never))
+        .await
+        .unwrap_err();
+    assert!(error.is_connection_error());
+    let error = failover
+        .read(|node| api::keys::list_keys_with_retry(node, "quorum", &never))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ClientError::Unavailable(_)));
+
+    assert!(matches!(
+        FailoverClient::new(vec![]),
+        Err(ClientError::ConfigError(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_failover_writes_sent_once() {
+    let active = active().await;
+    let errors = serde_json::json!({ "errors": ["internal error"] });
+    let (failing, failing_requests) = performance_standby(500, errors).await;
+
+    // without discovery the nodes are tried in the configured order
+    let failover = FailoverClient::new(vec![client(&failing), client(&active.uri())]).unwrap();
+    let signer = FailoverSigner::new(failover, "quorum");
+
+    // the failing node may have signed, the write is not sent to the next node
+    let error = signer.sign_data("some-id", b"data").await.unwrap_err();
+    assert_eq!(error.status(), Some(500));
+    assert!(!error.is_connect_error());
+    assert_eq!(failing_requests.load(Ordering::SeqCst), 1);
+    assert_eq!(requests(&active).await, 0);
+
+    // reads move on
+    signer.read_key("some-id").await.unwrap();
+    assert_eq!(failing_requests.load(Ordering::SeqCst), 2);
+    assert_eq!(requests(&active).await, 1);
+}
+
+#[tokio::test]
+async fn test_failover_sealed_nodes() {
+    let never = RetryPolicy::never();
+    let sealed = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/sys/health"))
+        .respond_with(ResponseTemplate::new(503).set_body_json(health()))
+        .mount(&sealed)
+        .await;
+
+    let failover = FailoverClient::new(vec![client(&sealed.uri())]).unwrap();
+    failover.discover().await;
+    assert_eq!(failover.nodes()[0].role, Role::Unavailable);
+
+    let error = failover
+        .read(|node| api::keys::read_key_with_retry(node, "quorum", "some-id", &never))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&error, ClientError::Unavailable(message) if message.contains("sealed")),
+        "{error}"
+    );
+    assert_eq!(requests(&sealed).await, 0);
+}
@@ -0,0 +1,95 @@
+use quorum_vault_client::api;
+use quorum_vault_client::import::{self, ImportStatus, Manifest};
+use quorum_vault_client::testing::MockVaultServer;
+
+const ACCOUNT_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+const ACCOUNT_ADDRESS: &str = "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23";
+const SIGNER_KEY: &str = "8da4ef21b864d2cc526dbdb2a120bd2874c36c9d0a1fb7f8c63d7f7a8b41de8f";
+const PROVER_KEY: &str = "0001020304050607080910111213141516171819202122232425262728293031";
+
+#[tokio::test]
+async fn test_bulk_import() {
+    let server = MockVaultServer::start().await;
+    let client = server.client();
+    let dir = std::env::temp_dir().join(format!("quorum-vault-import-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("keys")).unwrap();
+    std::fs::write(dir.join("keys/signer.hex"), format!("{SIGNER_KEY}\n")).unwrap();
+    std::fs::write(dir.join("keys/treasury.hex"), ACCOUNT_KEY).unwrap();
+    std::env::set_var("QUORUM_VAULT_TEST_PROVER_KEY", PROVER_KEY);
+    std::fs::write(
+        dir.join("manifest.csv"),
+        format!(
+            r#"kind,id,algorithm,tags,key_ref
+key,signer,secp256k1,"{{""team"":""payments""}}",file:keys/signer.hex
+key,prover,babyjubjub,,env:QUORUM_VAULT_TEST_PROVER_KEY
+ethereum_account,{ACCOUNT_ADDRESS},,,file:keys/treasury.hex
+key,missing,,,file:keys/missing.hex
+"#
+        ),
+    )
+    .unwrap();
+    let manifest = Manifest::from_file(dir.join("manifest.csv")).unwrap();
+
+    let report = import::import(&client, "quorum", &manifest, 2).await;
+    let statuses: Vec<_> = report.results.iter().map(|result| result.status).collect();
+    assert_eq!(
+        statuses,
+        [
+            ImportStatus::Imported,
+            ImportStatus::Unverified,
+            ImportStatus::Imported,
+            ImportStatus::Failed
+        ]
+    );
+    assert!(!report.is_success());
+    assert_eq!(report.results[2].id.as_deref(), Some(ACCOUNT_ADDRESS));
+    assert!(report.results[3]
+        .error
+        .as_ref()
+        .unwrap()
+        .contains("missing.hex"));
+    let key = api::keys::read_key(&client, "quorum", "signer")
+        .await
+        .unwrap();
+    assert_eq!(key.tags["team"], "payments");
+    assert_eq!(report.results[0].public_key.as_ref(), Some(&key.public_key));
+
+    for report in [report.to_csv(), report.to_json(), report.to_string()] {
+        for secret in [ACCOUNT_KEY.trim_start_matches("0x"), SIGNER_KEY, PROVER_KEY] {
+            assert!(!report.contains(secret));
+        }
+    }
+
+    // keys imported by an earlier run are verified and reported as such, but for the
+    // babyjubjub key whose public key is not derived
+    let report = import::import(&client, "quorum", &manifest, 2).await;
+    assert_eq!(report.results[0].status, ImportStatus::AlreadyImported);
+    assert_eq!(report.results[1].status, ImportStatus::Unverified);
+    assert_eq!(report.results[2].status, ImportStatus::AlreadyImported);
+    assert_eq!(report.results[2].id.as_deref(), Some(ACCOUNT_ADDRESS));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_bulk_import_manifest_validation() {
+    let manifest = Manifest::from_json(
+        r#"{"entries": [
+            {"id": "signer", "key_ref": "env:SIGNER_KEY"},
+            {"kind": "ethereum_account", "key_ref": "file:treasury.hex"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(manifest.entries.len(), 2);
+
+    for invalid in [
+        r#"{"entries": [{"key_ref": "env:SIGNER_KEY"}]}"#,
+        r#"{"entries": [{"id": "a", "key_ref": "env:A"}, {"id": "a", "key_ref": "env:B"}]}"#,
+        r#"{"entries": [{"kind": "ethereum_account", "algorithm": "babyjubjub", "key_ref": "env:A"}]}"#,
+        r#"{"entries": [{"kind": "zksnarks_account", "key_ref": "env:A"}]}"#,
+        r#"{"entries": [{"id": "a", "private_key": "00", "key_ref": "env:A"}]}"#,
+    ] {
+        assert!(Manifest::from_json(invalid).is_err(), "{invalid}");
+    }
+    assert!(Manifest::from_csv("id,private_key\na,00\n").is_err());
+    assert!(Manifest::from_csv("id\na\n").is_err());
+}
@@ -0,0 +1,119 @@
+use quorum_vault_client::api;
+use quorum_vault_client::api::keys::KeyCryptoAlgorithm;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::inventory::{self, EntryKind, Inventory};
+use quorum_vault_client::testing::MockVaultServer;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_inventory_export_and_diff() {
+    let server = MockVaultServer::start().await;
+    let client = server.client();
+    let account = api::ethereum::create_account(&client, "quorum")
+        .await
+        .unwrap();
+    let tags = HashMap::from([("env".to_string(), "prod, \"eu\"".to_string())]);
+    for id in ["payments", "archive"] {
+        api::keys::create_key(
+            &client,
+            "quorum",
+            id,
+            KeyCryptoAlgorithm::Secp256k1,
+            tags.clone(),
+        )
+        .await
+        .unwrap();
+    }
+    api::zksnarks::create_zksnarks_account(&client, "quorum")
+        .await
+        .unwrap();
+
+    let old = inventory::export(&client, "quorum", 4).await.unwrap();
+    assert_eq!(old.version, inventory::INVENTORY_VERSION);
+    assert_eq!(old.mount, "quorum");
+    let kinds: Vec<_> = old.entries.iter().map(|entry| entry.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            EntryKind::EthereumAccount,
+            EntryKind::Key,
+            EntryKind::Key,
+            EntryKind::ZksnarksAccount
+        ]
+    );
+    assert_eq!(old.entries[0].public_key, account.public_key);
+    assert_eq!(old.entries[1].id, "archive");
+    assert_eq!(old.entries[1].tags["env"], "prod, \"eu\"");
+    assert_eq!(old.entries[3].curve, "babyjubjub");
+
+    assert_eq!(Inventory::from_csv(&old.to_csv()).unwrap(), old);
+    assert_eq!(Inventory::from_json(&old.to_json()).unwrap(), old);
+    assert!(inventory::diff(&old, &old).is_empty());
+
+    api::keys::destroy_key(&client, "quorum", "archive")
+        .await
+        .unwrap();
+    api::keys::update_key_tags(&client, "quorum", "payments", HashMap::new())
+        .await
+        .unwrap();
+    api::keys::create_key(
+        &client,
+        "quorum",
+        "rollup",
+        KeyCryptoAlgorithm::Babyjubjub,
+        HashMap::new(),
+    )
+    .await
+    .unwrap();
+    let new = inventory::export(&client, "quorum", 4).await.unwrap();
+    let diff = inventory::diff(&old, &new);
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].id, "rollup");
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].id, "archive");
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].to.id, "payments");
+    assert!(diff.changed[0].fields.contains(&"tags"));
+    assert!(!diff.changed[0].fields.contains(&"public_key"));
+    assert_eq!(
+        diff.to_string(),
+        format!(
+            "+ key rollup\n- key archive\n~ key payments: {}\n",
+            diff.changed[0].fields.join(", ")
+        )
+    );
+}
+
+#[test]
+fn test_inventory_validation() {
+    let mut inventory = Inventory {
+        version: 2,
+        mount: "quorum".to_string(),
+        exported_at: 0,
+        entries: vec![],
+    };
+    assert!(matches!(
+        Inventory::from_json(&inventory.to_json()),
+        Err(ClientError::ConfigError(_))
+    ));
+
+    inventory.version = inventory::INVENTORY_VERSION;
+    let empty = Inventory::from_csv(&inventory.to_csv()).unwrap();
+    assert!(empty.entries.is_empty());
+    assert!(Inventory::from_csv("id,public_key\n").is_err());
+
+    let entry: inventory::InventoryEntry = serde_json::from_value(serde_json::json!({
+        "kind": "key",
+        "id": "payments",
+        "curve": "secp256k1",
+        "signing_algorithm": "ecdsa",
+        "public_key": "0x04",
+        "namespace": ""
+    }))
+    .unwrap();
+    inventory.entries = vec![entry.clone(), entry];
+    assert!(matches!(
+        Inventory::from_csv(&inventory.to_csv()),
+        Err(ClientError::ConfigError(_))
+    ));
+}
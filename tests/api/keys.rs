@@ -1,5 +1,15 @@
+use base64::Engine;
+use futures::StreamExt;
 use quorum_vault_client::api;
+use quorum_vault_client::api::keys::patch::TagPatch;
+use quorum_vault_client::api::keys::query::{KeyIndex, TagQuery};
+use quorum_vault_client::api::keys::responses::KeyResponse;
 use quorum_vault_client::api::keys::KeyCryptoAlgorithm;
+use quorum_vault_client::error::ClientError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
 use web3::signing::keccak256;
 use wiremock::matchers::{body_json, method, path};
@@ -88,6 +98,124 @@ async fn test_create_key() {
     assert_eq!(key.version, 1);
 }
 
+#[tokio::test]
+async fn test_ensure_key() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let key = |id: &str, tags: serde_json::Value| {
+        serde_json::json!({
+            "request_id": "39fdefc5-1f30-b5fe-08ba-02dd75f96daa",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": {
+                "created_at": "2023-01-28T13:33:28.583408531Z",
+                "curve": "secp256k1",
+                "id": id,
+                "namespace": "",
+                "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+                "signing_algorithm": "ecdsa",
+                "tags": tags,
+                "updated_at": "2023-01-28T13:33:28.583408531Z",
+                "version": 1
+            },
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        })
+    };
+    let tags: HashMap<String, String> = [("env".to_string(), "prod".to_string())].into();
+
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys/existing"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(key("existing", serde_json::json!({ "env": "dev" }))),
+        )
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/existing"))
+        .and(body_json(serde_json::json!({ "tags": { "env": "prod" } })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(key("existing", serde_json::json!({ "env": "prod" }))),
+        )
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys"))
+        .and(body_json(serde_json::json!({
+            "id": "missing",
+            "signing_algorithm": "ecdsa",
+            "curve": "secp256k1",
+            "tags": { "env": "prod" }
+        })))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(key("missing", serde_json::json!({ "env": "prod" }))),
+        )
+        .mount(&mock)
+        .await;
+
+    let algorithm = KeyCryptoAlgorithm::Secp256k1;
+    let created = api::keys::ensure_key(
+        &vault_client,
+        "quorum",
+        "missing",
+        algorithm,
+        tags.clone(),
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(created.id, "missing");
+
+    let existing = api::keys::ensure_key(
+        &vault_client,
+        "quorum",
+        "existing",
+        algorithm,
+        tags.clone(),
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(existing.tags["env"], "dev");
+
+    let reconciled = api::keys::ensure_key(
+        &vault_client,
+        "quorum",
+        "existing",
+        algorithm,
+        tags.clone(),
+        true,
+    )
+    .await
+    .unwrap();
+    assert_eq!(reconciled.tags["env"], "prod");
+
+    let error = api::keys::ensure_key(
+        &vault_client,
+        "quorum",
+        "existing",
+        KeyCryptoAlgorithm::Babyjubjub,
+        tags,
+        false,
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(error, ClientError::Conflict(_)));
+}
+
 #[tokio::test]
 async fn test_read_key() {
     let mock = MockServer::start().await;
@@ -191,6 +319,210 @@ async fn test_get_list_keys() {
     assert_eq!(keys.keys, vec!["dd4b594d-4b89-480d-a8a8-01ed7e1f0140"]);
 }
 
+#[tokio::test]
+async fn test_stream_keys() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let envelope = |data: serde_json::Value| {
+        serde_json::json!({
+            "request_id": "e3d064d4-8a4d-e7d3-475b-8aa00f43e75a",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": data,
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        })
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(envelope(
+            serde_json::json!({ "keys": ["key-1", "key-2", "key-3", "key-4"] }),
+        )))
+        .mount(&mock)
+        .await;
+    for id in ["key-1", "key-2", "key-4"] {
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/quorum/keys/{id}")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(envelope(serde_json::json!({
+                    "created_at": "2023-01-28T13:33:28.583408531Z",
+                    "curve": "secp256k1",
+                    "id": id,
+                    "namespace": "",
+                    "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+                    "signing_algorithm": "ecdsa",
+                    "tags": {},
+                    "updated_at": "2023-01-28T13:33:28.583408531Z",
+                    "version": 1
+                }))),
+            )
+            .mount(&mock)
+            .await;
+    }
+
+    let keys: Vec<_> = api::keys::stream_keys(&vault_client, "quorum", 2)
+        .collect()
+        .await;
+
+    assert_eq!(keys.len(), 4);
+    assert_eq!(keys[0].as_ref().unwrap().id, "key-1");
+    assert_eq!(keys[1].as_ref().unwrap().id, "key-2");
+    assert!(keys[2].is_err());
+    assert_eq!(keys[3].as_ref().unwrap().id, "key-4");
+
+    // a failed listing ends the stream with its error
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let keys: Vec<_> = api::keys::stream_keys(&vault_client, "quorum", 2)
+        .collect()
+        .await;
+    assert_eq!(keys.len(), 1);
+    assert!(keys[0].is_err());
+}
+
+#[tokio::test]
+async fn test_find_keys() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let envelope = |data: serde_json::Value| {
+        serde_json::json!({
+            "request_id": "e3d064d4-8a4d-e7d3-475b-8aa00f43e75a",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": data,
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        })
+    };
+
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(envelope(
+            serde_json::json!({ "keys": ["key-1", "key-2", "key-3"] }),
+        )))
+        .mount(&mock)
+        .await;
+    let tags = [
+        (
+            "key-1",
+            serde_json::json!({ "env": "prod", "team": "payments" }),
+        ),
+        (
+            "key-2",
+            serde_json::json!({ "env": "production", "owner": "alice" }),
+        ),
+        ("key-3", serde_json::json!({ "env": "dev" })),
+    ];
+    for (id, tags) in tags {
+        Mock::given(method("GET"))
+            .and(path(format!("/v1/quorum/keys/{id}")))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(envelope(serde_json::json!({
+                    "created_at": "2023-01-28T13:33:28.583408531Z",
+                    "curve": "secp256k1",
+                    "id": id,
+                    "namespace": "",
+                    "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+                    "signing_algorithm": "ecdsa",
+                    "tags": tags,
+                    "updated_at": "2023-01-28T13:33:28.583408531Z",
+                    "version": 1
+                }))),
+            )
+            .mount(&mock)
+            .await;
+    }
+
+    let find = |query: TagQuery| {
+        let vault_client = &vault_client;
+        async move {
+            let keys = api::keys::find_keys(vault_client, "quorum", &query, 2, None)
+                .await
+                .unwrap();
+            keys.into_iter().map(|key| key.id).collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(find(TagQuery::equals("env", "prod")).await, vec!["key-1"]);
+    assert_eq!(find(TagQuery::exists("owner")).await, vec!["key-2"]);
+    assert_eq!(
+        find(TagQuery::prefix("env", "prod")).await,
+        vec!["key-1", "key-2"]
+    );
+    assert_eq!(
+        find(TagQuery::prefix("env", "prod").and(TagQuery::exists("team"))).await,
+        vec!["key-1"]
+    );
+    assert_eq!(
+        find(TagQuery::equals("env", "dev").or(TagQuery::exists("owner"))).await,
+        vec!["key-2", "key-3"]
+    );
+
+    // the index reads the keys once
+    let reads = || async { mock.received_requests().await.unwrap().len() };
+    let before = reads().await;
+    let index = KeyIndex::new(Duration::from_secs(60));
+    let search = |query: TagQuery| {
+        let (vault_client, index) = (&vault_client, &index);
+        async move {
+            let keys = api::keys::find_keys(vault_client, "quorum", &query, 2, Some(index))
+                .await
+                .unwrap();
+            keys.into_iter().map(|key| key.id).collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(search(TagQuery::equals("env", "prod")).await, vec!["key-1"]);
+    assert_eq!(search(TagQuery::exists("owner")).await, vec!["key-2"]);
+    assert_eq!(reads().await, before + 4);
+
+    // keys of another mount are not served from the index of this one
+    let other = api::keys::find_keys(
+        &vault_client,
+        "other",
+        &TagQuery::exists("env"),
+        2,
+        Some(&index),
+    )
+    .await
+    .unwrap();
+    assert!(other.is_empty());
+
+    index.remove("other", "key-1").await;
+    assert_eq!(search(TagQuery::equals("env", "prod")).await, vec!["key-1"]);
+    index.remove("quorum", "key-1").await;
+    assert!(search(TagQuery::equals("env", "prod")).await.is_empty());
+    index.invalidate().await;
+    assert_eq!(search(TagQuery::equals("env", "prod")).await, vec!["key-1"]);
+    assert_eq!(reads().await, before + 9);
+}
+
 #[tokio::test]
 async fn test_sign_data() {
     let mock = MockServer::start().await;
@@ -300,6 +632,62 @@ async fn test_sign_hash() {
     );
 }
 
+#[tokio::test]
+async fn test_sign_hash_batch() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let hashes: Vec<[u8; 32]> = (0..5u8).map(|i| keccak256(&[i])).collect();
+    for (i, hash) in hashes.iter().enumerate() {
+        let expected_request = serde_json::json!({
+            "data": base64::prelude::BASE64_URL_SAFE.encode(hash)
+        });
+        let response = serde_json::json!({
+            "request_id": "e81af2c4-4e4c-a640-0f8f-99ce3f7d486a",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": {
+                "signature": format!("signature-{i}")
+            },
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        });
+        // later requests answer first, the third one fails
+        let template = if i == 2 {
+            ResponseTemplate::new(500).set_body_json(serde_json::json!({ "errors": ["failed"] }))
+        } else {
+            ResponseTemplate::new(200).set_body_json(&response)
+        };
+        Mock::given(method("POST"))
+            .and(path("/v1/quorum/keys/some-id/sign"))
+            .and(body_json(&expected_request))
+            .respond_with(template.set_delay(Duration::from_millis(50 * (5 - i as u64))))
+            .mount(&mock)
+            .await;
+    }
+
+    let signatures =
+        api::keys::sign_hash_batch(&vault_client, "quorum", "some-id", hashes, 3).await;
+
+    assert_eq!(signatures.len(), 5);
+    for (i, signature) in signatures.iter().enumerate() {
+        match signature {
+            Ok(signature) => assert_eq!(signature.signature, format!("signature-{i}")),
+            Err(_) => assert_eq!(i, 2),
+        }
+    }
+    assert!(signatures[2].is_err());
+}
+
 #[tokio::test]
 async fn test_update_key_tags() {
     let mock = MockServer::start().await;
@@ -367,6 +755,154 @@ async fn test_update_key_tags() {
     assert_eq!(key.tags.get("kind").unwrap(), "wallet");
 }
 
+#[tokio::test]
+async fn test_patch_key_tags() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let key = |tags: serde_json::Value, version: u64| {
+        serde_json::json!({
+            "request_id": "39fdefc5-1f30-b5fe-08ba-02dd75f96daa",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": {
+                "created_at": "2023-01-28T13:33:28.583408531Z",
+                "curve": "secp256k1",
+                "id": "some-id",
+                "namespace": "",
+                "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+                "signing_algorithm": "ecdsa",
+                "tags": tags,
+                "updated_at": "2023-01-28T13:33:28.583408531Z",
+                "version": version
+            },
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        })
+    };
+    let current = key(
+        serde_json::json!({ "env": "dev", "owner": "alice", "team": "payments" }),
+        2,
+    );
+
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(&current))
+        .mount(&mock)
+        .await;
+    let expected_request = serde_json::json!({
+        "tags": { "env": "prod", "team": "payments" }
+    });
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .and(body_json(&expected_request))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(key(expected_request["tags"].clone(), 3)),
+        )
+        .mount(&mock)
+        .await;
+
+    let patch = TagPatch::new().set("env", "prod").remove("owner");
+    let patched = api::keys::patch_key_tags(&vault_client, "quorum", "some-id", &patch, 3)
+        .await
+        .unwrap();
+    assert_eq!(patched.tags.len(), 2);
+    assert_eq!(patched.tags["team"], "payments");
+    assert_eq!(requests(&mock, "GET").await, 1);
+
+    // the write of a key read before another update lands over it, which is a conflict
+    let mut stale: KeyResponse = serde_json::from_value(current["data"].clone()).unwrap();
+    stale.version = 1;
+    let error = api::keys::patch_key_tags_from(&vault_client, "quorum", &stale, &patch)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ClientError::Conflict(_)));
+    assert_eq!(requests(&mock, "POST").await, 2);
+}
+
+#[tokio::test]
+async fn test_patch_key_tags_concurrent_updates() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    // another client updates the key between every read and write, the version of every
+    // write skipping one
+    let versions = Arc::new(AtomicU64::new(1));
+    let key = |tags: serde_json::Value, version: u64| {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "request_id": "39fdefc5-1f30-b5fe-08ba-02dd75f96daa",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": {
+                "created_at": "2023-01-28T13:33:28.583408531Z",
+                "curve": "secp256k1",
+                "id": "some-id",
+                "namespace": "",
+                "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+                "signing_algorithm": "ecdsa",
+                "tags": tags,
+                "updated_at": "2023-01-28T13:33:28.583408531Z",
+                "version": version
+            },
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        }))
+    };
+    let read_versions = versions.clone();
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(move |_: &wiremock::Request| {
+            let version = read_versions.fetch_add(1, Ordering::SeqCst) + 1;
+            key(serde_json::json!({ "env": "dev" }), version)
+        })
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(move |_: &wiremock::Request| {
+            let version = versions.fetch_add(2, Ordering::SeqCst) + 2;
+            key(serde_json::json!({ "env": "prod" }), version)
+        })
+        .mount(&mock)
+        .await;
+
+    let patch = TagPatch::new().set("env", "prod");
+    let error = api::keys::patch_key_tags(&vault_client, "quorum", "some-id", &patch, 2)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ClientError::Conflict(_)));
+    assert_eq!(requests(&mock, "GET").await, 2);
+    assert_eq!(requests(&mock, "POST").await, 2);
+}
+
+/// Requests with `verb` received by `mock`.
+async fn requests(mock: &MockServer, verb: &str) -> usize {
+    mock.received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.method.as_ref() == verb)
+        .count()
+}
+
 #[tokio::test]
 async fn test_destroy_key() {
     let mock = MockServer::start().await;
@@ -1,3 +1,25 @@
+mod audit;
+mod auth;
+mod cache;
+#[cfg(all(feature = "cli", feature = "testing"))]
+mod cli;
+mod config;
 mod ethereum;
+mod failover;
+#[cfg(feature = "testing")]
+mod import;
+#[cfg(feature = "testing")]
+mod inventory;
 mod keys;
+#[cfg(all(feature = "proxy", feature = "testing"))]
+mod proxy;
+#[cfg(feature = "testing")]
+mod reconcile;
+mod retry;
+#[cfg(feature = "testing")]
+mod signer;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(all(feature = "web3signer", feature = "testing"))]
+mod web3signer;
 mod zksnarks;
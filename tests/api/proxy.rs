@@ -0,0 +1,135 @@
+use quorum_vault_client::api::ethereum::replacement::decode_signed_transaction;
+use quorum_vault_client::proxy::{router, SigningProxy};
+use quorum_vault_client::signer::{EthereumSigner, VaultSigner};
+use quorum_vault_client::testing::MockVaultServer;
+use serde_json::{json, Value};
+use std::net::TcpListener;
+use std::sync::Arc;
+use web3::types::{Address, U256};
+use wiremock::matchers::{body_partial_json, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mock_rpc(node: &MockServer, rpc_method: &str, result: Value) {
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": rpc_method })))
+        .respond_with(move |request: &wiremock::Request| {
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }))
+        })
+        .mount(node)
+        .await;
+}
+
+async fn rpc(url: &str, rpc_method: &str, params: Value) -> Value {
+    reqwest::Client::new()
+        .post(url)
+        .json(&json!({ "jsonrpc": "2.0", "id": 7, "method": rpc_method, "params": params }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_signing_proxy() {
+    let vault = MockVaultServer::start().await;
+    let signer = VaultSigner::new(vault.client(), "quorum");
+    let account = signer.create_account().await.unwrap();
+
+    let node = MockServer::start().await;
+    mock_rpc(&node, "eth_chainId", json!("0x539")).await;
+    mock_rpc(&node, "eth_getTransactionCount", json!("0x5")).await;
+    mock_rpc(&node, "eth_estimateGas", json!("0x5208")).await;
+    mock_rpc(&node, "eth_gasPrice", json!("0x3b9aca00")).await;
+    mock_rpc(&node, "eth_blockNumber", json!("0x10")).await;
+    mock_rpc(
+        &node,
+        "eth_sendRawTransaction",
+        json!(format!("0x{}", "ab".repeat(32))),
+    )
+    .await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let proxy = SigningProxy::new(signer, &node.uri()).unwrap();
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router(Arc::new(proxy)).into_make_service()),
+    );
+
+    let accounts = rpc(&url, "eth_accounts", json!([])).await;
+    assert_eq!(accounts["id"], 7);
+    let accounts: Vec<Address> = serde_json::from_value(accounts["result"].clone()).unwrap();
+    assert_eq!(accounts, vec![account.address]);
+
+    let block_number = rpc(&url, "eth_blockNumber", json!([])).await;
+    assert_eq!(block_number["result"], "0x10");
+
+    let to = Address::from_low_u64_be(0x1234);
+    let sent = rpc(
+        &url,
+        "eth_sendTransaction",
+        json!([{ "from": account.address, "to": to, "value": "0x3e8" }]),
+    )
+    .await;
+    assert_eq!(sent["id"], 7);
+    assert_eq!(sent["result"], format!("0x{}", "ab".repeat(32)));
+
+    let raw_request = node
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|request| serde_json::from_slice::<Value>(&request.body).unwrap())
+        .find(|body| body["method"] == "eth_sendRawTransaction")
+        .unwrap();
+    let raw = hex::decode(
+        raw_request["params"][0]
+            .as_str()
+            .unwrap()
+            .trim_start_matches("0x"),
+    )
+    .unwrap();
+    let decoded = decode_signed_transaction(&raw).unwrap();
+    assert_eq!(decoded.chain_id, 1337);
+    assert_eq!(decoded.transaction.from, account.address);
+    assert_eq!(decoded.transaction.to, Some(to));
+    assert_eq!(decoded.transaction.value, Some(U256::from(1000)));
+    assert_eq!(decoded.transaction.nonce, Some(U256::from(5)));
+    assert_eq!(decoded.transaction.gas, Some(U256::from(21000)));
+    assert_eq!(
+        decoded.transaction.gas_price,
+        Some(U256::from(1_000_000_000))
+    );
+
+    let unknown = rpc(
+        &url,
+        "eth_sendTransaction",
+        json!([{ "from": Address::from_low_u64_be(1), "to": to }]),
+    )
+    .await;
+    assert_eq!(unknown["error"]["code"], -32000);
+
+    let deployment = rpc(
+        &url,
+        "eth_sendTransaction",
+        json!([{ "from": account.address, "data": "0x6080" }]),
+    )
+    .await;
+    assert_eq!(deployment["error"]["code"], -32602);
+    let raw_transactions = node
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| {
+            serde_json::from_slice::<Value>(&request.body).unwrap()["method"]
+                == "eth_sendRawTransaction"
+        })
+        .count();
+    assert_eq!(raw_transactions, 1);
+}
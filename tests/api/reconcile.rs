@@ -0,0 +1,161 @@
+use quorum_vault_client::api;
+use quorum_vault_client::api::keys::KeyCryptoAlgorithm;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::reconcile::{self, Change, DesiredState};
+use quorum_vault_client::testing::MockVaultServer;
+use std::collections::HashMap;
+
+fn tags(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_reconcile_plan_and_apply() {
+    let server = MockVaultServer::start().await;
+    let client = server.client();
+    let secp256k1 = KeyCryptoAlgorithm::Secp256k1;
+    for (id, key_tags) in [
+        ("retagged", tags(&[("team", "payments"), ("env", "dev")])),
+        ("unchanged", tags(&[("team", "payments")])),
+        ("pruned", tags(&[("team", "payments")])),
+        ("other-team", tags(&[("team", "ops")])),
+    ] {
+        api::keys::create_key(&client, "quorum", id, secp256k1, key_tags)
+            .await
+            .unwrap();
+    }
+
+    let desired = DesiredState::from_yaml(
+        r#"
+keys:
+  - id: created
+    algorithm: babyjubjub
+    tags:
+      team: payments
+  - id: retagged
+    tags:
+      team: payments
+      env: prod
+  - id: unchanged
+    tags:
+      team: payments
+prune: true
+selector:
+  team: payments
+"#,
+    )
+    .unwrap();
+
+    let plan = reconcile::plan(&client, "quorum", &desired, 4)
+        .await
+        .unwrap();
+    assert_eq!(
+        plan.changes,
+        vec![
+            Change::Create {
+                id: "created".to_string(),
+                algorithm: KeyCryptoAlgorithm::Babyjubjub,
+                tags: tags(&[("team", "payments")]),
+            },
+            Change::Retag {
+                id: "retagged".to_string(),
+                from: tags(&[("team", "payments"), ("env", "dev")]),
+                to: tags(&[("team", "payments"), ("env", "prod")]),
+            },
+            Change::Delete {
+                id: "pruned".to_string()
+            },
+        ]
+    );
+    assert_eq!(
+        plan.to_string(),
+        "+ create created (babyjubjub) {team=payments}\n\
+         ~ retag retagged {env=dev, team=payments} -> {env=prod, team=payments}\n\
+         - delete pruned\n"
+    );
+
+    reconcile::apply(&client, "quorum", &plan).await.unwrap();
+    let plan = reconcile::plan(&client, "quorum", &desired, 4)
+        .await
+        .unwrap();
+    assert!(plan.is_empty());
+    assert_eq!(plan.to_string(), "no changes\n");
+
+    let mut keys = api::keys::list_keys(&client, "quorum").await.unwrap().keys;
+    keys.sort();
+    assert_eq!(keys, vec!["created", "other-team", "retagged", "unchanged"]);
+
+    // soft-deleted keys can be restored
+    api::keys::restore_key(&client, "quorum", "pruned")
+        .await
+        .unwrap();
+    api::keys::read_key(&client, "quorum", "pruned")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_reconcile_conflicts() {
+    let server = MockVaultServer::start().await;
+    let client = server.client();
+    api::keys::create_key(
+        &client,
+        "quorum",
+        "some-id",
+        KeyCryptoAlgorithm::Secp256k1,
+        HashMap::new(),
+    )
+    .await
+    .unwrap();
+
+    let desired = DesiredState::from_json(
+        r#"{ "keys": [{ "id": "some-id", "algorithm": "babyjubjub" }, { "id": "new" }] }"#,
+    )
+    .unwrap();
+    let plan = reconcile::plan(&client, "quorum", &desired, 4)
+        .await
+        .unwrap();
+    assert!(plan.has_conflicts());
+    let error = reconcile::apply(&client, "quorum", &plan)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ClientError::Conflict(_)));
+    // nothing applied
+    assert_eq!(
+        api::keys::list_keys(&client, "quorum").await.unwrap().keys,
+        vec!["some-id"]
+    );
+
+    let duplicated =
+        DesiredState::from_json(r#"{ "keys": [{ "id": "some-id" }, { "id": "some-id" }] }"#)
+            .unwrap();
+    let error = reconcile::plan(&client, "quorum", &duplicated, 4)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ClientError::ConfigError(_)));
+
+    assert!(DesiredState::from_json(r#"{ "keys": [], "unknown": true }"#).is_err());
+
+    // a key retagged between planning and applying is not overwritten
+    let retagged =
+        DesiredState::from_json(r#"{ "keys": [{ "id": "some-id", "tags": { "env": "prod" } }] }"#)
+            .unwrap();
+    let plan = reconcile::plan(&client, "quorum", &retagged, 4)
+        .await
+        .unwrap();
+    let tags = HashMap::from([("env".to_string(), "dev".to_string())]);
+    api::keys::update_key_tags(&client, "quorum", "some-id", tags)
+        .await
+        .unwrap();
+    let error = reconcile::apply(&client, "quorum", &plan)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, ClientError::Conflict(_)));
+    let key = api::keys::read_key(&client, "quorum", "some-id")
+        .await
+        .unwrap();
+    assert_eq!(key.tags["env"], "dev");
+}
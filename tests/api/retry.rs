@@ -0,0 +1,191 @@
+use quorum_vault_client::api;
+use quorum_vault_client::error::ClientError;
+use quorum_vault_client::retry::{RetryPolicy, RetryingSigner};
+use quorum_vault_client::signer::{KeySigner, VaultSigner};
+use std::time::Duration;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn client(address: &str) -> VaultClient {
+    VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(address)
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+fn policy() -> RetryPolicy {
+    RetryPolicy {
+        initial_backoff: Duration::from_millis(1),
+        ..RetryPolicy::default()
+    }
+}
+
+fn success(data: serde_json::Value) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(serde_json::json!({
+        "request_id": "b5efba4e-9ed6-5e53-66ab-c072a078e6f5",
+        "lease_id": "",
+        "renewable": false,
+        "lease_duration": 0,
+        "data": data,
+        "wrap_info": null,
+        "warnings": null,
+        "auth": null
+    }))
+}
+
+fn key() -> serde_json::Value {
+    serde_json::json!({
+        "created_at": "2023-01-28T13:33:28.583408531Z",
+        "curve": "secp256k1",
+        "id": "some-id",
+        "namespace": "",
+        "public_key": "BMDV2nRbw6iFuqs0RRF9cdhgvvHDoxmA4MPN0jomXTGhQIhPlCLMsnWFHaoeYihRUNnQ01CAftQUkAMol8G5SuU=",
+        "signing_algorithm": "ecdsa",
+        "tags": {},
+        "updated_at": "2023-01-28T13:33:28.583408531Z",
+        "version": 1
+    })
+}
+
+/// Answers `failures` times with `status` before succeeding.
+async fn mock_flaky(mock: &MockServer, verb: &str, route: &str, status: u16, failures: u64) {
+    Mock::given(method(verb))
+        .and(path(route))
+        .respond_with(
+            ResponseTemplate::new(status).set_body_json(serde_json::json!({
+                "errors": ["temporarily unavailable"]
+            })),
+        )
+        .up_to_n_times(failures)
+        .mount(mock)
+        .await;
+}
+
+async fn requests(mock: &MockServer) -> usize {
+    mock.received_requests().await.unwrap().len()
+}
+
+#[tokio::test]
+async fn test_retry_reads() {
+    let mock = MockServer::start().await;
+    mock_flaky(&mock, "GET", "/v1/quorum/keys/some-id", 503, 2).await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys/some-id"))
+        .respond_with(success(key()))
+        .mount(&mock)
+        .await;
+
+    let signer = RetryingSigner::new(VaultSigner::new(client(&mock.uri()), "quorum"), policy());
+    let key = signer.read_key("some-id").await.unwrap();
+    assert_eq!(key.id, "some-id");
+    assert_eq!(requests(&mock).await, 3);
+
+    // not found is final
+    let error = signer.read_key("other-id").await.unwrap_err();
+    assert_eq!(error.status(), Some(404));
+    assert_eq!(requests(&mock).await, 4);
+
+    // reads and lists with a client are retried on their own
+    mock_flaky(&mock, "GET", "/v1/quorum/keys", 503, 2).await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/keys"))
+        .respond_with(success(serde_json::json!({ "keys": ["some-id"] })))
+        .mount(&mock)
+        .await;
+    let keys = api::keys::list_keys(&client(&mock.uri()), "quorum")
+        .await
+        .unwrap();
+    assert_eq!(keys.keys, ["some-id"]);
+    assert_eq!(requests(&mock).await, 7);
+}
+
+#[tokio::test]
+async fn test_retry_gives_up() {
+    let mock = MockServer::start().await;
+    mock_flaky(&mock, "GET", "/v1/quorum/ethereum/accounts", 502, 5).await;
+
+    let client = client(&mock.uri());
+    let error = api::ethereum::list_accounts_with_retry(&client, "quorum", &policy())
+        .await
+        .unwrap_err();
+    assert_eq!(ClientError::from(error).status(), Some(502));
+    assert_eq!(requests(&mock).await, 3);
+
+    // connection refused
+    let address = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let client = self::client(&address);
+    let never = RetryPolicy::never();
+    let mut attempts = 0;
+    let error = policy()
+        .run(|| {
+            attempts += 1;
+            api::keys::list_keys_with_retry(&client, "quorum", &never)
+        })
+        .await
+        .unwrap_err();
+    assert!(error.is_connection_error());
+    assert_eq!(attempts, 3);
+}
+
+#[tokio::test]
+async fn test_retry_signing_opt_in() {
+    let mock = MockServer::start().await;
+    let signature = serde_json::json!({ "signature": "c2lnbmF0dXJl" });
+    mock_flaky(&mock, "POST", "/v1/quorum/keys/some-id/sign", 500, 1).await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/some-id/sign"))
+        .respond_with(success(signature))
+        .mount(&mock)
+        .await;
+
+    let signer = RetryingSigner::new(VaultSigner::new(client(&mock.uri()), "quorum"), policy());
+    let error = signer.sign_data("some-id", b"data").await.unwrap_err();
+    assert_eq!(error.status(), Some(500));
+    assert_eq!(requests(&mock).await, 1);
+
+    mock.reset().await;
+    mock_flaky(&mock, "POST", "/v1/quorum/keys/some-id/sign", 500, 1).await;
+    Mock::given(method("POST"))
+        .and(path("/v1/quorum/keys/some-id/sign"))
+        .respond_with(success(serde_json::json!({ "signature": "c2lnbmF0dXJl" })))
+        .mount(&mock)
+        .await;
+    let signer = RetryingSigner::new(
+        VaultSigner::new(client(&mock.uri()), "quorum"),
+        RetryPolicy {
+            retry_signing: true,
+            ..policy()
+        },
+    );
+    let signed = signer.sign_data("some-id", b"data").await.unwrap();
+    assert_eq!(signed.signature, "c2lnbmF0dXJl");
+    assert_eq!(requests(&mock).await, 2);
+}
+
+#[test]
+fn test_backoff() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(300),
+        jitter: false,
+        ..RetryPolicy::default()
+    };
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(300));
+    assert_eq!(policy.backoff(40), Duration::from_millis(300));
+
+    let jittered = RetryPolicy {
+        jitter: true,
+        ..policy
+    };
+    assert!((0..20).all(|_| jittered.backoff(2) <= Duration::from_millis(200)));
+}
@@ -0,0 +1,143 @@
+use base64::Engine;
+use quorum_vault_client::api::keys::KeyCryptoAlgorithm;
+use quorum_vault_client::signer::{
+    babyjubjub, EthereumSigner, InMemorySigner, KeySigner, VaultSigner, ZkSigner,
+};
+use quorum_vault_client::testing::MockVaultServer;
+use std::collections::HashMap;
+use std::str::FromStr;
+use web3::signing::{keccak256, recover};
+use web3::types::{Address, TransactionRequest, U256};
+
+async fn exercise(signer: &(impl EthereumSigner + KeySigner + ZkSigner)) {
+    let account = signer.create_account().await.unwrap();
+    let imported = signer
+        .import_private_key("0a1232595b77534d99364bfde13383accbcb40775967a7eacd15d355c96288a5")
+        .await
+        .unwrap();
+    assert_eq!(
+        imported.address,
+        Address::from_str("0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F").unwrap()
+    );
+    let mut expected = vec![account.address, imported.address];
+    expected.sort();
+    assert_eq!(signer.list_accounts().await.unwrap().keys, expected);
+    assert_eq!(
+        signer
+            .read_account(account.address)
+            .await
+            .unwrap()
+            .public_key,
+        account.public_key
+    );
+    assert!(signer.read_account(Address::zero()).await.is_err());
+
+    let transaction = TransactionRequest::builder()
+        .from(account.address)
+        .to(imported.address)
+        .value(U256::from(1000))
+        .nonce(U256::from(3))
+        .build();
+    let signed = signer.sign_transaction(1, transaction).await.unwrap();
+    assert_eq!(signed.signature.len(), 2 + 65 * 2);
+
+    let signature = signer.sign(imported.address, b"hello").await.unwrap();
+    let signature = hex::decode(signature.signature.trim_start_matches("0x")).unwrap();
+    let signer_address =
+        recover(&keccak256(b"hello"), &signature[..64], signature[64] as i32).unwrap();
+    assert_eq!(signer_address, imported.address);
+
+    let key = signer
+        .create_key("key", KeyCryptoAlgorithm::Babyjubjub, HashMap::new())
+        .await
+        .unwrap();
+    assert!(signer
+        .create_key("key", KeyCryptoAlgorithm::Secp256k1, HashMap::new())
+        .await
+        .is_err());
+    let signature = signer.sign_data("key", b"hello").await.unwrap();
+    let signature: [u8; 64] = base64::prelude::BASE64_URL_SAFE
+        .decode(signature.signature)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let public_key: [u8; 32] = base64::prelude::BASE64_URL_SAFE
+        .decode(key.public_key)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert!(babyjubjub::verify(
+        &public_key,
+        &keccak256(b"hello"),
+        &signature
+    ));
+    let tags: HashMap<String, String> = [("env".to_string(), "dev".to_string())]
+        .into_iter()
+        .collect();
+    let updated = signer.update_key_tags("key", tags.clone()).await.unwrap();
+    assert_eq!(updated.tags, tags);
+    assert_eq!(updated.version, 2);
+    assert_eq!(signer.list_keys().await.unwrap().keys, vec!["key"]);
+    signer.destroy_key("key").await.unwrap();
+    assert!(signer.read_key("key").await.is_err());
+
+    let zk = signer.create_zksnarks_account().await.unwrap();
+    assert_eq!(
+        signer.list_zksnarks_accounts().await.unwrap().keys,
+        vec![zk.public_key.clone()]
+    );
+    let signature = signer
+        .zksnarks_sign_hash(&zk.public_key, [7u8; 32])
+        .await
+        .unwrap();
+    let signature: [u8; 64] = hex::decode(signature.signature.trim_start_matches("0x"))
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let public_key: [u8; 32] = hex::decode(zk.public_key.trim_start_matches("0x"))
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert!(babyjubjub::verify(&public_key, &[7u8; 32], &signature));
+}
+
+#[tokio::test]
+async fn test_in_memory_signer() {
+    exercise(&InMemorySigner::new()).await;
+}
+
+#[tokio::test]
+async fn test_vault_signer() {
+    let server = MockVaultServer::start().await;
+    exercise(&VaultSigner::new(server.client(), "quorum")).await;
+}
+
+#[tokio::test]
+async fn test_signers_agree() {
+    let memory = InMemorySigner::new();
+    let server = MockVaultServer::start().await;
+    let vault = VaultSigner::new(server.client(), "quorum");
+    let account = memory.create_account().await.unwrap();
+    let private_key = memory.ethereum_private_key(account.address).unwrap();
+    vault.import_private_key(&private_key).await.unwrap();
+
+    let transaction = TransactionRequest::builder()
+        .from(account.address)
+        .to(Address::zero())
+        .value(U256::from(1))
+        .gas(U256::from(30000))
+        .data(vec![1, 2, 3].into())
+        .build();
+    assert_eq!(
+        memory
+            .sign_transaction(5, transaction.clone())
+            .await
+            .unwrap()
+            .signature,
+        vault
+            .sign_transaction(5, transaction)
+            .await
+            .unwrap()
+            .signature
+    );
+}
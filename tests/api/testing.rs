@@ -0,0 +1,380 @@
+use base64::Engine;
+use quorum_vault_client::api;
+use quorum_vault_client::api::ethereum::responses::{
+    EthereumAccountResponse, EthereumSignTransactionResponse,
+};
+use quorum_vault_client::api::keys::responses::KeyResponse;
+use quorum_vault_client::api::keys::KeyCryptoAlgorithm;
+use quorum_vault_client::testing::{babyjubjub, fixtures, MockVaultServer};
+use rlp::RlpStream;
+use std::collections::HashMap;
+use std::str::FromStr;
+use vaultrs::client::{VaultClient, VaultClientSettingsBuilder};
+use web3::signing::{keccak256, recover};
+use web3::types::{Address, TransactionRequest, U256};
+use wiremock::matchers::body_json;
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn test_mock_server_ethereum() {
+    let server = MockVaultServer::start().await;
+    let client = server.client();
+
+    let created = api::ethereum::create_account(&client, "quorum")
+        .await
+        .unwrap();
+    let imported = api::ethereum::import_private_key(
+        &client,
+        "quorum",
+        "0a1232595b77534d99364bfde13383accbcb40775967a7eacd15d355c96288a5",
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        imported.address,
+        Address::from_str("0xeCB96104c306DF32Aed607EF0B8a44cC94BE782F").unwrap()
+    );
+    assert_eq!(imported.public_key, "0x046b5ae5ec570abb9c4c50746d08fb63c911641170581b07f5f531a993b8b6cbeced5f8e3de3f4c7416a7661ed2c7eef8fea416c62df47ec43896af26086b87594");
+
+    let accounts = api::ethereum::list_accounts(&client, "quorum")
+        .await
+        .unwrap();
+    let mut expected = vec![created.address, imported.address];
+    expected.sort();
+    assert_eq!(accounts.keys, expected);
+    assert!(api::ethereum::list_accounts(&client, "other")
+        .await
+        .unwrap()
+        .keys
+        .is_empty());
+
+    let read = api::ethereum::read_account(&client, "quorum", created.address)
+        .await
+        .unwrap();
+    assert_eq!(read.public_key, created.public_key);
+
+    let to = Address::from_str("0x1daBe0aCaAA4D1F81b9b43Eaf51C8439378231a0").unwrap();
+    let mut tx: TransactionRequest = TransactionRequest::builder()
+        .from(created.address)
+        .to(to)
+        .value(U256::from(1000))
+        .gas(U256::from(21000))
+        .nonce(U256::from(7))
+        .build();
+    tx.gas_price = Some(U256::from(1));
+
+    let signature = api::ethereum::sign_transaction(&client, "quorum", 1337, tx)
+        .await
+        .unwrap();
+
+    let mut stream = RlpStream::new_list(9);
+    stream.append(&7u64);
+    stream.append(&U256::from(1));
+    stream.append(&21000u64);
+    stream.append(&to);
+    stream.append(&U256::from(1000));
+    stream.append(&Vec::<u8>::new());
+    stream.append(&1337u64);
+    stream.append(&0u8);
+    stream.append(&0u8);
+    let signature = hex::decode(signature.signature.trim_start_matches("0x")).unwrap();
+    let signer = recover(
+        &keccak256(&stream.out()),
+        &signature[..64],
+        signature[64] as i32,
+    )
+    .unwrap();
+    assert_eq!(signer, created.address);
+
+    let signature = api::ethereum::sign(&client, "quorum", imported.address, b"Hello, world!")
+        .await
+        .unwrap();
+    let signature = hex::decode(signature.signature.trim_start_matches("0x")).unwrap();
+    let signer = recover(
+        &keccak256(b"Hello, world!"),
+        &signature[..64],
+        signature[64] as i32,
+    )
+    .unwrap();
+    assert_eq!(signer, imported.address);
+
+    assert!(
+        api::ethereum::read_account(&client, "quorum", Address::zero())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_mock_server_keys() {
+    let server = MockVaultServer::start().await;
+    let client = server.client();
+    let tags: HashMap<String, String> = [("env".to_string(), "dev".to_string())]
+        .into_iter()
+        .collect();
+
+    let key = api::keys::create_key(
+        &client,
+        "quorum",
+        "ecdsa-key",
+        KeyCryptoAlgorithm::Secp256k1,
+        tags.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(key.curve, "secp256k1");
+    assert_eq!(key.signing_algorithm, "ecdsa");
+    assert_eq!(key.tags, tags);
+    assert_eq!(key.version, 1);
+
+    assert!(api::keys::create_key(
+        &client,
+        "quorum",
+        "ecdsa-key",
+        KeyCryptoAlgorithm::Secp256k1,
+        HashMap::new(),
+    )
+    .await
+    .is_err());
+
+    let imported = api::keys::import_key(
+        &client,
+        "quorum",
+        "imported-key",
+        KeyCryptoAlgorithm::Secp256k1,
+        HashMap::new(),
+        "1s1iv38t1CBEGJOHo2ah7yrrhFoUZKiiU4aUq_59B3A=",
+    )
+    .await
+    .unwrap();
+    let secret = base64::prelude::BASE64_URL_SAFE
+        .decode("1s1iv38t1CBEGJOHo2ah7yrrhFoUZKiiU4aUq_59B3A=")
+        .unwrap();
+    let expected = secp256k1::PublicKey::from_secret_key(
+        &secp256k1::Secp256k1::new(),
+        &secp256k1::SecretKey::from_slice(&secret).unwrap(),
+    );
+    assert_eq!(
+        imported.public_key,
+        base64::prelude::BASE64_URL_SAFE.encode(expected.serialize_uncompressed())
+    );
+
+    let signature = api::keys::sign(&client, "quorum", "ecdsa-key", b"Hello, world!")
+        .await
+        .unwrap();
+    let signature = base64::prelude::BASE64_URL_SAFE
+        .decode(signature.signature)
+        .unwrap();
+    let public_key = base64::prelude::BASE64_URL_SAFE
+        .decode(&key.public_key)
+        .unwrap();
+    let secp = secp256k1::Secp256k1::verification_only();
+    secp.verify_ecdsa(
+        &secp256k1::Message::from_slice(&keccak256(b"Hello, world!")).unwrap(),
+        &secp256k1::ecdsa::Signature::from_compact(&signature).unwrap(),
+        &secp256k1::PublicKey::from_slice(&public_key).unwrap(),
+    )
+    .unwrap();
+
+    let eddsa = api::keys::create_key(
+        &client,
+        "quorum",
+        "eddsa-key",
+        KeyCryptoAlgorithm::Babyjubjub,
+        HashMap::new(),
+    )
+    .await
+    .unwrap();
+    let hash = keccak256(b"Hello, world!");
+    let signature = api::keys::sign_hash(&client, "quorum", "eddsa-key", hash)
+        .await
+        .unwrap();
+    let signature: [u8; 64] = base64::prelude::BASE64_URL_SAFE
+        .decode(signature.signature)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let public_key: [u8; 32] = base64::prelude::BASE64_URL_SAFE
+        .decode(&eddsa.public_key)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert!(babyjubjub::verify(&public_key, &hash, &signature));
+    assert!(!babyjubjub::verify(&public_key, &[0u8; 32], &signature));
+
+    let updated = api::keys::update_key_tags(&client, "quorum", "ecdsa-key", HashMap::new())
+        .await
+        .unwrap();
+    assert!(updated.tags.is_empty());
+    assert_eq!(updated.version, 2);
+    assert_eq!(updated.created_at, key.created_at);
+
+    let keys = api::keys::list_keys(&client, "quorum").await.unwrap();
+    assert_eq!(keys.keys, vec!["ecdsa-key", "eddsa-key", "imported-key"]);
+
+    api::keys::destroy_key(&client, "quorum", "ecdsa-key")
+        .await
+        .unwrap();
+    assert!(api::keys::read_key(&client, "quorum", "ecdsa-key")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_mock_server_zksnarks() {
+    let server = MockVaultServer::start().await;
+    let client = server.client();
+
+    let account = api::zksnarks::create_zksnarks_account(&client, "quorum")
+        .await
+        .unwrap();
+    assert_eq!(account.curve, "babyjubjub");
+    assert_eq!(account.signing_algorithm, "eddsa");
+
+    let accounts = api::zksnarks::list_zksnarks_accounts(&client, "quorum")
+        .await
+        .unwrap();
+    assert_eq!(accounts.keys, vec![account.public_key.clone()]);
+
+    let read = api::zksnarks::read_zksnarks_account(&client, "quorum", &account.public_key)
+        .await
+        .unwrap();
+    assert_eq!(read.public_key, account.public_key);
+
+    let signature =
+        api::zksnarks::zksnarks_sign(&client, "quorum", &account.public_key, b"Hello, world!")
+            .await
+            .unwrap();
+    let signature: [u8; 64] = hex::decode(signature.signature.trim_start_matches("0x"))
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let public_key: [u8; 32] = hex::decode(account.public_key.trim_start_matches("0x"))
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert!(babyjubjub::verify(
+        &public_key,
+        &keccak256(b"Hello, world!"),
+        &signature
+    ));
+}
+
+fn fixture_client(server: &MockServer) -> VaultClient {
+    VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(server.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_fixtures_keys() {
+    let server = MockServer::start().await;
+    let client = fixture_client(&server);
+    let key = KeyResponse {
+        created_at: "2023-01-09T14:30:32.536415Z".to_string(),
+        curve: "secp256k1".to_string(),
+        id: "some-id".to_string(),
+        namespace: "".to_string(),
+        public_key: "BIGxldAqsi2XKmyw63wFHhZTzibTCQ1dJC0Wc6zHhKQ=".to_string(),
+        signing_algorithm: "ecdsa".to_string(),
+        tags: [("env".to_string(), "dev".to_string())]
+            .into_iter()
+            .collect(),
+        updated_at: "2023-01-09T14:30:32.536415Z".to_string(),
+        version: 1,
+    };
+
+    fixtures::keys::mock_create_key("quorum", &key)
+        .expect(1)
+        .mount(&server)
+        .await;
+    fixtures::keys::update_key_tags("quorum", "some-id")
+        .and(body_json(serde_json::json!({ "tags": {} })))
+        .respond_with(fixtures::permission_denied())
+        .mount(&server)
+        .await;
+    fixtures::keys::read_key("quorum", "missing")
+        .respond_with(fixtures::not_found())
+        .mount(&server)
+        .await;
+    fixtures::keys::mock_destroy_key("quorum", "some-id")
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let created = api::keys::create_key(
+        &client,
+        "quorum",
+        "some-id",
+        KeyCryptoAlgorithm::Secp256k1,
+        key.tags.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(created.id, key.id);
+    assert_eq!(created.public_key, key.public_key);
+    assert_eq!(created.tags, key.tags);
+
+    let error = api::keys::update_key_tags(&client, "quorum", "some-id", HashMap::new())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        vaultrs::error::ClientError::APIError { code: 403, ref errors } if errors == &["permission denied"]
+    ));
+
+    let error = api::keys::read_key(&client, "quorum", "missing")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        vaultrs::error::ClientError::APIError { code: 404, .. }
+    ));
+
+    api::keys::destroy_key(&client, "quorum", "some-id")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_fixtures_ethereum() {
+    let server = MockServer::start().await;
+    let client = fixture_client(&server);
+    let account = EthereumAccountResponse {
+        address: Address::from_str("0x8d3113e29CB92F44F1762E52D2a0276509b36b82").unwrap(),
+        compressed_public_key: "0x02b9f8b5f3b6c0b7c3c5e5e5c9f0b6b9".to_string(),
+        public_key: "0x04b9f8b5f3b6c0b7c3c5e5e5c9f0b6b9".to_string(),
+        namespace: "".to_string(),
+    };
+    let signature = EthereumSignTransactionResponse {
+        signature: "0xf8628080825208".to_string(),
+    };
+
+    fixtures::ethereum::mock_read_account("quorum", &account)
+        .mount(&server)
+        .await;
+    fixtures::ethereum::mock_sign_transaction("quorum", account.address, &signature)
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let read = api::ethereum::read_account(&client, "quorum", account.address)
+        .await
+        .unwrap();
+    assert_eq!(read.public_key, account.public_key);
+
+    let tx = TransactionRequest::builder()
+        .from(account.address)
+        .to(Address::zero())
+        .build();
+    let signed = api::ethereum::sign_transaction(&client, "quorum", 1, tx)
+        .await
+        .unwrap();
+    assert_eq!(signed.signature, signature.signature);
+}
@@ -0,0 +1,150 @@
+use base64::Engine;
+use quorum_vault_client::api::keys::KeyCryptoAlgorithm;
+use quorum_vault_client::signer::{EthereumSigner, KeySigner, VaultSigner};
+use quorum_vault_client::testing::MockVaultServer;
+use quorum_vault_client::web3signer::{router, Web3Signer};
+use std::collections::HashMap;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+use web3::signing::{keccak256, recover};
+
+#[tokio::test]
+async fn test_web3signer() {
+    let server = MockVaultServer::start().await;
+    let signer = VaultSigner::new(server.client(), "quorum");
+    let account = signer.create_account().await.unwrap();
+    signer
+        .create_key("ecdsa", KeyCryptoAlgorithm::Secp256k1, HashMap::new())
+        .await
+        .unwrap();
+    signer
+        .create_key("eddsa", KeyCryptoAlgorithm::Babyjubjub, HashMap::new())
+        .await
+        .unwrap();
+    let key_address = {
+        let key = signer.read_key("ecdsa").await.unwrap();
+        let public_key = base64::prelude::BASE64_URL_SAFE
+            .decode(key.public_key)
+            .unwrap();
+        web3::types::Address::from_slice(&keccak256(&public_key[1..])[12..])
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = router(Arc::new(
+        Web3Signer::new(signer).with_refresh_interval(Duration::from_secs(60)),
+    ));
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    let http = reqwest::Client::new();
+
+    let upcheck = http.get(format!("{url}/upcheck")).send().await.unwrap();
+    assert!(upcheck.status().is_success());
+    assert_eq!(upcheck.text().await.unwrap(), "OK");
+
+    let public_keys: Vec<String> = http
+        .get(format!("{url}/api/v1/eth1/publicKeys"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(public_keys.len(), 2);
+    let account_key = format!("0x{}", &account.public_key[4..]);
+    assert!(public_keys.contains(&account_key));
+
+    let data = b"hello web3signer";
+    for public_key in &public_keys {
+        let response = http
+            .post(format!("{url}/api/v1/eth1/sign/{public_key}"))
+            .json(&serde_json::json!({ "data": format!("0x{}", hex::encode(data)) }))
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        let signature = response.text().await.unwrap();
+        let signature = hex::decode(signature.trim_start_matches("0x")).unwrap();
+        assert_eq!(signature.len(), 65);
+        assert!(signature[64] == 27 || signature[64] == 28);
+        let signer = recover(
+            &keccak256(data),
+            &signature[..64],
+            signature[64] as i32 - 27,
+        )
+        .unwrap();
+        let expected = if public_key == &account_key {
+            account.address
+        } else {
+            key_address
+        };
+        assert_eq!(signer, expected);
+    }
+
+    let missing = http
+        .post(format!("{url}/api/v1/eth1/sign/0x{}", "00".repeat(64)))
+        .json(&serde_json::json!({ "data": "0x00" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), 404);
+    let invalid = http
+        .post(format!("{url}/api/v1/eth1/sign/0x1234"))
+        .json(&serde_json::json!({ "data": "0x00" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(invalid.status(), 404);
+
+    // unknown public keys list the accounts again at most once per refresh interval
+    let created = VaultSigner::new(server.client(), "quorum")
+        .create_account()
+        .await
+        .unwrap();
+    let sign = |public_key: String| {
+        http.post(format!("{url}/api/v1/eth1/sign/{public_key}"))
+            .json(&serde_json::json!({ "data": "0x00" }))
+            .send()
+    };
+    let response = sign(format!("0x{}", &created.public_key[4..]))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 404);
+}
+
+#[tokio::test]
+async fn test_web3signer_errors() {
+    let address = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+    let client = vaultrs::client::VaultClient::new(
+        vaultrs::client::VaultClientSettingsBuilder::default()
+            .address(address)
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let app = router(Arc::new(Web3Signer::new(VaultSigner::new(
+        client, "quorum",
+    ))));
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+
+    // failures are not detailed to the caller
+    let response = reqwest::get(format!("{url}/api/v1/eth1/publicKeys"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 500);
+    assert_eq!(response.text().await.unwrap(), "internal error");
+}
@@ -246,3 +246,61 @@ async fn test_sign_hash() {
 
     assert_eq!(signature.signature, "0xac34541ff103beac043f2525d756c9a5f4288be4910c33f49c4fcea69b766ca6011b28e6ad62a1a3eddf2cc08ca7265553c175ffa60982616fa4facaf5f87d4a");
 }
+
+#[tokio::test]
+async fn test_ensure_zksnarks_account() {
+    let mock = MockServer::start().await;
+    let vault_client = VaultClient::new(
+        VaultClientSettingsBuilder::default()
+            .address(mock.uri())
+            .token("s.1234567890abcdef")
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    let account = |curve: &str, signing_algorithm: &str| {
+        serde_json::json!({
+            "request_id": "e81af2c4-4e4c-a640-0f8f-99ce3f7d486a",
+            "lease_id": "",
+            "renewable": false,
+            "lease_duration": 0,
+            "data": {
+                "curve": curve,
+                "namespace": "",
+                "public_key": "0x7e8249b895434a1b02aade22033b887620ab5e756aa106d415ff33ace9048626",
+                "signing_algorithm": signing_algorithm
+            },
+            "wrap_info": null,
+            "warnings": null,
+            "auth": null
+        })
+    };
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/zk-snarks/accounts/prover"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(account("babyjubjub", "eddsa")))
+        .mount(&mock)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/v1/quorum/zk-snarks/accounts/signer"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(account("secp256k1", "ecdsa")))
+        .mount(&mock)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(0)
+        .mount(&mock)
+        .await;
+
+    let account = api::zksnarks::ensure_zksnarks_account(&vault_client, "quorum", "prover")
+        .await
+        .unwrap();
+    assert_eq!(account.curve, "babyjubjub");
+    let error = api::zksnarks::ensure_zksnarks_account(&vault_client, "quorum", "signer")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        quorum_vault_client::error::ClientError::Conflict(_)
+    ));
+}